@@ -0,0 +1,429 @@
+//! Decoding of many telegrams at once, either the `.hex` files of a directory
+//! or a log with one telegram per line, followed by a summary.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use m_bus_parser::frames::{Frame, FrameError};
+use m_bus_parser::user_data::data_information::DataInformationError;
+use m_bus_parser::user_data::libmbus_xml::XmlError;
+use m_bus_parser::user_data::value_information::ValueInformationError;
+use m_bus_parser::user_data::variable_user_data::{DataRecordError, VariableUserDataError};
+use m_bus_parser::user_data::{ApplicationLayerError, UserDataBlock};
+use walkdir::WalkDir;
+
+use crate::format::{self, Format, Source, Written};
+use crate::{decode_input, parse, CliError};
+
+/// The counts of decoded and failed telegrams
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    decoded: usize,
+    failed: usize,
+    errors: BTreeMap<String, usize>,
+    /// decoded and failed telegrams per manufacturer
+    manufacturers: BTreeMap<String, (usize, usize)>,
+}
+
+impl Summary {
+    fn add(&mut self, input: &[u8], result: &Result<(), CliError>) {
+        let manufacturer = self
+            .manufacturers
+            .entry(manufacturer(input).unwrap_or_else(|| "unknown".to_string()))
+            .or_default();
+        match result {
+            Ok(()) => {
+                self.decoded += 1;
+                manufacturer.0 += 1;
+            }
+            Err(error) => {
+                self.failed += 1;
+                manufacturer.1 += 1;
+                *self.errors.entry(error_kind(error)).or_default() += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Decoded {} of {} telegrams, {} failed",
+            self.decoded,
+            self.decoded + self.failed,
+            self.failed
+        )?;
+        if !self.errors.is_empty() {
+            writeln!(f, "\nFailures by error kind:")?;
+            for (kind, count) in &self.errors {
+                writeln!(f, "{:>8}  {}", count, kind)?;
+            }
+        }
+        if !self.manufacturers.is_empty() {
+            writeln!(f, "\nManufacturer   Decoded    Failed")?;
+            for (manufacturer, (decoded, failed)) in &self.manufacturers {
+                writeln!(f, "{:12} {:>9} {:>9}", manufacturer, decoded, failed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decodes every telegram below `path` and writes it to `output`, failures
+/// are reported to `errors` as they occur
+pub fn run(
+    path: &str,
+    format: Format,
+    output: &mut impl Write,
+    errors: &mut impl Write,
+) -> Result<Summary, CliError> {
+    let mut batch = Batch {
+        format,
+        written: Written::default(),
+        summary: Summary::default(),
+    };
+    let read_error = |error| CliError::Read {
+        path: path.to_string(),
+        error,
+    };
+
+    if Path::new(path).is_dir() {
+        let files = WalkDir::new(path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "hex"));
+        for entry in files {
+            let name = entry.path().display().to_string();
+            let input = fs::read(entry.path()).map_err(|error| CliError::Read {
+                path: name.clone(),
+                error,
+            });
+            let source = Source {
+                name: &name,
+                timestamp: None,
+            };
+            batch.decode(&source, input.and_then(decode_input), output, errors)?;
+        }
+    } else {
+        let log = BufReader::new(File::open(path).map_err(read_error)?);
+        for (index, line) in log.lines().enumerate() {
+            let line = line.map_err(read_error)?;
+            let Some((timestamp, telegram)) = split_log_line(&line) else {
+                continue;
+            };
+            let name = format!("{}:{}", path, index + 1);
+            let source = Source {
+                name: &name,
+                timestamp,
+            };
+            let digits: String = telegram.split_whitespace().collect();
+            let input = if digits.is_empty() {
+                Err(CliError::NoTelegram)
+            } else {
+                hex::decode(digits).map_err(CliError::Hex)
+            };
+            batch.decode(&source, input, output, errors)?;
+        }
+    }
+    let end = format::finish(batch.format, &batch.written);
+    output
+        .write_all(end.as_bytes())
+        .map_err(|error| CliError::Write { error })?;
+    Ok(batch.summary)
+}
+
+struct Batch {
+    format: Format,
+    written: Written,
+    summary: Summary,
+}
+
+impl Batch {
+    fn decode(
+        &mut self,
+        source: &Source,
+        input: Result<Vec<u8>, CliError>,
+        output: &mut impl Write,
+        errors: &mut impl Write,
+    ) -> Result<(), CliError> {
+        let (input, result) = match input {
+            Ok(input) => {
+                let result = parse(&input).and_then(|report| {
+                    format::render_from(&report, self.format, Some(source), &mut self.written)
+                });
+                (input, result)
+            }
+            Err(error) => (Vec::new(), Err(error)),
+        };
+        let write_error = |error| CliError::Write { error };
+        let result = match result {
+            Ok(text) => {
+                output.write_all(text.as_bytes()).map_err(write_error)?;
+                Ok(())
+            }
+            Err(error) => {
+                writeln!(errors, "error: {}: {}", source.name, error).map_err(write_error)?;
+                Err(error)
+            }
+        };
+        self.summary.add(&input, &result);
+        Ok(())
+    }
+}
+
+/// A log line is a telegram in hex, optionally behind an ISO 8601 timestamp
+/// like `2024-05-01T12:00:00Z` or `2024-05-01 12:00:00`. Empty lines and
+/// lines starting with `#` are skipped.
+fn split_log_line(line: &str) -> Option<(Option<&str>, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    // the timestamp consists of the leading words which are no hex bytes
+    let mut timestamp_end = 0;
+    for word in line.split_whitespace() {
+        if word.chars().all(|c| c.is_ascii_hexdigit()) {
+            break;
+        }
+        let start = word.as_ptr() as usize - line.as_ptr() as usize;
+        timestamp_end = start + word.len();
+    }
+    match timestamp_end {
+        0 => Some((None, line)),
+        end => Some((Some(&line[..end]), line[end..].trim_start())),
+    }
+}
+
+/// The manufacturer from the fixed data header, as far as it can be decoded
+fn manufacturer(input: &[u8]) -> Option<String> {
+    let (Frame::LongFrame { data, .. } | Frame::ControlFrame { data, .. }) =
+        Frame::try_from(input).ok()?
+    else {
+        return None;
+    };
+    match UserDataBlock::try_from(data).ok()? {
+        UserDataBlock::VariableDataStructure {
+            fixed_data_header, ..
+        } => Some(fixed_data_header.manufacturer.code.iter().collect()),
+        _ => None,
+    }
+}
+
+/// The error without the values it carries, e.g. `invalid frame: Wrong checksum`
+fn error_kind(error: &CliError) -> String {
+    match error {
+        // the message of the error of reading a file carries the path
+        CliError::Read { error, .. } => format!("could not read: {}", error.kind()),
+        CliError::Write { error } => format!("could not write the output: {}", error.kind()),
+        CliError::Hex(_) => "invalid hex input".to_string(),
+        CliError::Frame(error) => format!("invalid frame: {}", frame_error_kind(error)),
+        CliError::ApplicationLayer(error) => {
+            format!("invalid user data: {}", application_layer_error_kind(error))
+        }
+        CliError::Records(error) => format!("invalid data records: {}", records_error_kind(error)),
+        CliError::Xml(error) => format!("could not write XML: {}", xml_error_kind(error)),
+        CliError::NoDataRecords | CliError::NoTelegram => error.to_string(),
+    }
+}
+
+fn frame_error_kind(error: &FrameError) -> &'static str {
+    match error {
+        FrameError::EmptyData => "Data is empty",
+        FrameError::InvalidStartByte => "Invalid start byte",
+        FrameError::InvalidStopByte => "Invalid stop byte",
+        FrameError::LengthMismatch => "Length mismatch",
+        FrameError::LengthShorterThanSix { .. } => "Length is shorter than six",
+        FrameError::WrongChecksum { .. } => "Wrong checksum",
+        FrameError::InvalidControlInformation { .. } => "Invalid control information",
+        FrameError::InvalidFunction { .. } => "Invalid function",
+    }
+}
+
+fn application_layer_error_kind(error: &ApplicationLayerError) -> &'static str {
+    match error {
+        ApplicationLayerError::MissingControlInformation => "Missing control information",
+        ApplicationLayerError::InvalidControlInformation { .. } => "Invalid control information",
+        ApplicationLayerError::IdentificationNumberError { .. } => "Invalid identification number",
+        ApplicationLayerError::InvalidManufacturerCode { .. } => "Invalid manufacturer code",
+        ApplicationLayerError::InsufficientData => "Insufficient data",
+        ApplicationLayerError::Unimplemented { .. } => "Unimplemented control information",
+    }
+}
+
+fn records_error_kind(error: &VariableUserDataError) -> &'static str {
+    match error {
+        VariableUserDataError::DataInformationError(DataRecordError::DataInformationError(
+            error,
+        )) => match error {
+            DataInformationError::NoData => "Invalid data information: No data",
+            DataInformationError::DataTooLong => "Invalid data information: Data too long",
+            DataInformationError::DataTooShort => "Invalid data information: Data too short",
+            DataInformationError::InvalidVariableLength { .. } => {
+                "Invalid data information: Invalid variable length"
+            }
+        },
+        VariableUserDataError::DataInformationError(DataRecordError::ValueInformationError(
+            error,
+        )) => match error {
+            ValueInformationError::InvalidValueInformation => {
+                "Invalid value information: Invalid value information"
+            }
+            ValueInformationError::DataTooShort => "Invalid value information: Data too short",
+            ValueInformationError::TooManyExtensions => {
+                "Invalid value information: Too many extensions"
+            }
+        },
+        VariableUserDataError::TooManyRecords => "Too many records",
+    }
+}
+
+fn xml_error_kind(error: &XmlError) -> &'static str {
+    match error {
+        XmlError::Format => "Formatting failed",
+        XmlError::VariableUserDataError(error) => records_error_kind(error),
+        XmlError::NoMeterData => "No meter data",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_log_line() {
+        assert_eq!(
+            split_log_line("10 5B FE 59 16"),
+            Some((None, "10 5B FE 59 16"))
+        );
+        assert_eq!(
+            split_log_line("2024-05-01T12:00:00Z 105BFE5916"),
+            Some((Some("2024-05-01T12:00:00Z"), "105BFE5916"))
+        );
+        assert_eq!(
+            split_log_line("2024-05-01 12:00:00.250  10 5B FE 59 16 "),
+            Some((Some("2024-05-01 12:00:00.250"), "10 5B FE 59 16"))
+        );
+        assert_eq!(split_log_line("  "), None);
+        assert_eq!(split_log_line("# comment"), None);
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let error = CliError::Frame(m_bus_parser::frames::FrameError::WrongChecksum {
+            expected: 0x58,
+            actual: 0x59,
+        });
+        assert_eq!(error_kind(&error), "invalid frame: Wrong checksum");
+        let error = CliError::Hex(hex::FromHexError::OddLength);
+        assert_eq!(error_kind(&error), "invalid hex input");
+    }
+
+    #[test]
+    fn test_run() {
+        let log = std::env::temp_dir().join(format!("m-bus-batch-{}.log", std::process::id()));
+        fs::write(
+            &log,
+            "# readout\n\
+             2024-05-01T12:00:00Z 10 5B FE 59 16\n\
+             2024-05-01T12:00:01Z 10 5B FE 58 16\n\
+             2024-05-01T12:00:02Z 68 03 03 68 53 FE 50 A1 16\n\
+             2024-05-01T12:00:03Z\n",
+        )
+        .unwrap();
+
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+        let path = log.to_str().unwrap();
+        let summary = run(path, Format::Table, &mut output, &mut errors).unwrap();
+        fs::remove_file(&log).unwrap();
+
+        assert_eq!(summary.decoded, 1);
+        assert_eq!(summary.failed, 3);
+        assert_eq!(
+            summary.errors.into_iter().collect::<Vec<_>>(),
+            [
+                ("invalid frame: Wrong checksum".to_string(), 1),
+                ("invalid user data: Insufficient data".to_string(), 1),
+                ("the line contains no telegram".to_string(), 1),
+            ]
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(&format!("== {}:2 2024-05-01T12:00:00Z ==\n", path)));
+        let errors = String::from_utf8(errors).unwrap();
+        assert!(errors.starts_with(&format!("error: {}:3: invalid frame: Wrong checksum", path)));
+    }
+
+    #[test]
+    fn test_run_directory() {
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+        let summary = run(
+            "../tests/rscada/test-frames",
+            Format::Csv,
+            &mut output,
+            &mut errors,
+        )
+        .unwrap();
+        assert_eq!(summary.failed, 0);
+        assert!(summary.errors.is_empty());
+        assert!(errors.is_empty());
+        let files = fs::read_dir("../tests/rscada/test-frames")
+            .unwrap()
+            .filter(|entry| {
+                let path = entry.as_ref().unwrap().path();
+                path.extension().is_some_and(|ext| ext == "hex")
+            })
+            .count();
+        assert_eq!(summary.decoded, files);
+
+        /* the 2 frames with a fixed data structure have columns of their own */
+        let output = String::from_utf8(output).unwrap();
+        let header = output.lines().next().unwrap();
+        assert!(header.starts_with("source,timestamp,identification_number,manufacturer,"));
+        let fixed_header = "source,timestamp,identification_number,access_number,status,\
+                            medium_and_unit,counter1,counter2,";
+        assert_eq!(
+            output
+                .lines()
+                .filter(|line| line.starts_with(fixed_header))
+                .count(),
+            2
+        );
+        assert_eq!(output.matches(header).count(), 3);
+    }
+
+    #[test]
+    fn test_run_xml() {
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+        let path = "../tests/rscada/test-frames";
+        let summary = run(path, Format::Xml, &mut output, &mut errors).unwrap();
+        assert_eq!(summary.failed, 0);
+
+        /* one document with an element per telegram */
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(
+            "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<MBusBatch>\n<Telegram source="
+        ));
+        assert!(output.ends_with("</Telegram>\n</MBusBatch>\n"));
+        assert_eq!(output.matches("<?xml").count(), 1);
+        assert_eq!(output.matches("<Telegram ").count(), summary.decoded);
+        assert_eq!(output.matches("<MBusData>").count(), summary.decoded);
+
+        /* a log without telegrams is still a document */
+        let log = std::env::temp_dir().join(format!("m-bus-batch-xml-{}.log", std::process::id()));
+        fs::write(&log, "# readout\n").unwrap();
+        let mut output = Vec::new();
+        run(log.to_str().unwrap(), Format::Xml, &mut output, &mut errors).unwrap();
+        fs::remove_file(&log).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<MBusBatch>\n</MBusBatch>\n"
+        );
+    }
+}
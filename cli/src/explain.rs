@@ -0,0 +1,64 @@
+//! Annotated hex dump of a frame, one line per field with long fields
+//! wrapped onto continuation lines.
+
+use std::fmt::Write;
+
+use m_bus_parser::dissection::Dissection;
+
+const BYTES_PER_LINE: usize = 8;
+
+pub fn render(dissection: &Dissection) -> String {
+    let layers: Vec<String> = dissection
+        .iter()
+        .map(|span| span.layer.to_string())
+        .collect();
+    let layer_width = layers.iter().map(String::len).max().unwrap_or_default();
+    let field_width = dissection
+        .iter()
+        .map(|span| span.field.len())
+        .max()
+        .unwrap_or_default();
+    let bytes_width = BYTES_PER_LINE * 3 - 1;
+
+    let mut output = String::new();
+    for (span, layer) in dissection.iter().zip(&layers) {
+        for (line, chunk) in span.bytes.chunks(BYTES_PER_LINE).enumerate() {
+            let bytes = hex_bytes(chunk);
+            let offset = span.offset + line * BYTES_PER_LINE;
+            let text = if line == 0 {
+                format!(
+                    "{:04X}  {:bytes_width$}  {:layer_width$}  {:field_width$}  {}",
+                    offset, bytes, layer, span.field, span.meaning
+                )
+            } else {
+                format!("{:04X}  {}", offset, bytes)
+            };
+            let _ = writeln!(output, "{}", text.trim_end());
+        }
+    }
+    output
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+    bytes.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let frame = [0x10, 0x7B, 0x8B, 0x06, 0x16];
+        let dissection = Dissection::try_from(frame.as_slice()).unwrap();
+        assert_eq!(
+            render(&dissection),
+            "0000  10                       link  start\n\
+             0001  7B                       link  C field   REQ_UD2 (FCB 1)\n\
+             0002  8B                       link  A field   139\n\
+             0003  06                       link  checksum  0x06\n\
+             0004  16                       link  stop\n"
+        );
+    }
+}
@@ -0,0 +1,585 @@
+//! Rendering of a decoded frame as a table for the terminal, or flattened to
+//! one row per data record as JSON lines, CSV or YAML, or as libmbus XML.
+
+use std::fmt::Write;
+
+use m_bus_parser::frames::Frame;
+use m_bus_parser::user_data::data_information::DataValue;
+use m_bus_parser::user_data::date_time::DateTime;
+use m_bus_parser::user_data::libmbus_xml::{self, XmlFormat};
+use m_bus_parser::user_data::variable_user_data::DataRecord;
+use m_bus_parser::user_data::{DataRecords, FixedDataHeader, StatusField, UserDataBlock};
+
+use crate::{CliError, Report};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Table,
+    Json,
+    Xml,
+    Csv,
+    Yaml,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 5] = ["table", "json", "xml", "csv", "yaml"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(Format::Table),
+            "json" => Some(Format::Json),
+            "xml" => Some(Format::Xml),
+            "csv" => Some(Format::Csv),
+            "yaml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Where a telegram of a batch comes from, a file or a line of a log
+pub struct Source<'a> {
+    pub name: &'a str,
+    pub timestamp: Option<&'a str>,
+}
+
+/// What the telegrams of a batch rendered so far have written
+#[derive(Debug, Default)]
+pub struct Written {
+    any: bool,
+    /// The last CSV header line, a new one is written when the columns change,
+    /// e.g. from a variable to a fixed data structure
+    csv_header: Option<String>,
+    /// Whether the root element of the XML documents of a batch is open
+    xml_root: bool,
+}
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n";
+
+pub fn render(report: &Report, format: Format) -> Result<String, CliError> {
+    render_from(report, format, None, &mut Written::default())
+}
+
+/// Completes the output of a batch after its last telegram
+pub fn finish(format: Format, written: &Written) -> String {
+    match format {
+        Format::Xml if written.xml_root => "</MBusBatch>\n".to_string(),
+        Format::Xml => format!("{}<MBusBatch>\n</MBusBatch>\n", XML_DECLARATION),
+        _ => String::new(),
+    }
+}
+
+/// Renders one telegram of a batch, the flattened rows start with its source
+pub fn render_from(
+    report: &Report,
+    format: Format,
+    source: Option<&Source>,
+    written: &mut Written,
+) -> Result<String, CliError> {
+    let first = !written.any;
+    written.any = true;
+    if format == Format::Table {
+        let table = table(report);
+        return Ok(match source {
+            Some(source) => {
+                let separator = if first { "" } else { "\n" };
+                let timestamp = source.timestamp.unwrap_or_default();
+                let heading = format!("== {} {}", source.name, timestamp);
+                format!("{}{} ==\n{}", separator, heading.trim_end(), table)
+            }
+            None => table,
+        });
+    }
+    if format == Format::Xml {
+        let Some(user_data) = &report.user_data else {
+            return Err(CliError::NoDataRecords);
+        };
+        let mut output = String::new();
+        libmbus_xml::write_user_data_xml(&mut output, user_data, XmlFormat::Plain)
+            .map_err(CliError::Xml)?;
+        let Some(source) = source else {
+            return Ok(output);
+        };
+        // the documents of a batch become elements of a single document
+        let document = output.strip_prefix(XML_DECLARATION).unwrap_or(&output);
+        let mut element = String::new();
+        if !written.xml_root {
+            written.xml_root = true;
+            element.push_str(XML_DECLARATION);
+            element.push_str("<MBusBatch>\n");
+        }
+        let _ = write!(element, "<Telegram source=\"{}\"", xml_escaped(source.name));
+        if let Some(timestamp) = source.timestamp {
+            let _ = write!(element, " timestamp=\"{}\"", xml_escaped(timestamp));
+        }
+        let _ = write!(element, ">\n{}</Telegram>\n", document);
+        return Ok(element);
+    }
+
+    let mut header = Fields::new();
+    if let Some(source) = source {
+        header.push(("source", Field::text(source.name)));
+        header.push((
+            "timestamp",
+            source.timestamp.map_or(Field::Null, Field::text),
+        ));
+    }
+    match &report.user_data {
+        Some(UserDataBlock::VariableDataStructure {
+            fixed_data_header, ..
+        }) => header.extend(header_fields(fixed_data_header)),
+        Some(UserDataBlock::FixedDataStructure {
+            identification_number,
+            access_number,
+            status,
+            medium_ad_unit,
+            counter1,
+            counter2,
+        }) => header.extend([
+            (
+                "identification_number",
+                Field::text(format!("{:08}", u32::from(*identification_number))),
+            ),
+            ("access_number", Field::number(access_number)),
+            ("status", Field::text(status_flags(*status))),
+            (
+                "medium_and_unit",
+                Field::text(format!("{:04X}", medium_ad_unit)),
+            ),
+            ("counter1", Field::number(counter1)),
+            ("counter2", Field::number(counter2)),
+        ]),
+        Some(UserDataBlock::ResetAtApplicationLevel { subcode }) => {
+            header.push(("reset_subcode", Field::text(subcode)))
+        }
+        None => {}
+    }
+    let records: Vec<Fields> = match &report.records {
+        Some(records) => (0..records.len())
+            .filter_map(|index| records.get(index))
+            .map(record_fields)
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(match format {
+        Format::Table | Format::Xml => unreachable!(),
+        Format::Json => json_lines(&header, &records),
+        Format::Csv => {
+            let line = csv_header(&header);
+            let rows = csv_rows(&header, &records);
+            if written.csv_header.as_ref() == Some(&line) {
+                rows
+            } else {
+                written.csv_header = Some(line.clone());
+                line + &rows
+            }
+        }
+        Format::Yaml if source.is_some() => format!("---\n{}", yaml(&header, &records)),
+        Format::Yaml => yaml(&header, &records),
+    })
+}
+
+/// A value of the flattened output, numbers are kept as their exact decimal
+/// representation so that nothing gets lost on the way to JSON or CSV
+#[derive(Debug, PartialEq)]
+enum Field {
+    Null,
+    Number(String),
+    Text(String),
+}
+
+impl Field {
+    fn number(value: impl ToString) -> Self {
+        Field::Number(value.to_string())
+    }
+
+    fn text(value: impl ToString) -> Self {
+        Field::Text(value.to_string())
+    }
+
+    fn plain(&self) -> &str {
+        match self {
+            Field::Null => "",
+            Field::Number(value) | Field::Text(value) => value,
+        }
+    }
+}
+
+type Fields = Vec<(&'static str, Field)>;
+
+fn header_fields(header: &FixedDataHeader) -> Fields {
+    vec![
+        (
+            "identification_number",
+            Field::text(format!("{:08}", u32::from(header.identification_number))),
+        ),
+        ("manufacturer", Field::text(header.manufacturer)),
+        ("version", Field::number(header.version)),
+        ("medium", Field::text(header.medium)),
+        ("access_number", Field::number(header.access_number)),
+        ("status", Field::text(status_flags(header.status))),
+        ("signature", Field::number(header.signature)),
+    ]
+}
+
+// The set flags by name as the serde representation of the library writes
+// them, e.g. `TEMPORARY_ERROR | MANUFACTURER_SPECIFIC_1`
+fn status_flags(status: StatusField) -> String {
+    status
+        .iter_names()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+const RECORD_COLUMNS: [&str; 7] = [
+    "storage", "tariff", "subunit", "function", "quantity", "unit", "value",
+];
+
+fn record_fields(record: &DataRecord) -> Fields {
+    let fields = [
+        Field::number(record.storage_number),
+        Field::number(record.tariff),
+        Field::number(record.sub_unit),
+        Field::text(record.function),
+        Field::text(record.quantity),
+        Field::text(unit(record)),
+        value(record),
+    ];
+    RECORD_COLUMNS.into_iter().zip(fields).collect()
+}
+
+fn unit(record: &DataRecord) -> String {
+    match record.plain_text_unit {
+        Some(text) => text.to_string(),
+        None => record.unit.to_string(),
+    }
+}
+
+fn value(record: &DataRecord) -> Field {
+    match (record.decimal_value(), record.value) {
+        (Some(decimal), _) => Field::number(decimal),
+        (None, DataValue::Real(_)) => match record.scaled_value() {
+            Some(value) if value.is_finite() => Field::number(value),
+            _ => Field::Null,
+        },
+        (None, DataValue::NoData) => Field::Null,
+        // Meters send invalid digits like FFFF while they can not measure
+        (None, DataValue::Bcd(_)) => Field::text(hex::encode_upper(
+            record.raw_data.iter().rev().copied().collect::<Vec<_>>(),
+        )),
+        (None, DataValue::Date(date)) => Field::text(format!(
+            "{:04}-{:02}-{:02}",
+            date.year, date.month, date.day
+        )),
+        (None, DataValue::Time(time)) => Field::text(format!(
+            "{:02}:{:02}:{:02}",
+            time.hour, time.minute, time.second
+        )),
+        (None, DataValue::DateTime(date_time)) => {
+            let DateTime { date, time, .. } = date_time;
+            let mut text = format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                date.year, date.month, date.day, time.hour, time.minute, time.second
+            );
+            if let Some(offset) = date_time.utc_offset {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                let _ = write!(text, "{}{:02}:{:02}", sign, offset / 60, offset % 60);
+            }
+            Field::Text(text)
+        }
+        (None, DataValue::Manufacturer(code)) => Field::text(code),
+        (None, DataValue::Medium(medium)) => Field::text(medium),
+        (None, DataValue::Ascii(text)) => Field::text(text),
+        (None, DataValue::Raw(bytes)) => Field::text(hex::encode_upper(bytes)),
+        (None, value) => Field::text(format!("{:?}", value)),
+    }
+}
+
+fn table(report: &Report) -> String {
+    let mut output = String::new();
+    let (kind, function, address) = match &report.frame {
+        Frame::SingleCharacter { character } => {
+            return format!("Frame: single character 0x{:02X}\n", character);
+        }
+        Frame::ShortFrame { function, address } => ("short frame", function, address),
+        Frame::LongFrame {
+            function, address, ..
+        } => ("long frame", function, address),
+        Frame::ControlFrame {
+            function, address, ..
+        } => ("control frame", function, address),
+    };
+    let mut properties = vec![
+        ("Frame", kind.to_string()),
+        ("Function", function.to_string()),
+        ("Address", address.to_string()),
+    ];
+
+    match &report.user_data {
+        Some(UserDataBlock::VariableDataStructure {
+            fixed_data_header: header,
+            ..
+        }) => properties.extend([
+            (
+                "Identification number",
+                format!("{:08}", u32::from(header.identification_number)),
+            ),
+            ("Manufacturer", header.manufacturer.to_string()),
+            ("Version", header.version.to_string()),
+            ("Medium", header.medium.to_string()),
+            ("Access number", header.access_number.to_string()),
+            ("Status", header.status.to_string()),
+            ("Signature", format!("{:04X}", header.signature)),
+        ]),
+        Some(UserDataBlock::FixedDataStructure {
+            identification_number,
+            access_number,
+            status,
+            medium_ad_unit,
+            counter1,
+            counter2,
+        }) => properties.extend([
+            ("User data", "fixed data structure".to_string()),
+            (
+                "Identification number",
+                format!("{:08}", u32::from(*identification_number)),
+            ),
+            ("Access number", access_number.to_string()),
+            ("Status", status.to_string()),
+            ("Medium and unit", format!("{:04X}", medium_ad_unit)),
+            ("Counter 1", counter1.to_string()),
+            ("Counter 2", counter2.to_string()),
+        ]),
+        Some(UserDataBlock::ResetAtApplicationLevel { subcode }) => properties.extend([
+            ("User data", "reset at application level".to_string()),
+            ("Subcode", subcode.to_string()),
+        ]),
+        None => {}
+    }
+
+    let width = properties
+        .iter()
+        .map(|(name, _)| name.len() + 1)
+        .max()
+        .unwrap_or_default();
+    for (name, value) in properties {
+        let _ = writeln!(output, "{:width$} {}", format!("{}:", name), value);
+    }
+    if let Some(records) = &report.records {
+        output += &records_table(records);
+    }
+    output
+}
+
+fn records_table(records: &DataRecords) -> String {
+    const COLUMNS: [&str; 8] = [
+        "#", "Storage", "Tariff", "Subunit", "Function", "Quantity", "Value", "Unit",
+    ];
+    // The index, storage number, tariff and subunit are aligned to the right
+    const NUMERIC: usize = 4;
+
+    let rows: Vec<[String; 8]> = (0..records.len())
+        .filter_map(|index| Some((index, records.get(index)?)))
+        .map(|(index, record)| {
+            [
+                index.to_string(),
+                record.storage_number.to_string(),
+                record.tariff.to_string(),
+                record.sub_unit.to_string(),
+                record.function.to_string(),
+                record.quantity.to_string(),
+                match value(record) {
+                    Field::Null => "-".to_string(),
+                    field => field.plain().to_string(),
+                },
+                unit(record),
+            ]
+        })
+        .collect();
+    let mut widths = COLUMNS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::from("\n");
+    let header = COLUMNS.map(str::to_string);
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+        for (column, (cell, width)) in row.iter().zip(widths).enumerate() {
+            if column < NUMERIC {
+                let _ = write!(line, "{:>width$}  ", cell);
+            } else {
+                let _ = write!(line, "{:width$}  ", cell);
+            }
+        }
+        output += line.trim_end();
+        output.push('\n');
+    }
+    if let Some(data) = records.manufacturer_data() {
+        let _ = writeln!(output, "\nManufacturer data: {}", hex::encode_upper(data));
+    }
+    if records.more_records_follow() {
+        output += "\nMore records follow\n";
+    }
+    output
+}
+
+/// One JSON object per data record, each with the fixed data header, or the
+/// header alone for telegrams without data records
+fn json_lines(header: &Fields, records: &[Fields]) -> String {
+    let mut output = String::new();
+    for record in rows(records) {
+        output.push('{');
+        for (index, (name, field)) in header.iter().chain(record).enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            let _ = write!(output, "\"{}\":{}", name, json(field));
+        }
+        output += "}\n";
+    }
+    output
+}
+
+fn json(field: &Field) -> String {
+    match field {
+        Field::Null => "null".to_string(),
+        Field::Number(value) => value.clone(),
+        Field::Text(value) => quoted(value),
+    }
+}
+
+/// A text with the characters of a special meaning in XML escaped
+fn xml_escaped(value: &str) -> String {
+    let mut output = String::new();
+    for c in value.chars() {
+        match c {
+            '&' => output += "&amp;",
+            '<' => output += "&lt;",
+            '>' => output += "&gt;",
+            '"' => output += "&quot;",
+            '\'' => output += "&apos;",
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// A double quoted string with the escapes of JSON, which YAML understands too
+fn quoted(value: &str) -> String {
+    let mut output = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => output += "\\\"",
+            '\\' => output += "\\\\",
+            '\n' => output += "\\n",
+            '\r' => output += "\\r",
+            '\t' => output += "\\t",
+            c if c.is_control() => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+fn csv_header(header: &Fields) -> String {
+    let names = header.iter().map(|(name, _)| *name).chain(RECORD_COLUMNS);
+    let mut output = names.collect::<Vec<_>>().join(",");
+    output.push('\n');
+    output
+}
+
+/// One row per data record, each with the fixed data header
+fn csv_rows(header: &Fields, records: &[Fields]) -> String {
+    let mut output = String::new();
+    for record in rows(records) {
+        // the record columns stay empty for telegrams without data records
+        let empty = RECORD_COLUMNS.len() - record.len();
+        let line: Vec<String> = header
+            .iter()
+            .chain(record)
+            .map(|(_, field)| csv_cell(field.plain()))
+            .chain(std::iter::repeat_n(String::new(), empty))
+            .collect();
+        output += &line.join(",");
+        output.push('\n');
+    }
+    output
+}
+
+// A single row without record fields if there are no data records
+fn rows(records: &[Fields]) -> &[Fields] {
+    const NO_RECORD: &[Fields] = &[Vec::new()];
+    if records.is_empty() {
+        NO_RECORD
+    } else {
+        records
+    }
+}
+
+fn csv_cell(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn yaml(header: &Fields, records: &[Fields]) -> String {
+    let mut output = String::new();
+    for (name, field) in header {
+        let _ = writeln!(output, "{}: {}", name, json(field));
+    }
+    if records.is_empty() {
+        output += "records: []\n";
+        return output;
+    }
+    output += "records:\n";
+    for record in records {
+        for (index, (name, field)) in record.iter().enumerate() {
+            let indent = if index == 0 { "  - " } else { "    " };
+            let _ = writeln!(output, "{}{}: {}", indent, name, json(field));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(quoted("a \"b\"\\\n\u{1}"), "\"a \\\"b\\\"\\\\\\n\\u0001\"");
+        assert_eq!(csv_cell("m3"), "m3");
+        assert_eq!(csv_cell("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn test_flattened() {
+        let header = vec![("manufacturer", Field::text("ELS"))];
+        let records = vec![
+            vec![
+                ("storage", Field::number(0)),
+                ("value", Field::number("22.7")),
+            ],
+            vec![("storage", Field::number(1)), ("value", Field::Null)],
+        ];
+        assert_eq!(
+            json_lines(&header, &records),
+            "{\"manufacturer\":\"ELS\",\"storage\":0,\"value\":22.7}\n\
+             {\"manufacturer\":\"ELS\",\"storage\":1,\"value\":null}\n"
+        );
+        assert_eq!(
+            yaml(&header, &records),
+            "manufacturer: \"ELS\"\nrecords:\n  - storage: 0\n    value: 22.7\n  \
+             - storage: 1\n    value: null\n"
+        );
+    }
+}
@@ -1,27 +1,340 @@
-use clap::{App, Arg, SubCommand};
+mod batch;
+mod explain;
+mod format;
 
-fn main() {
-    let matches = App::new("m-bus-parser-cli")
-        .version("0.0.0")
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use clap::{Arg, Command};
+use m_bus_parser::dissection::Dissection;
+use m_bus_parser::frames::{Frame, FrameError};
+use m_bus_parser::user_data::libmbus_xml::XmlError;
+use m_bus_parser::user_data::variable_user_data::VariableUserDataError;
+use m_bus_parser::user_data::{ApplicationLayerError, DataRecords, UserDataBlock};
+
+use crate::format::Format;
+
+#[derive(Debug)]
+enum CliError {
+    Read { path: String, error: io::Error },
+    Write { error: io::Error },
+    Hex(hex::FromHexError),
+    Frame(FrameError),
+    ApplicationLayer(ApplicationLayerError),
+    Records(VariableUserDataError),
+    NoDataRecords,
+    NoTelegram,
+    Xml(XmlError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Read { path, error } => write!(f, "could not read {}: {}", path, error),
+            CliError::Write { error } => write!(f, "could not write the output: {}", error),
+            CliError::Hex(error) => write!(f, "invalid hex input: {}", error),
+            CliError::Frame(error) => write!(f, "invalid frame: {}", error),
+            CliError::ApplicationLayer(error) => write!(f, "invalid user data: {}", error),
+            CliError::Records(error) => write!(f, "invalid data records: {}", error),
+            CliError::NoDataRecords => write!(f, "the frame contains no data records"),
+            CliError::NoTelegram => write!(f, "the line contains no telegram"),
+            CliError::Xml(error) => write!(f, "could not write XML: {}", error),
+        }
+    }
+}
+
+fn command() -> Command {
+    Command::new("m-bus-parser-cli")
+        .version(env!("CARGO_PKG_VERSION"))
         .about("CLI tool for m-bus parsing")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("parse")
+                .about("Parses an M-Bus frame given as hex, with or without spaces, or as binary")
+                .arg(
+                    Arg::new("file")
+                        .help("The file to parse, standard input if omitted or -")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("format")
+                        .help(
+                            "The output format, json writes one line per data record and \
+                             xml one MBusBatch document with a Telegram element each",
+                        )
+                        .long("format")
+                        .short('f')
+                        .value_parser(Format::NAMES)
+                        .default_value("table"),
+                ),
+        )
         .subcommand(
-            SubCommand::with_name("parse")
-                .about("Parses an M-Bus data file")
+            Command::new("batch")
+                .about(
+                    "Decodes the .hex files below a directory or a log with one telegram \
+                     in hex per line, optionally behind an ISO 8601 timestamp",
+                )
+                .after_help(
+                    "Failures are reported on standard error and do not change the exit \
+                     status, which is followed by a summary of the batch.",
+                )
                 .arg(
-                    Arg::with_name("file")
-                        .help("The file to parse")
+                    Arg::new("path")
+                        .help("The directory or log file")
                         .required(true)
                         .index(1),
+                )
+                .arg(
+                    Arg::new("format")
+                        .help("The output format, json writes one line per data record")
+                        .long("format")
+                        .short('f')
+                        .value_parser(Format::NAMES)
+                        .default_value("table"),
                 ),
         )
-        .get_matches();
+        .subcommand(
+            Command::new("explain")
+                .about("Shows which field and layer every byte of a frame belongs to")
+                .arg(
+                    Arg::new("file")
+                        .help("The file to explain, standard input if omitted or -")
+                        .index(1),
+                ),
+        )
+}
 
-    // Handling "parse" command
-    if let Some(matches) = matches.subcommand_matches("parse") {
-        if let Some(file_path) = matches.value_of("file") {
-            // Call your library's function to parse the file here
-            println!("Parsing file: {}", file_path);
-            // For example: m_bus_parser::parse_file(file_path);
+fn main() -> ExitCode {
+    let matches = command().get_matches();
+
+    let result = match matches.subcommand() {
+        Some(("parse", matches)) => {
+            let path = matches.get_one::<String>("file").map(String::as_str);
+            let format = matches
+                .get_one::<String>("format")
+                .and_then(|name| Format::from_name(name))
+                .unwrap_or(Format::Table);
+            read_input(path).and_then(|input| format::render(&parse(&input)?, format))
+        }
+        Some(("batch", matches)) => {
+            let path = matches.get_one::<String>("path").map_or("", String::as_str);
+            let format = matches
+                .get_one::<String>("format")
+                .and_then(|name| Format::from_name(name))
+                .unwrap_or(Format::Table);
+            let summary = batch::run(path, format, &mut io::stdout().lock(), &mut io::stderr());
+            summary.map(|summary| {
+                eprint!("\n{}", summary);
+                String::new()
+            })
         }
+        Some(("explain", matches)) => {
+            let path = matches.get_one::<String>("file").map(String::as_str);
+            read_input(path).and_then(|input| {
+                let dissection = Dissection::try_from(input.as_slice()).map_err(CliError::Frame)?;
+                Ok(explain::render(&dissection))
+            })
+        }
+        _ => unreachable!("a subcommand is required"),
+    };
+
+    match result {
+        Ok(output) => {
+            print!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read_input(path: Option<&str>) -> Result<Vec<u8>, CliError> {
+    let input = match path {
+        None | Some("-") => {
+            let mut input = Vec::new();
+            io::stdin()
+                .read_to_end(&mut input)
+                .map(|_| input)
+                .map_err(|error| CliError::Read {
+                    path: "standard input".to_string(),
+                    error,
+                })
+        }
+        Some(path) => fs::read(path).map_err(|error| CliError::Read {
+            path: path.to_string(),
+            error,
+        }),
+    }?;
+    decode_input(input)
+}
+
+/// Input consisting of hex digits and whitespace only is taken as hex, like
+/// the `.hex` files of rscada libmbus, anything else as the raw frame. A frame
+/// can not be mistaken for hex as none of its start bytes is a hex digit.
+fn decode_input(input: Vec<u8>) -> Result<Vec<u8>, CliError> {
+    let is_hex = input
+        .iter()
+        .all(|byte| byte.is_ascii_hexdigit() || byte.is_ascii_whitespace())
+        && input.iter().any(u8::is_ascii_hexdigit);
+    if !is_hex {
+        return Ok(input);
+    }
+    let digits: Vec<u8> = input
+        .into_iter()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    hex::decode(digits).map_err(CliError::Hex)
+}
+
+/// A decoded frame with its user data and data records as far as present
+struct Report<'a> {
+    frame: Frame<'a>,
+    user_data: Option<UserDataBlock<'a>>,
+    records: Option<DataRecords<'a>>,
+}
+
+fn parse(input: &[u8]) -> Result<Report<'_>, CliError> {
+    let frame = Frame::try_from(input).map_err(CliError::Frame)?;
+    let data = match &frame {
+        Frame::LongFrame { data, .. } | Frame::ControlFrame { data, .. } => *data,
+        Frame::SingleCharacter { .. } | Frame::ShortFrame { .. } => {
+            return Ok(Report {
+                frame,
+                user_data: None,
+                records: None,
+            })
+        }
+    };
+
+    let user_data = UserDataBlock::try_from(data).map_err(CliError::ApplicationLayer)?;
+    let records = match &user_data {
+        UserDataBlock::VariableDataStructure {
+            variable_data_block,
+            ..
+        } => Some(DataRecords::try_from(*variable_data_block).map_err(CliError::Records)?),
+        _ => None,
+    };
+    Ok(Report {
+        frame,
+        user_data: Some(user_data),
+        records,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_input() {
+        let frame = [0x10, 0x5B, 0xFE, 0x59, 0x16];
+        assert_eq!(decode_input(b"10 5B FE 59 16\n".to_vec()).unwrap(), frame);
+        assert_eq!(decode_input(b"105bfe5916".to_vec()).unwrap(), frame);
+        assert_eq!(decode_input(frame.to_vec()).unwrap(), frame);
+        assert!(matches!(
+            decode_input(b"10 5B F".to_vec()),
+            Err(CliError::Hex(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = fs::read("../tests/rscada/test-frames/ELS_Elster-F96-Plus.hex").unwrap();
+        let input = decode_input(input).unwrap();
+        let report = parse(&input).unwrap();
+        assert_eq!(report.records.as_ref().map(DataRecords::len), Some(16));
+
+        assert!(matches!(
+            parse(&[0x10, 0x5B, 0xFE, 0x58, 0x16]),
+            Err(CliError::Frame(FrameError::WrongChecksum { .. }))
+        ));
+        let input = decode_input(b"68 01 01 68 08 08 16".to_vec()).unwrap();
+        assert!(matches!(
+            parse(&input),
+            Err(CliError::Frame(FrameError::LengthMismatch))
+        ));
+    }
+
+    #[test]
+    fn test_formats() {
+        let input = fs::read("../tests/rscada/test-frames/ELS_Elster-F96-Plus.hex").unwrap();
+        let input = decode_input(input).unwrap();
+        let report = parse(&input).unwrap();
+
+        let table = format::render(&report, Format::Table).unwrap();
+        assert!(table.contains("Function:              RSP_UD (ACD 0, DFC 0)\n"));
+        assert!(table.contains("Identification number: 44493951\nManufacturer:          ELS\n"));
+        assert!(table.contains(
+            " 6        0       0        0  Instantaneous value       Flow temperature        22.7                 °C\n"
+        ));
+
+        let input = fs::read("../tests/rscada/test-frames/manual_frame2.hex").unwrap();
+        let input = decode_input(input).unwrap();
+        let fixed = parse(&input).unwrap();
+        let table = format::render(&fixed, Format::Table).unwrap();
+        assert!(table.contains("User data:             fixed data structure\n"));
+        assert!(table.contains("Counter 2:             135\n"));
+
+        let json = format::render(&report, Format::Json).unwrap();
+        assert_eq!(json.lines().count(), 16);
+        assert_eq!(
+            json.lines().nth(6),
+            Some(
+                "{\"identification_number\":\"44493951\",\"manufacturer\":\"ELS\",\"version\":47,\
+                 \"medium\":\"Heat\",\"access_number\":161,\
+                 \"status\":\"TEMPORARY_ERROR | MANUFACTURER_SPECIFIC_1 | MANUFACTURER_SPECIFIC_2\",\
+                 \"signature\":0,\"storage\":0,\"tariff\":0,\"subunit\":0,\
+                 \"function\":\"Instantaneous value\",\"quantity\":\"Flow temperature\",\
+                 \"unit\":\"°C\",\"value\":22.7}"
+            )
+        );
+
+        let csv = format::render(&report, Format::Csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "identification_number,manufacturer,version,medium,access_number,status,\
+                 signature,storage,tariff,subunit,function,quantity,unit,value"
+            )
+        );
+        assert_eq!(
+            lines.nth(6),
+            Some(
+                "44493951,ELS,47,Heat,161,\
+                 TEMPORARY_ERROR | MANUFACTURER_SPECIFIC_1 | MANUFACTURER_SPECIFIC_2,0,0,0,0,\
+                 Instantaneous value,Flow temperature,°C,22.7"
+            )
+        );
+
+        let xml = format::render(&report, Format::Xml).unwrap();
+        assert!(xml.contains("<Id>44493951</Id>"));
+
+        /* telegrams without data records give a single row of their header */
+        assert_eq!(
+            format::render(&fixed, Format::Json).unwrap(),
+            "{\"identification_number\":\"12345678\",\"access_number\":10,\"status\":\"\",\
+             \"medium_and_unit\":\"E97E\",\"counter1\":1,\"counter2\":135}\n"
+        );
+        let short_frame = parse(&[0x10, 0x5B, 0xFE, 0x59, 0x16]).unwrap();
+        assert_eq!(format::render(&short_frame, Format::Json).unwrap(), "{}\n");
+        assert_eq!(
+            format::render(&short_frame, Format::Csv).unwrap(),
+            "storage,tariff,subunit,function,quantity,unit,value\n,,,,,,\n"
+        );
+        assert!(matches!(
+            format::render(&short_frame, Format::Xml),
+            Err(CliError::NoDataRecords)
+        ));
+    }
+
+    #[test]
+    fn test_command() {
+        command().debug_assert();
     }
 }
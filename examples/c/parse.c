@@ -0,0 +1,69 @@
+/*
+ * Parses a long frame and prints its data records, built and run by the test
+ * `test_c_example` in tests/ffi.rs:
+ *
+ *   cc -I include examples/c/parse.c target/debug/libm_bus_parser.a -lpthread -ldl -lm
+ */
+#include <inttypes.h>
+#include <stdio.h>
+
+#include "m_bus_parser.h"
+
+static const uint8_t FRAME[] = {
+    0x68, 0x4D, 0x4D, 0x68, 0x08, 0x01, 0x72, 0x01, 0x00, 0x00, 0x00, 0x96,
+    0x15, 0x01, 0x00, 0x18, 0x00, 0x00, 0x00, 0x0C, 0x78, 0x56, 0x00, 0x00,
+    0x00, 0x01, 0xFD, 0x1B, 0x00, 0x02, 0xFC, 0x03, 0x48, 0x52, 0x25, 0x74,
+    0x44, 0x0D, 0x22, 0xFC, 0x03, 0x48, 0x52, 0x25, 0x74, 0xF1, 0x0C, 0x12,
+    0xFC, 0x03, 0x48, 0x52, 0x25, 0x74, 0x63, 0x11, 0x02, 0x65, 0xB4, 0x09,
+    0x22, 0x65, 0x86, 0x09, 0x12, 0x65, 0xB7, 0x09, 0x01, 0x72, 0x00, 0x72,
+    0x65, 0x00, 0x00, 0xB2, 0x01, 0x65, 0x00, 0x00, 0x1F, 0xB3, 0x16,
+};
+
+int main(void) {
+    MbusFrame frame;
+    MbusUserData user_data;
+    MbusRecordIterator iterator;
+    MbusRecord record;
+    MbusStatus status;
+    char unit[32];
+
+    status = mbus_parse_frame(FRAME, sizeof(FRAME), &frame);
+    if (status != MBUS_STATUS_OK) {
+        fprintf(stderr, "invalid frame: %d\n", status);
+        return 1;
+    }
+    status = mbus_parse_user_data(frame.user_data, frame.user_data_length, &user_data);
+    if (status != MBUS_STATUS_OK) {
+        fprintf(stderr, "invalid user data: %d\n", status);
+        return 1;
+    }
+    printf("address %u, identification number %08" PRIu32 ", manufacturer %s\n",
+           frame.address, user_data.identification_number,
+           (const char *)user_data.manufacturer);
+
+    mbus_records_begin(user_data.records, user_data.records_length, &iterator);
+    while ((status = mbus_records_next(&iterator, &record)) == MBUS_STATUS_OK) {
+        printf("quantity %d, storage %" PRIu64 ", ", record.quantity, record.storage_number);
+        if (record.unit == MBUS_UNIT_PLAIN_TEXT) {
+            mbus_record_unit_text(&record, unit, sizeof(unit));
+            printf("unit %s, ", unit);
+        }
+        switch (record.value_type) {
+        case MBUS_VALUE_TYPE_UNSIGNED:
+        case MBUS_VALUE_TYPE_SIGNED:
+        case MBUS_VALUE_TYPE_BCD:
+        case MBUS_VALUE_TYPE_REAL:
+            printf("value %g\n", record.value);
+            break;
+        default:
+            printf("value of type %d\n", record.value_type);
+            break;
+        }
+    }
+    if (status != MBUS_STATUS_END_OF_RECORDS) {
+        fprintf(stderr, "invalid record: %d\n", status);
+        return 1;
+    }
+    printf("more records follow: %s\n", iterator.more_records_follow ? "yes" : "no");
+    return 0;
+}
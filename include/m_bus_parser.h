@@ -0,0 +1,386 @@
+#ifndef M_BUS_PARSER_H
+#define M_BUS_PARSER_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum {
+  MBUS_STATUS_OK = 0,
+  // A pointer argument was null
+  MBUS_STATUS_NULL_POINTER,
+  MBUS_STATUS_INVALID_FRAME,
+  MBUS_STATUS_INVALID_USER_DATA,
+  MBUS_STATUS_INVALID_RECORD,
+  // There are no more records, see the manufacturer data of the iterator
+  MBUS_STATUS_END_OF_RECORDS,
+} MbusStatus;
+
+typedef enum {
+  MBUS_FRAME_TYPE_SINGLE_CHARACTER,
+  MBUS_FRAME_TYPE_SHORT_FRAME,
+  MBUS_FRAME_TYPE_LONG_FRAME,
+  MBUS_FRAME_TYPE_CONTROL_FRAME,
+} MbusFrameType;
+
+typedef enum {
+  MBUS_USER_DATA_TYPE_RESET_AT_APPLICATION_LEVEL,
+  MBUS_USER_DATA_TYPE_FIXED_DATA_STRUCTURE,
+  MBUS_USER_DATA_TYPE_VARIABLE_DATA_STRUCTURE,
+} MbusUserDataType;
+
+typedef enum {
+  MBUS_MEDIUM_OTHER,
+  MBUS_MEDIUM_OIL,
+  MBUS_MEDIUM_ELECTRICITY,
+  MBUS_MEDIUM_GAS,
+  MBUS_MEDIUM_HEAT,
+  MBUS_MEDIUM_STEAM,
+  MBUS_MEDIUM_HOT_WATER,
+  MBUS_MEDIUM_WATER,
+  MBUS_MEDIUM_HEAT_COST_ALLOCATOR,
+  MBUS_MEDIUM_RESERVED,
+  MBUS_MEDIUM_GAS_MODE2,
+  MBUS_MEDIUM_HEAT_MODE2,
+  MBUS_MEDIUM_HOT_WATER_MODE2,
+  MBUS_MEDIUM_WATER_MODE2,
+  MBUS_MEDIUM_HEAT_COST_ALLOCATOR2,
+  MBUS_MEDIUM_RESERVED_MODE2,
+  MBUS_MEDIUM_UNKNOWN,
+  MBUS_MEDIUM_COLD_WATER,
+  MBUS_MEDIUM_DUAL_WATER,
+  MBUS_MEDIUM_PRESSURE,
+  MBUS_MEDIUM_AD_CONVERTER,
+} MbusMedium;
+
+typedef enum {
+  MBUS_FUNCTION_FIELD_INSTANTANEOUS_VALUE,
+  MBUS_FUNCTION_FIELD_MAXIMUM_VALUE,
+  MBUS_FUNCTION_FIELD_MINIMUM_VALUE,
+  MBUS_FUNCTION_FIELD_VALUE_DURING_ERROR_STATE,
+} MbusFunctionField;
+
+typedef enum {
+  MBUS_QUANTITY_VOLUME,
+  MBUS_QUANTITY_ENERGY,
+  MBUS_QUANTITY_MANUFACTURER_SPECIFIC,
+  MBUS_QUANTITY_ANY,
+  MBUS_QUANTITY_ERROR_FLAGS,
+  MBUS_QUANTITY_TIME_POINT,
+  MBUS_QUANTITY_VOLUME_FLOW,
+  MBUS_QUANTITY_MASS_FLOW,
+  MBUS_QUANTITY_MASS,
+  MBUS_QUANTITY_TEMPERATURE,
+  MBUS_QUANTITY_FLOW_TEMPERATURE,
+  MBUS_QUANTITY_RETURN_TEMPERATURE,
+  MBUS_QUANTITY_TEMPERATURE_DIFFERENCE,
+  MBUS_QUANTITY_BINARY_DIGITAL_INPUT,
+  MBUS_QUANTITY_RELATIVE_HUMIDITY,
+  MBUS_QUANTITY_ON_TIME,
+  MBUS_QUANTITY_OPERATING_TIME,
+  MBUS_QUANTITY_AVERAGING_DURATION,
+  MBUS_QUANTITY_ACTUALITY_DURATION,
+  MBUS_QUANTITY_EXTERNAL_TEMPERATURE,
+  MBUS_QUANTITY_DURATION,
+  MBUS_QUANTITY_POWER,
+  MBUS_QUANTITY_PRESSURE,
+  MBUS_QUANTITY_HCA,
+  MBUS_QUANTITY_IDENTIFICATION_NUMBER,
+  MBUS_QUANTITY_ENHANCED_IDENTIFICATION,
+  MBUS_QUANTITY_BUS_ADDRESS,
+  MBUS_QUANTITY_PLAIN_TEXT,
+  MBUS_QUANTITY_RESERVED,
+  MBUS_QUANTITY_CREDIT,
+  MBUS_QUANTITY_DEBIT,
+  MBUS_QUANTITY_ACCESS_NUMBER,
+  MBUS_QUANTITY_MEDIUM,
+  MBUS_QUANTITY_MANUFACTURER,
+  MBUS_QUANTITY_PARAMETER_SET_IDENTIFICATION,
+  MBUS_QUANTITY_MODEL_VERSION,
+  MBUS_QUANTITY_HARDWARE_VERSION,
+  MBUS_QUANTITY_FIRMWARE_VERSION,
+  MBUS_QUANTITY_SOFTWARE_VERSION,
+  MBUS_QUANTITY_CUSTOMER_LOCATION,
+  MBUS_QUANTITY_CUSTOMER,
+  MBUS_QUANTITY_ACCESS_CODE,
+  MBUS_QUANTITY_PASSWORD,
+  MBUS_QUANTITY_ERROR_MASK,
+  MBUS_QUANTITY_SECURITY_KEY,
+  MBUS_QUANTITY_BINARY_DIGITAL_OUTPUT,
+  MBUS_QUANTITY_BAUD_RATE,
+  MBUS_QUANTITY_RESPONSE_DELAY_TIME,
+  MBUS_QUANTITY_RETRY,
+  MBUS_QUANTITY_REMOTE_CONTROL,
+  MBUS_QUANTITY_FIRST_STORAGE_NUMBER,
+  MBUS_QUANTITY_LAST_STORAGE_NUMBER,
+  MBUS_QUANTITY_SIZE_OF_STORAGE_BLOCK,
+  MBUS_QUANTITY_STORAGE_INTERVAL,
+  MBUS_QUANTITY_OPERATOR_SPECIFIC_DATA,
+  MBUS_QUANTITY_DURATION_SINCE_LAST_READOUT,
+  MBUS_QUANTITY_DURATION_OF_TARIFF,
+  MBUS_QUANTITY_PERIOD_OF_TARIFF,
+  MBUS_QUANTITY_DIMENSIONLESS,
+  MBUS_QUANTITY_DATA_CONTAINER,
+  MBUS_QUANTITY_PERIOD_OF_NOMINAL_DATA_TRANSMISSIONS,
+  MBUS_QUANTITY_VOLTAGE,
+  MBUS_QUANTITY_CURRENT,
+  MBUS_QUANTITY_RESET_COUNTER,
+  MBUS_QUANTITY_CUMULATION_COUNTER,
+  MBUS_QUANTITY_CONTROL_SIGNAL,
+  MBUS_QUANTITY_DAY_OF_WEEK,
+  MBUS_QUANTITY_WEEK_NUMBER,
+  MBUS_QUANTITY_STATE_OF_PARAMETER_ACTIVATION,
+  MBUS_QUANTITY_SPECIAL_SUPPLIER_INFORMATION,
+  MBUS_QUANTITY_DURATION_SINCE_LAST_CUMULATION,
+  MBUS_QUANTITY_OPERATING_TIME_BATTERY,
+  MBUS_QUANTITY_RF_LEVEL,
+  MBUS_QUANTITY_DAYLIGHT_SAVING,
+  MBUS_QUANTITY_LISTENING_WINDOW_MANAGEMENT,
+  MBUS_QUANTITY_REMAINING_BATTERY_LIFE_TIME,
+  MBUS_QUANTITY_NUMBER_OF_TIMES_METER_STOPPED,
+  MBUS_QUANTITY_REACTIVE_ENERGY,
+  MBUS_QUANTITY_APPARENT_ENERGY,
+  MBUS_QUANTITY_REACTIVE_POWER,
+  MBUS_QUANTITY_APPARENT_POWER,
+  MBUS_QUANTITY_PHASE_VOLTAGE_TO_VOLTAGE,
+  MBUS_QUANTITY_PHASE_VOLTAGE_TO_CURRENT,
+  MBUS_QUANTITY_FREQUENCY,
+  MBUS_QUANTITY_COLD_WARM_TEMPERATURE_LIMIT,
+  MBUS_QUANTITY_CUMULATIVE_COUNT_MAX_POWER,
+} MbusQuantity;
+
+typedef enum {
+  MBUS_UNIT_HOUR_MINUTE_SECOND,
+  MBUS_UNIT_DAY_MONTH_YEAR,
+  MBUS_UNIT_WATT_HOUR,
+  MBUS_UNIT_KILO_WATT_HOUR,
+  MBUS_UNIT_MEGA_WATT_HOUR,
+  MBUS_UNIT_JOUL,
+  MBUS_UNIT_KILOGRAM,
+  MBUS_UNIT_KILO_JOUL,
+  MBUS_UNIT_MEGA_JOUL,
+  MBUS_UNIT_GIGA_JOUL,
+  MBUS_UNIT_WATT,
+  MBUS_UNIT_KILO_WATT,
+  MBUS_UNIT_MEGA_WAT,
+  MBUS_UNIT_KILO_JOUL_HOUR,
+  MBUS_UNIT_MEGA_JOUL_HOUR,
+  MBUS_UNIT_GIGA_JOUL_HOUR,
+  MBUS_UNIT_MEGA_LITER,
+  MBUS_UNIT_LITER,
+  MBUS_UNIT_CUBIC_METER,
+  MBUS_UNIT_MEGA_LITER_HOUR,
+  MBUS_UNIT_LITER_HOUR,
+  MBUS_UNIT_CUBIC_METER_PER_HOUR,
+  MBUS_UNIT_CUBIC_METER_PER_MINUTE,
+  MBUS_UNIT_CUBIC_METER_PER_SECOND,
+  MBUS_UNIT_KILOGRAM_PER_HOUR,
+  MBUS_UNIT_CELSIUS,
+  MBUS_UNIT_KELVIN,
+  MBUS_UNIT_BAR,
+  MBUS_UNIT_HCA,
+  MBUS_UNIT_RESERVED,
+  MBUS_UNIT_WITHOUT_UNITS,
+  MBUS_UNIT_SECONDS,
+  MBUS_UNIT_MINUTES,
+  MBUS_UNIT_HOURS,
+  MBUS_UNIT_DAYS,
+  MBUS_UNIT_JOUL_PER_HOUR,
+  MBUS_UNIT_TIME_POINT,
+  MBUS_UNIT_FABRICATION_NUMBER,
+  MBUS_UNIT_MEGA_WATT,
+  MBUS_UNIT_PLAIN_TEXT,
+  MBUS_UNIT_LOCAL_CURRENCY,
+  MBUS_UNIT_VOLT,
+  MBUS_UNIT_AMPERE,
+  MBUS_UNIT_BAUD,
+  MBUS_UNIT_BIT_TIMES,
+  MBUS_UNIT_MONTHS,
+  MBUS_UNIT_YEARS,
+  MBUS_UNIT_DECIBEL_MILLIWATT,
+  MBUS_UNIT_KILO_VOLT_AMPERE_REACTIVE_HOUR,
+  MBUS_UNIT_KILO_VOLT_AMPERE_HOUR,
+  MBUS_UNIT_MEGA_CALORIE,
+  MBUS_UNIT_KILO_VOLT_AMPERE_REACTIVE,
+  MBUS_UNIT_KILO_VOLT_AMPERE,
+  MBUS_UNIT_TONNE,
+  MBUS_UNIT_PERCENT,
+  MBUS_UNIT_CUBIC_FEET,
+  MBUS_UNIT_AMERICAN_GALLON,
+  MBUS_UNIT_AMERICAN_GALLON_PER_MINUTE,
+  MBUS_UNIT_AMERICAN_GALLON_PER_HOUR,
+  MBUS_UNIT_DEGREE,
+  MBUS_UNIT_HERTZ,
+  MBUS_UNIT_FAHRENHEIT,
+} MbusUnit;
+
+typedef enum {
+  MBUS_VALUE_TYPE_NO_DATA,
+  MBUS_VALUE_TYPE_UNSIGNED,
+  MBUS_VALUE_TYPE_SIGNED,
+  MBUS_VALUE_TYPE_BCD,
+  MBUS_VALUE_TYPE_REAL,
+  MBUS_VALUE_TYPE_DATE,
+  MBUS_VALUE_TYPE_TIME,
+  MBUS_VALUE_TYPE_DATE_TIME,
+  // ASCII text, see `mbus_record_text`
+  MBUS_VALUE_TYPE_TEXT,
+  // A manufacturer code, see `mbus_record_text`
+  MBUS_VALUE_TYPE_MANUFACTURER,
+  // Only the raw bytes of the value are available
+  MBUS_VALUE_TYPE_OTHER,
+} MbusValueType;
+
+typedef struct {
+  MbusFrameType frame_type;
+  // The C field, 0xE5 for the single character
+  uint8_t control;
+  // The A field, 0 for the single character
+  uint8_t address;
+  // The user data of long and control frames starting with the CI field,
+  // null for the other frames
+  const uint8_t *user_data;
+  size_t user_data_length;
+} MbusFrame;
+
+// The fields which are not transmitted by the structure are zero
+typedef struct {
+  MbusUserDataType user_data_type;
+  // The byte following the CI field of a reset at application level
+  uint8_t reset_subcode;
+  uint32_t identification_number;
+  // The three letters of the manufacturer, null terminated
+  uint8_t manufacturer[4];
+  uint8_t version;
+  MbusMedium medium;
+  uint8_t access_number;
+  uint8_t status;
+  uint16_t signature;
+  uint16_t medium_and_unit;
+  uint32_t counter1;
+  uint32_t counter2;
+  // The data records of the variable data structure, to be passed to
+  // `mbus_records_begin`
+  const uint8_t *records;
+  size_t records_length;
+} MbusUserData;
+
+// The position in the data records, owned by the caller
+typedef struct {
+  const uint8_t *data;
+  size_t length;
+  size_t offset;
+  // The data following the DIF 0x0F or 0x1F, set once `mbus_records_next`
+  // returned `EndOfRecords`
+  const uint8_t *manufacturer_data;
+  size_t manufacturer_data_length;
+  // Whether the DIF 0x1F announced that more records follow in the next
+  // telegram
+  bool more_records_follow;
+} MbusRecordIterator;
+
+typedef struct {
+  uint16_t year;
+  uint8_t month;
+  uint8_t day;
+  uint8_t hour;
+  uint8_t minute;
+  uint8_t second;
+  // The time invalid bit (IV) was set by the meter
+  bool invalid;
+  // The summer time bit (SU) was set by the meter
+  bool summer_time;
+} MbusDateTime;
+
+typedef struct {
+  MbusFunctionField function;
+  uint64_t storage_number;
+  uint64_t tariff;
+  uint32_t sub_unit;
+  MbusQuantity quantity;
+  MbusUnit unit;
+  int32_t exponent;
+  MbusValueType value_type;
+  // The numeric value multiplied by 10^exponent, NaN for other values and
+  // BCD with invalid digits
+  double value;
+  // The value of `Unsigned` records
+  uint64_t unsigned_value;
+  // The value of `Signed` and `Bcd` records
+  int64_t signed_value;
+  // The value of `Date`, `Time` and `DateTime` records
+  MbusDateTime date_time;
+  // The bytes of the value as transmitted
+  const uint8_t *data;
+  size_t data_length;
+  // The unit of records with the unit `PlainText` as transmitted, that is
+  // with the last character first, see `mbus_record_unit_text`
+  const uint8_t *plain_text_unit;
+  size_t plain_text_unit_length;
+} MbusRecord;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parses the frame in `data` into `frame`
+//
+// # Safety
+//
+// `data` has to point to `length` readable bytes and `frame` to writable
+// memory for an `MbusFrame`.
+MbusStatus mbus_parse_frame(const uint8_t *data, size_t length, MbusFrame *frame);
+
+// Parses the user data of a frame, starting with the CI field, into
+// `user_data`
+//
+// # Safety
+//
+// `data` has to point to `length` readable bytes and `user_data` to writable
+// memory for an `MbusUserData`.
+MbusStatus mbus_parse_user_data(const uint8_t *data, size_t length, MbusUserData *user_data);
+
+// Starts iterating over the data records in `data`, usually the `records`
+// of an `MbusUserData`
+//
+// # Safety
+//
+// `iterator` has to point to writable memory for an `MbusRecordIterator` and
+// `data` to `length` bytes which stay readable while iterating.
+MbusStatus mbus_records_begin(const uint8_t *data, size_t length, MbusRecordIterator *iterator);
+
+// Parses the next data record into `record`, skipping idle fillers, returns
+// `EndOfRecords` after the last one
+//
+// # Safety
+//
+// `iterator` has to be initialised by `mbus_records_begin` and `record` has
+// to point to writable memory for an `MbusRecord`.
+MbusStatus mbus_records_next(MbusRecordIterator *iterator, MbusRecord *record);
+
+// Writes the text of a `Text` or `Manufacturer` record to `buffer`, null
+// terminated and truncated to `size` bytes. Returns the length of the whole
+// text, like `snprintf`.
+//
+// # Safety
+//
+// `record` has to be filled by `mbus_records_next` from data which is still
+// readable, and `buffer` has to point to `size` writable bytes.
+size_t mbus_record_text(const MbusRecord *record, char *buffer, size_t size);
+
+// Writes the plain text unit of a record to `buffer` like `mbus_record_text`
+//
+// # Safety
+//
+// See `mbus_record_text`.
+size_t mbus_record_unit_text(const MbusRecord *record, char *buffer, size_t size);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* M_BUS_PARSER_H */
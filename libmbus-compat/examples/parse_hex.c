@@ -0,0 +1,57 @@
+/*
+ * Decodes a telegram given in hex with the API of libmbus and prints it as
+ * XML followed by one line per record, built and run by the test
+ * `test_c_example` in tests/compat.rs:
+ *
+ *   cc -I include examples/parse_hex.c -L ../target/debug -lmbus
+ *   ./a.out "$(cat ../tests/rscada/test-frames/EDC.hex)"
+ */
+#include <stdio.h>
+#include <stdlib.h>
+
+#include <mbus/mbus.h>
+
+int main(int argc, char *argv[]) {
+    unsigned char data[MBUS_FRAME_LONG_BASE_SIZE + MBUS_FRAME_DATA_LENGTH];
+    size_t size = 0;
+    unsigned int byte;
+    const char *hex;
+    int consumed;
+    mbus_frame frame;
+    mbus_frame_data frame_data;
+    mbus_data_record *record;
+    char *xml;
+
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s HEX\n", argv[0]);
+        return 1;
+    }
+    for (hex = argv[1]; size < sizeof(data) && sscanf(hex, " %2x%n", &byte, &consumed) == 1;
+         hex += consumed) {
+        data[size++] = (unsigned char)byte;
+    }
+
+    if (mbus_parse(&frame, data, size) != 0) {
+        fprintf(stderr, "invalid frame\n");
+        return 1;
+    }
+    if (mbus_frame_data_parse(&frame, &frame_data) != 0) {
+        fprintf(stderr, "invalid user data\n");
+        return 1;
+    }
+    xml = mbus_frame_data_xml(&frame_data);
+    if (xml != NULL) {
+        printf("%s", xml);
+        free(xml);
+    }
+
+    if (frame_data.type == MBUS_DATA_TYPE_VARIABLE) {
+        for (record = frame_data.data_var.record; record != NULL; record = record->next) {
+            printf("%s, storage %ld: %s %s\n", mbus_data_record_function(record),
+                   mbus_data_record_storage_number(record), mbus_data_record_value(record),
+                   mbus_data_record_unit(record));
+        }
+        mbus_data_record_free(frame_data.data_var.record);
+    }
+    return 0;
+}
@@ -0,0 +1,270 @@
+#ifndef MBUS_H
+#define MBUS_H
+
+/* Generated by cbindgen from src/lib.rs, do not edit */
+
+#include <stddef.h>
+#include <stdint.h>
+#include <time.h>
+
+#define MBUS_FRAME_TYPE_ANY 0
+
+#define MBUS_FRAME_TYPE_ACK 1
+
+#define MBUS_FRAME_TYPE_SHORT 2
+
+#define MBUS_FRAME_TYPE_CONTROL 3
+
+#define MBUS_FRAME_TYPE_LONG 4
+
+#define MBUS_FRAME_ACK_BASE_SIZE 1
+
+#define MBUS_FRAME_SHORT_BASE_SIZE 5
+
+#define MBUS_FRAME_CONTROL_BASE_SIZE 9
+
+#define MBUS_FRAME_LONG_BASE_SIZE 9
+
+#define MBUS_FRAME_ACK_START 229
+
+#define MBUS_FRAME_SHORT_START 16
+
+#define MBUS_FRAME_CONTROL_START 104
+
+#define MBUS_FRAME_LONG_START 104
+
+#define MBUS_FRAME_STOP 22
+
+#define MBUS_FRAME_DATA_LENGTH 252
+
+#define MBUS_DATA_RECORD_DATA_LENGTH 234
+
+#define MBUS_DATA_VARIABLE_HEADER_LENGTH 12
+
+#define MBUS_DATA_FIXED_LENGTH 16
+
+#define MBUS_DATA_TYPE_FIXED 1
+
+#define MBUS_DATA_TYPE_VARIABLE 2
+
+#define MBUS_DATA_TYPE_ERROR 3
+
+#define MBUS_CONTROL_INFO_ERROR_GENERAL 112
+
+#define MBUS_CONTROL_INFO_RESP_VARIABLE 114
+
+#define MBUS_CONTROL_INFO_RESP_FIXED 115
+
+typedef struct mbus_frame {
+  uint8_t start1;
+  uint8_t length1;
+  uint8_t length2;
+  uint8_t start2;
+  uint8_t control;
+  uint8_t address;
+  uint8_t control_information;
+  uint8_t checksum;
+  uint8_t stop;
+  // The user data following the CI field
+  uint8_t data[MBUS_FRAME_DATA_LENGTH];
+  size_t data_size;
+  int type;
+  time_t timestamp;
+  // The next frame of a reply spanning several telegrams
+  struct mbus_frame *next;
+} mbus_frame;
+
+typedef struct mbus_data_variable_header {
+  uint8_t id_bcd[4];
+  uint8_t manufacturer[2];
+  uint8_t version;
+  uint8_t medium;
+  uint8_t access_no;
+  uint8_t status;
+  uint8_t signature[2];
+} mbus_data_variable_header;
+
+typedef struct mbus_data_information_block {
+  uint8_t dif;
+  uint8_t dife[10];
+  size_t ndife;
+} mbus_data_information_block;
+
+typedef struct mbus_value_information_block {
+  uint8_t vif;
+  uint8_t vife[10];
+  size_t nvife;
+  // The plain text unit in reading order, null terminated
+  uint8_t custom_vif[128];
+} mbus_value_information_block;
+
+typedef struct mbus_data_record_header {
+  struct mbus_data_information_block dib;
+  struct mbus_value_information_block vib;
+} mbus_data_record_header;
+
+typedef struct mbus_data_record {
+  struct mbus_data_record_header drh;
+  // The data field without the LVAR byte of variable length data
+  uint8_t data[MBUS_DATA_RECORD_DATA_LENGTH];
+  size_t data_len;
+  time_t timestamp;
+  struct mbus_data_record *next;
+} mbus_data_record;
+
+typedef struct mbus_data_variable {
+  struct mbus_data_variable_header header;
+  // The records as a list, the data following the DIF 0x0F or 0x1F is the
+  // last record
+  struct mbus_data_record *record;
+  size_t nrecords;
+  // The user data of the frame which was parsed, starting with the header
+  uint8_t *data;
+  size_t data_len;
+  uint8_t more_records_follow;
+  // The DIF 0x0F or 0x1F in front of the manufacturer data
+  uint8_t mdh;
+  uint8_t *mfg_data;
+  size_t mfg_data_len;
+} mbus_data_variable;
+
+typedef struct mbus_data_fixed {
+  uint8_t id_bcd[4];
+  uint8_t tx_cnt;
+  uint8_t status;
+  uint8_t cnt1_type;
+  uint8_t cnt2_type;
+  uint8_t cnt1_val[4];
+  uint8_t cnt2_val[4];
+} mbus_data_fixed;
+
+typedef struct mbus_frame_data {
+  struct mbus_data_variable data_var;
+  struct mbus_data_fixed data_fix;
+  int type;
+  int error;
+} mbus_frame_data;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Allocates a frame of the given type with its start and stop bytes set
+struct mbus_frame *mbus_frame_new(int frame_type);
+
+// Frees a frame allocated by `mbus_frame_new` and the frames following it
+//
+// # Safety
+//
+// `frame` has to be null or allocated by `mbus_frame_new`.
+int mbus_frame_free(struct mbus_frame *frame);
+
+// Allocates empty frame data
+struct mbus_frame_data *mbus_frame_data_new(void);
+
+// Frees frame data allocated by `mbus_frame_data_new` and its records
+//
+// # Safety
+//
+// `data` has to be null or allocated by `mbus_frame_data_new`.
+void mbus_frame_data_free(struct mbus_frame_data *data);
+
+// Frees a list of records as created by `mbus_frame_data_parse`
+//
+// # Safety
+//
+// `record` has to be null or the first record of a list created by
+// `mbus_frame_data_parse` which was not freed yet.
+void mbus_data_record_free(struct mbus_data_record *record);
+
+// Parses the frame at the start of `data` into `frame`. Returns 0 on
+// success, -1 for an invalid frame and the number of bytes which are at
+// least missing for an incomplete one.
+//
+// # Safety
+//
+// `frame` has to point to a writable frame and `data` to `data_size`
+// readable bytes.
+int mbus_parse(struct mbus_frame *frame, const uint8_t *data, size_t data_size);
+
+// Decodes the user data of a frame into `data`, the records of a variable
+// data structure are allocated and have to be freed with
+// `mbus_frame_data_free` or `mbus_data_record_free`. Returns 0 on success
+// and -1 otherwise.
+//
+// The `data` of the variable data structure points into the frame, which
+// therefore has to outlive `data` for `mbus_frame_data_xml`.
+//
+// # Safety
+//
+// `frame` has to point to a frame and `data` to writable frame data.
+int mbus_frame_data_parse(struct mbus_frame *frame, struct mbus_frame_data *data);
+
+// Prints the frame data as XML like libmbus, the string is allocated with
+// `malloc` and has to be freed by the caller. Returns null for error
+// replies and data which cannot be decoded.
+//
+// # Safety
+//
+// `data` has to point to frame data filled by `mbus_frame_data_parse` whose
+// frame is still alive.
+char *mbus_frame_data_xml(struct mbus_frame_data *data);
+
+// Packs the frame into `data`, filling in its length and checksum. Returns
+// the number of bytes written or -1 if the frame type is unknown or `data`
+// is too small.
+//
+// # Safety
+//
+// `frame` has to point to a frame and `data` to `data_size` writable bytes.
+int mbus_frame_pack(struct mbus_frame *frame, uint8_t *data, size_t data_size);
+
+// The function of the record, e.g. `Instantaneous value`
+//
+// # Safety
+//
+// `record` has to be null or point to a record. The string is overwritten by
+// the next call of an accessor on the same thread.
+const char *mbus_data_record_function(struct mbus_data_record *record);
+
+// The unit of the record as in the XML, e.g. `Energy (10 kWh)`
+//
+// # Safety
+//
+// See `mbus_data_record_function`.
+const char *mbus_data_record_unit(struct mbus_data_record *record);
+
+// The value of the record as in the XML, the data of the DIF 0x0F or 0x1F is
+// printed in hex
+//
+// # Safety
+//
+// See `mbus_data_record_function`.
+const char *mbus_data_record_value(struct mbus_data_record *record);
+
+// The storage number from the DIF and the DIFEs, -1 for a null record
+//
+// # Safety
+//
+// `record` has to be null or point to a record.
+long mbus_data_record_storage_number(struct mbus_data_record *record);
+
+// The tariff from the DIFEs, -1 if the record has none
+//
+// # Safety
+//
+// `record` has to be null or point to a record.
+long mbus_data_record_tariff(struct mbus_data_record *record);
+
+// The device, that is the subunit, from the DIFEs, -1 if the record has none
+//
+// # Safety
+//
+// `record` has to be null or point to a record.
+int mbus_data_record_device(struct mbus_data_record *record);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MBUS_H */
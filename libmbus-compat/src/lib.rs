@@ -0,0 +1,851 @@
+//! Drop-in replacement for the parser of rscada libmbus, built as `libmbus.so`
+//! with the header `include/mbus/mbus.h`.
+//!
+//! The structs have the layout of those of libmbus, so tools which were
+//! compiled against libmbus and use the common subset of its API can be
+//! linked against this library instead: parsing frames with `mbus_parse`,
+//! decoding them with `mbus_frame_data_parse`, printing them with
+//! `mbus_frame_data_xml` or the `mbus_data_record_*` accessors and packing
+//! frames with `mbus_frame_pack`. The serial and TCP handling of libmbus is
+//! not part of it.
+
+#![allow(non_camel_case_types)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_long};
+use std::ptr;
+use std::slice;
+
+use libc::time_t;
+use m_bus_parser::frames::Frame;
+use m_bus_parser::user_data::libmbus_xml::{
+    record_function, write_manufacturer_data, write_record_unit, write_record_value,
+    write_user_data_xml, XmlFormat,
+};
+use m_bus_parser::user_data::value_information::PlainTextLayout;
+use m_bus_parser::user_data::variable_user_data::DataRecord;
+use m_bus_parser::user_data::{DataRecords, ParserOptions, UserDataBlock};
+
+pub const MBUS_FRAME_TYPE_ANY: c_int = 0x00;
+pub const MBUS_FRAME_TYPE_ACK: c_int = 0x01;
+pub const MBUS_FRAME_TYPE_SHORT: c_int = 0x02;
+pub const MBUS_FRAME_TYPE_CONTROL: c_int = 0x03;
+pub const MBUS_FRAME_TYPE_LONG: c_int = 0x04;
+
+pub const MBUS_FRAME_ACK_BASE_SIZE: usize = 1;
+pub const MBUS_FRAME_SHORT_BASE_SIZE: usize = 5;
+pub const MBUS_FRAME_CONTROL_BASE_SIZE: usize = 9;
+pub const MBUS_FRAME_LONG_BASE_SIZE: usize = 9;
+
+pub const MBUS_FRAME_ACK_START: u8 = 0xE5;
+pub const MBUS_FRAME_SHORT_START: u8 = 0x10;
+pub const MBUS_FRAME_CONTROL_START: u8 = 0x68;
+pub const MBUS_FRAME_LONG_START: u8 = 0x68;
+pub const MBUS_FRAME_STOP: u8 = 0x16;
+
+pub const MBUS_FRAME_DATA_LENGTH: usize = 252;
+pub const MBUS_DATA_RECORD_DATA_LENGTH: usize = 234;
+pub const MBUS_DATA_VARIABLE_HEADER_LENGTH: usize = 12;
+pub const MBUS_DATA_FIXED_LENGTH: usize = 16;
+
+pub const MBUS_DATA_TYPE_FIXED: c_int = 1;
+pub const MBUS_DATA_TYPE_VARIABLE: c_int = 2;
+pub const MBUS_DATA_TYPE_ERROR: c_int = 3;
+
+pub const MBUS_CONTROL_INFO_ERROR_GENERAL: u8 = 0x70;
+pub const MBUS_CONTROL_INFO_RESP_VARIABLE: u8 = 0x72;
+pub const MBUS_CONTROL_INFO_RESP_FIXED: u8 = 0x73;
+
+// The size of the buffer the accessors of a record return, as in libmbus
+const VALUE_BUFFER_SIZE: usize = 768;
+
+#[repr(C)]
+pub struct mbus_frame {
+    pub start1: u8,
+    pub length1: u8,
+    pub length2: u8,
+    pub start2: u8,
+    pub control: u8,
+    pub address: u8,
+    pub control_information: u8,
+    pub checksum: u8,
+    pub stop: u8,
+    /// The user data following the CI field
+    pub data: [u8; MBUS_FRAME_DATA_LENGTH],
+    pub data_size: usize,
+    pub r#type: c_int,
+    pub timestamp: time_t,
+    /// The next frame of a reply spanning several telegrams
+    pub next: *mut mbus_frame,
+}
+
+#[repr(C)]
+pub struct mbus_data_information_block {
+    pub dif: u8,
+    pub dife: [u8; 10],
+    pub ndife: usize,
+}
+
+#[repr(C)]
+pub struct mbus_value_information_block {
+    pub vif: u8,
+    pub vife: [u8; 10],
+    pub nvife: usize,
+    /// The plain text unit in reading order, null terminated
+    pub custom_vif: [u8; 128],
+}
+
+#[repr(C)]
+pub struct mbus_data_record_header {
+    pub dib: mbus_data_information_block,
+    pub vib: mbus_value_information_block,
+}
+
+#[repr(C)]
+pub struct mbus_data_record {
+    pub drh: mbus_data_record_header,
+    /// The data field without the LVAR byte of variable length data
+    pub data: [u8; MBUS_DATA_RECORD_DATA_LENGTH],
+    pub data_len: usize,
+    pub timestamp: time_t,
+    pub next: *mut mbus_data_record,
+}
+
+#[repr(C)]
+pub struct mbus_data_variable_header {
+    pub id_bcd: [u8; 4],
+    pub manufacturer: [u8; 2],
+    pub version: u8,
+    pub medium: u8,
+    pub access_no: u8,
+    pub status: u8,
+    pub signature: [u8; 2],
+}
+
+#[repr(C)]
+pub struct mbus_data_variable {
+    pub header: mbus_data_variable_header,
+    /// The records as a list, the data following the DIF 0x0F or 0x1F is the
+    /// last record
+    pub record: *mut mbus_data_record,
+    pub nrecords: usize,
+    /// The user data of the frame which was parsed, starting with the header
+    pub data: *mut u8,
+    pub data_len: usize,
+    pub more_records_follow: u8,
+    /// The DIF 0x0F or 0x1F in front of the manufacturer data
+    pub mdh: u8,
+    pub mfg_data: *mut u8,
+    pub mfg_data_len: usize,
+}
+
+#[repr(C)]
+pub struct mbus_data_fixed {
+    pub id_bcd: [u8; 4],
+    pub tx_cnt: u8,
+    pub status: u8,
+    pub cnt1_type: u8,
+    pub cnt2_type: u8,
+    pub cnt1_val: [u8; 4],
+    pub cnt2_val: [u8; 4],
+}
+
+#[repr(C)]
+pub struct mbus_frame_data {
+    pub data_var: mbus_data_variable,
+    pub data_fix: mbus_data_fixed,
+    pub r#type: c_int,
+    pub error: c_int,
+}
+
+/// Allocates a frame of the given type with its start and stop bytes set
+#[no_mangle]
+pub extern "C" fn mbus_frame_new(frame_type: c_int) -> *mut mbus_frame {
+    // SAFETY: all fields of the frame are integers, arrays or raw pointers
+    let mut frame: Box<mbus_frame> = Box::new(unsafe { std::mem::zeroed() });
+    frame.r#type = frame_type;
+    match frame_type {
+        MBUS_FRAME_TYPE_ACK => frame.start1 = MBUS_FRAME_ACK_START,
+        MBUS_FRAME_TYPE_SHORT => {
+            frame.start1 = MBUS_FRAME_SHORT_START;
+            frame.stop = MBUS_FRAME_STOP;
+        }
+        MBUS_FRAME_TYPE_CONTROL | MBUS_FRAME_TYPE_LONG => {
+            frame.start1 = MBUS_FRAME_LONG_START;
+            frame.start2 = MBUS_FRAME_LONG_START;
+            frame.stop = MBUS_FRAME_STOP;
+        }
+        _ => {}
+    }
+    Box::into_raw(frame)
+}
+
+/// Frees a frame allocated by `mbus_frame_new` and the frames following it
+///
+/// # Safety
+///
+/// `frame` has to be null or allocated by `mbus_frame_new`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_frame_free(frame: *mut mbus_frame) -> c_int {
+    let mut frame = frame;
+    while !frame.is_null() {
+        let next = (*frame).next;
+        drop(Box::from_raw(frame));
+        frame = next;
+    }
+    0
+}
+
+/// Allocates empty frame data
+#[no_mangle]
+pub extern "C" fn mbus_frame_data_new() -> *mut mbus_frame_data {
+    // SAFETY: all fields of the frame data are integers, arrays or raw pointers
+    Box::into_raw(Box::new(unsafe { std::mem::zeroed() }))
+}
+
+/// Frees frame data allocated by `mbus_frame_data_new` and its records
+///
+/// # Safety
+///
+/// `data` has to be null or allocated by `mbus_frame_data_new`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_frame_data_free(data: *mut mbus_frame_data) {
+    if !data.is_null() {
+        mbus_data_record_free((*data).data_var.record);
+        drop(Box::from_raw(data));
+    }
+}
+
+/// Frees a list of records as created by `mbus_frame_data_parse`
+///
+/// # Safety
+///
+/// `record` has to be null or the first record of a list created by
+/// `mbus_frame_data_parse` which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_free(record: *mut mbus_data_record) {
+    let mut record = record;
+    while !record.is_null() {
+        let next = (*record).next;
+        drop(Box::from_raw(record));
+        record = next;
+    }
+}
+
+/// Parses the frame at the start of `data` into `frame`. Returns 0 on
+/// success, -1 for an invalid frame and the number of bytes which are at
+/// least missing for an incomplete one.
+///
+/// # Safety
+///
+/// `frame` has to point to a writable frame and `data` to `data_size`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_parse(
+    frame: *mut mbus_frame,
+    data: *const u8,
+    data_size: usize,
+) -> c_int {
+    if frame.is_null() || data.is_null() || data_size == 0 {
+        return -1;
+    }
+    let frame = &mut *frame;
+    let data = slice::from_raw_parts(data, data_size);
+
+    let size = match (data[0], data.get(1)) {
+        (MBUS_FRAME_ACK_START, _) => MBUS_FRAME_ACK_BASE_SIZE,
+        (MBUS_FRAME_SHORT_START, _) => MBUS_FRAME_SHORT_BASE_SIZE,
+        (MBUS_FRAME_LONG_START, None) => MBUS_FRAME_LONG_BASE_SIZE,
+        // the length covers the C, A and CI fields
+        (MBUS_FRAME_LONG_START, Some(&length)) if length < 3 => return -1,
+        (MBUS_FRAME_LONG_START, Some(&length)) => length as usize + 6,
+        _ => return -1,
+    };
+    if data.len() < size {
+        return (size - data.len()) as c_int;
+    }
+    let data = &data[..size];
+
+    match Frame::try_from(data) {
+        Ok(Frame::SingleCharacter { .. }) => {
+            frame.r#type = MBUS_FRAME_TYPE_ACK;
+            frame.start1 = data[0];
+            frame.data_size = 0;
+        }
+        Ok(Frame::ShortFrame { .. }) => {
+            frame.r#type = MBUS_FRAME_TYPE_SHORT;
+            frame.start1 = data[0];
+            frame.control = data[1];
+            frame.address = data[2];
+            frame.checksum = data[3];
+            frame.stop = data[4];
+            frame.data_size = 0;
+        }
+        Ok(Frame::LongFrame { .. } | Frame::ControlFrame { .. }) => {
+            let user_data = &data[7..size - 2];
+            frame.r#type = if user_data.is_empty() {
+                MBUS_FRAME_TYPE_CONTROL
+            } else {
+                MBUS_FRAME_TYPE_LONG
+            };
+            frame.start1 = data[0];
+            frame.length1 = data[1];
+            frame.length2 = data[2];
+            frame.start2 = data[3];
+            frame.control = data[4];
+            frame.address = data[5];
+            frame.control_information = data[6];
+            frame.data[..user_data.len()].copy_from_slice(user_data);
+            frame.data_size = user_data.len();
+            frame.checksum = data[size - 2];
+            frame.stop = data[size - 1];
+        }
+        Err(_) => return -1,
+    }
+    0
+}
+
+/// Decodes the user data of a frame into `data`, the records of a variable
+/// data structure are allocated and have to be freed with
+/// `mbus_frame_data_free` or `mbus_data_record_free`. Returns 0 on success
+/// and -1 otherwise.
+///
+/// The `data` of the variable data structure points into the frame, which
+/// therefore has to outlive `data` for `mbus_frame_data_xml`.
+///
+/// # Safety
+///
+/// `frame` has to point to a frame and `data` to writable frame data.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_frame_data_parse(
+    frame: *mut mbus_frame,
+    data: *mut mbus_frame_data,
+) -> c_int {
+    if frame.is_null() || data.is_null() || (*frame).data_size > MBUS_FRAME_DATA_LENGTH {
+        return -1;
+    }
+    let frame = &mut *frame;
+    let frame_data = frame.data.as_mut_ptr();
+    let user_data = &frame.data[..frame.data_size];
+    // SAFETY: all fields of the frame data are integers, arrays or raw pointers
+    data.write(std::mem::zeroed());
+    let data = &mut *data;
+
+    match frame.control_information {
+        MBUS_CONTROL_INFO_ERROR_GENERAL => {
+            data.r#type = MBUS_DATA_TYPE_ERROR;
+            data.error = user_data.first().copied().unwrap_or(0) as c_int;
+        }
+        MBUS_CONTROL_INFO_RESP_FIXED if user_data.len() >= MBUS_DATA_FIXED_LENGTH => {
+            let fixed = &mut data.data_fix;
+            fixed.id_bcd.copy_from_slice(&user_data[0..4]);
+            fixed.tx_cnt = user_data[4];
+            fixed.status = user_data[5];
+            fixed.cnt1_type = user_data[6];
+            fixed.cnt2_type = user_data[7];
+            fixed.cnt1_val.copy_from_slice(&user_data[8..12]);
+            fixed.cnt2_val.copy_from_slice(&user_data[12..16]);
+            data.r#type = MBUS_DATA_TYPE_FIXED;
+        }
+        MBUS_CONTROL_INFO_RESP_VARIABLE if user_data.len() >= MBUS_DATA_VARIABLE_HEADER_LENGTH => {
+            let Ok(records) = DataRecords::try_from(&user_data[MBUS_DATA_VARIABLE_HEADER_LENGTH..])
+            else {
+                return -1;
+            };
+            let variable = &mut data.data_var;
+            let header = &mut variable.header;
+            header.id_bcd.copy_from_slice(&user_data[0..4]);
+            header.manufacturer.copy_from_slice(&user_data[4..6]);
+            header.version = user_data[6];
+            header.medium = user_data[7];
+            header.access_no = user_data[8];
+            header.status = user_data[9];
+            header.signature.copy_from_slice(&user_data[10..12]);
+            variable.data = frame_data;
+            variable.data_len = frame.data_size;
+
+            let mut list = Vec::new();
+            for index in 0..records.len() {
+                if let Some(record) = records.get(index) {
+                    list.push(new_record(record));
+                }
+            }
+            if let Some(manufacturer_data) = records.manufacturer_data() {
+                let dif = if records.more_records_follow() {
+                    0x1F
+                } else {
+                    0x0F
+                };
+                // the manufacturer data ends the user data of the frame
+                let offset = frame.data_size - manufacturer_data.len();
+                variable.more_records_follow = records.more_records_follow() as u8;
+                variable.mdh = dif;
+                variable.mfg_data = frame_data.add(offset);
+                variable.mfg_data_len = manufacturer_data.len();
+                list.push(new_special_record(dif, manufacturer_data));
+            }
+            variable.nrecords = list.len();
+            variable.record = list
+                .into_iter()
+                .rev()
+                .fold(ptr::null_mut(), |next, mut record| {
+                    record.next = next;
+                    Box::into_raw(record)
+                });
+            data.r#type = MBUS_DATA_TYPE_VARIABLE;
+        }
+        _ => return -1,
+    }
+    0
+}
+
+/// Prints the frame data as XML like libmbus, the string is allocated with
+/// `malloc` and has to be freed by the caller. Returns null for error
+/// replies and data which cannot be decoded.
+///
+/// # Safety
+///
+/// `data` has to point to frame data filled by `mbus_frame_data_parse` whose
+/// frame is still alive.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_frame_data_xml(data: *mut mbus_frame_data) -> *mut c_char {
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+    let data = &*data;
+    let mut user_data = Vec::new();
+    match data.r#type {
+        MBUS_DATA_TYPE_FIXED => {
+            let fixed = &data.data_fix;
+            user_data.push(MBUS_CONTROL_INFO_RESP_FIXED);
+            user_data.extend_from_slice(&fixed.id_bcd);
+            user_data.extend_from_slice(&[
+                fixed.tx_cnt,
+                fixed.status,
+                fixed.cnt1_type,
+                fixed.cnt2_type,
+            ]);
+            user_data.extend_from_slice(&fixed.cnt1_val);
+            user_data.extend_from_slice(&fixed.cnt2_val);
+        }
+        MBUS_DATA_TYPE_VARIABLE if !data.data_var.data.is_null() => {
+            user_data.push(MBUS_CONTROL_INFO_RESP_VARIABLE);
+            user_data.extend_from_slice(slice::from_raw_parts(
+                data.data_var.data,
+                data.data_var.data_len,
+            ));
+        }
+        _ => return ptr::null_mut(),
+    }
+
+    let mut xml = String::new();
+    let written = UserDataBlock::try_from(user_data.as_slice())
+        .map_err(|_| ())
+        .and_then(|user_data| {
+            write_user_data_xml(&mut xml, &user_data, XmlFormat::Plain).map_err(|_| ())
+        });
+    if written.is_err() {
+        return ptr::null_mut();
+    }
+    let string = libc::malloc(xml.len() + 1) as *mut u8;
+    if !string.is_null() {
+        ptr::copy_nonoverlapping(xml.as_ptr(), string, xml.len());
+        *string.add(xml.len()) = 0;
+    }
+    string as *mut c_char
+}
+
+/// Packs the frame into `data`, filling in its length and checksum. Returns
+/// the number of bytes written or -1 if the frame type is unknown or `data`
+/// is too small.
+///
+/// # Safety
+///
+/// `frame` has to point to a frame and `data` to `data_size` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_frame_pack(
+    frame: *mut mbus_frame,
+    data: *mut u8,
+    data_size: usize,
+) -> c_int {
+    if frame.is_null() || data.is_null() || (*frame).data_size > MBUS_FRAME_DATA_LENGTH {
+        return -1;
+    }
+    let frame = &mut *frame;
+    let data = slice::from_raw_parts_mut(data, data_size);
+
+    let size = match frame.r#type {
+        MBUS_FRAME_TYPE_ACK => MBUS_FRAME_ACK_BASE_SIZE,
+        MBUS_FRAME_TYPE_SHORT => MBUS_FRAME_SHORT_BASE_SIZE,
+        MBUS_FRAME_TYPE_CONTROL | MBUS_FRAME_TYPE_LONG => {
+            MBUS_FRAME_LONG_BASE_SIZE + frame.data_size
+        }
+        _ => return -1,
+    };
+    if data.len() < size {
+        return -1;
+    }
+    match frame.r#type {
+        MBUS_FRAME_TYPE_ACK => {
+            frame.start1 = MBUS_FRAME_ACK_START;
+            data[0] = frame.start1;
+        }
+        MBUS_FRAME_TYPE_SHORT => {
+            frame.start1 = MBUS_FRAME_SHORT_START;
+            frame.checksum = frame.control.wrapping_add(frame.address);
+            frame.stop = MBUS_FRAME_STOP;
+            data[..size].copy_from_slice(&[
+                frame.start1,
+                frame.control,
+                frame.address,
+                frame.checksum,
+                frame.stop,
+            ]);
+        }
+        _ => {
+            let user_data = &frame.data[..frame.data_size];
+            frame.start1 = MBUS_FRAME_LONG_START;
+            frame.start2 = MBUS_FRAME_LONG_START;
+            frame.length1 = (frame.data_size + 3) as u8;
+            frame.length2 = frame.length1;
+            frame.checksum = user_data.iter().fold(
+                frame
+                    .control
+                    .wrapping_add(frame.address)
+                    .wrapping_add(frame.control_information),
+                |sum, &byte| sum.wrapping_add(byte),
+            );
+            frame.stop = MBUS_FRAME_STOP;
+            data[..7].copy_from_slice(&[
+                frame.start1,
+                frame.length1,
+                frame.length2,
+                frame.start2,
+                frame.control,
+                frame.address,
+                frame.control_information,
+            ]);
+            data[7..size - 2].copy_from_slice(user_data);
+            data[size - 2] = frame.checksum;
+            data[size - 1] = frame.stop;
+        }
+    }
+    size as c_int
+}
+
+/// The function of the record, e.g. `Instantaneous value`
+///
+/// # Safety
+///
+/// `record` has to be null or point to a record. The string is overwritten by
+/// the next call of an accessor on the same thread.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_function(record: *mut mbus_data_record) -> *const c_char {
+    if record.is_null() {
+        return ptr::null();
+    }
+    buffered(record_function((*record).drh.dib.dif))
+}
+
+/// The unit of the record as in the XML, e.g. `Energy (10 kWh)`
+///
+/// # Safety
+///
+/// See `mbus_data_record_function`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_unit(record: *mut mbus_data_record) -> *const c_char {
+    if record.is_null() {
+        return ptr::null();
+    }
+    let record = &*record;
+    let mut unit = String::new();
+    if !is_special(record) {
+        let bytes = record_bytes(record);
+        if let Ok(record) = DataRecord::parse(&bytes, &COMPAT_OPTIONS) {
+            let _ = write_record_unit(&mut unit, &record);
+        }
+    }
+    buffered(&unit)
+}
+
+/// The value of the record as in the XML, the data of the DIF 0x0F or 0x1F is
+/// printed in hex
+///
+/// # Safety
+///
+/// See `mbus_data_record_function`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_value(record: *mut mbus_data_record) -> *const c_char {
+    if record.is_null() {
+        return ptr::null();
+    }
+    let record = &*record;
+    let mut value = String::new();
+    if is_special(record) {
+        let _ = write_manufacturer_data(&mut value, record_data(record));
+    } else {
+        let bytes = record_bytes(record);
+        if let Ok(record) = DataRecord::parse(&bytes, &COMPAT_OPTIONS) {
+            let _ = write_record_value(&mut value, &record);
+        }
+    }
+    buffered(&value)
+}
+
+/// The storage number from the DIF and the DIFEs, -1 for a null record
+///
+/// # Safety
+///
+/// `record` has to be null or point to a record.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_storage_number(record: *mut mbus_data_record) -> c_long {
+    if record.is_null() {
+        return -1;
+    }
+    let dib = &(*record).drh.dib;
+    difes(dib).enumerate().fold(
+        ((dib.dif & 0x40) >> 6) as c_long,
+        |number, (index, dife)| number | (((dife & 0x0F) as c_long) << (1 + 4 * index)),
+    )
+}
+
+/// The tariff from the DIFEs, -1 if the record has none
+///
+/// # Safety
+///
+/// `record` has to be null or point to a record.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_tariff(record: *mut mbus_data_record) -> c_long {
+    if record.is_null() || (*record).drh.dib.ndife == 0 {
+        return -1;
+    }
+    difes(&(*record).drh.dib)
+        .enumerate()
+        .fold(0, |tariff, (index, dife)| {
+            tariff | ((((dife & 0x30) >> 4) as c_long) << (2 * index))
+        })
+}
+
+/// The device, that is the subunit, from the DIFEs, -1 if the record has none
+///
+/// # Safety
+///
+/// `record` has to be null or point to a record.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_data_record_device(record: *mut mbus_data_record) -> c_int {
+    if record.is_null() || (*record).drh.dib.ndife == 0 {
+        return -1;
+    }
+    difes(&(*record).drh.dib)
+        .enumerate()
+        .fold(0, |device, (index, dife)| {
+            device | ((((dife & 0x40) >> 6) as c_int) << index)
+        })
+}
+
+fn difes(dib: &mbus_data_information_block) -> impl Iterator<Item = u8> + '_ {
+    dib.dife[..dib.ndife.min(dib.dife.len())].iter().copied()
+}
+
+fn is_special(record: &mbus_data_record) -> bool {
+    matches!(record.drh.dib.dif, 0x0F | 0x1F)
+}
+
+// The value information block as transmitted, with the plain text unit
+// between the VIF and the VIFEs
+fn value_information(record: &mbus_data_record) -> Vec<u8> {
+    let vib = &record.drh.vib;
+    let mut bytes = vec![vib.vif];
+    if vib.vif & 0x7F == 0x7C {
+        let text = vib
+            .custom_vif
+            .split(|&byte| byte == 0)
+            .next()
+            .unwrap_or(&[]);
+        bytes.push(text.len() as u8);
+        bytes.extend(text.iter().rev());
+    }
+    bytes.extend_from_slice(&vib.vife[..vib.nvife.min(vib.vife.len())]);
+    bytes
+}
+
+// The value information block is put together with the plain text unit
+// before the VIFEs
+const COMPAT_OPTIONS: ParserOptions = ParserOptions {
+    plain_text_layout: PlainTextLayout::Common,
+    manufacturer_decoders: &[],
+};
+
+fn record_data(record: &mbus_data_record) -> &[u8] {
+    &record.data[..record.data_len.min(MBUS_DATA_RECORD_DATA_LENGTH)]
+}
+
+// The record as transmitted, to be parsed again. The LVAR byte of variable
+// length data is not kept, libmbus prints all of it as text anyway, so the
+// data is given the LVAR of a text.
+fn record_bytes(record: &mbus_data_record) -> Vec<u8> {
+    let dib = &record.drh.dib;
+    let mut bytes = vec![dib.dif];
+    bytes.extend(difes(dib));
+    bytes.extend(value_information(record));
+    let mut data = record_data(record);
+    if dib.dif & 0x0F == 0x0D {
+        data = &data[..data.len().min(0xBF)];
+        bytes.push(data.len() as u8);
+    }
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+fn new_record(record: &DataRecord) -> Box<mbus_data_record> {
+    // SAFETY: all fields of the record are integers, arrays or raw pointers
+    let mut result: Box<mbus_data_record> = Box::new(unsafe { std::mem::zeroed() });
+    let dib = &mut result.drh.dib;
+    if let Some((&dif, difes)) = record.raw_data_information.split_first() {
+        dib.dif = dif;
+        dib.ndife = difes.len().min(dib.dife.len());
+        dib.dife[..dib.ndife].copy_from_slice(&difes[..dib.ndife]);
+    }
+
+    let vib = &mut result.drh.vib;
+    let raw = record.raw_value_information;
+    let mut vifes = Vec::new();
+    if let Some((&vif, rest)) = raw.split_first() {
+        vib.vif = vif;
+        match record.plain_text_unit {
+            // the text is a part of the raw value information, with the
+            // length in front of it and the VIFEs before or after it
+            Some(text) => {
+                let start = text.bytes.as_ptr() as usize - raw.as_ptr() as usize;
+                vifes.extend_from_slice(&raw[1..start - 1]);
+                vifes.extend_from_slice(&raw[start + text.bytes.len()..]);
+                let length = text.bytes.len().min(vib.custom_vif.len() - 1);
+                for (target, &byte) in vib.custom_vif.iter_mut().zip(text.bytes.iter().rev()) {
+                    *target = byte;
+                }
+                vib.custom_vif[length] = 0;
+            }
+            None => vifes.extend_from_slice(rest),
+        }
+    }
+    vib.nvife = vifes.len().min(vib.vife.len());
+    vib.vife[..vib.nvife].copy_from_slice(&vifes[..vib.nvife]);
+
+    let data = match (result.drh.dib.dif & 0x0F, record.raw_data.split_first()) {
+        (0x0D, Some((_, data))) => data,
+        _ => record.raw_data,
+    };
+    set_data(&mut result, data);
+    result
+}
+
+fn new_special_record(dif: u8, data: &[u8]) -> Box<mbus_data_record> {
+    // SAFETY: all fields of the record are integers, arrays or raw pointers
+    let mut result: Box<mbus_data_record> = Box::new(unsafe { std::mem::zeroed() });
+    result.drh.dib.dif = dif;
+    set_data(&mut result, data);
+    result
+}
+
+fn set_data(record: &mut mbus_data_record, data: &[u8]) {
+    record.data_len = data.len().min(MBUS_DATA_RECORD_DATA_LENGTH);
+    record.data[..record.data_len].copy_from_slice(&data[..record.data_len]);
+    // SAFETY: time accepts a null pointer
+    record.timestamp = unsafe { libc::time(ptr::null_mut()) };
+}
+
+thread_local! {
+    static VALUE_BUFFER: RefCell<[u8; VALUE_BUFFER_SIZE]> =
+        const { RefCell::new([0; VALUE_BUFFER_SIZE]) };
+}
+
+// Copies the text to the buffer of the thread like libmbus copies it to a
+// static one, truncated and null terminated
+fn buffered(text: &str) -> *const c_char {
+    VALUE_BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        let length = text.len().min(VALUE_BUFFER_SIZE - 1);
+        buffer[..length].copy_from_slice(&text.as_bytes()[..length]);
+        buffer[length] = 0;
+        buffer.as_ptr() as *const c_char
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_incomplete_frames() {
+        let mut frame = unsafe { Box::from_raw(mbus_frame_new(MBUS_FRAME_TYPE_ANY)) };
+        let data = [0x68, 0x4D, 0x4D, 0x68, 0x08, 0x01];
+        assert_eq!(unsafe { mbus_parse(&mut *frame, data.as_ptr(), 1) }, 8);
+        assert_eq!(
+            unsafe { mbus_parse(&mut *frame, data.as_ptr(), data.len()) },
+            0x4D + 6 - 6
+        );
+        let data = [0x10, 0x5B];
+        assert_eq!(unsafe { mbus_parse(&mut *frame, data.as_ptr(), 2) }, 3);
+        let data = [0x10, 0x5B, 0xFE, 0x58, 0x16];
+        assert_eq!(unsafe { mbus_parse(&mut *frame, data.as_ptr(), 5) }, -1);
+        let data = [0xE5];
+        assert_eq!(unsafe { mbus_parse(&mut *frame, data.as_ptr(), 1) }, 0);
+        assert_eq!(frame.r#type, MBUS_FRAME_TYPE_ACK);
+    }
+
+    #[test]
+    fn test_pack() {
+        let mut buffer = [0; 16];
+        let frame = mbus_frame_new(MBUS_FRAME_TYPE_SHORT);
+        unsafe {
+            (*frame).control = 0x5B;
+            (*frame).address = 0xFE;
+            let length = mbus_frame_pack(frame, buffer.as_mut_ptr(), buffer.len());
+            assert_eq!(&buffer[..length as usize], [0x10, 0x5B, 0xFE, 0x59, 0x16]);
+            mbus_frame_free(frame);
+        }
+
+        // the frame setting the primary address to 8 from the README
+        let frame = mbus_frame_new(MBUS_FRAME_TYPE_LONG);
+        let parsed = mbus_frame_new(MBUS_FRAME_TYPE_ANY);
+        unsafe {
+            let long = &mut *frame;
+            long.control = 0x53;
+            long.address = 0xFE;
+            long.control_information = 0x51;
+            long.data[..3].copy_from_slice(&[0x01, 0x7A, 0x08]);
+            long.data_size = 3;
+            assert_eq!(mbus_frame_pack(frame, buffer.as_mut_ptr(), 11), -1);
+            let length = mbus_frame_pack(frame, buffer.as_mut_ptr(), buffer.len());
+            let expected = [
+                0x68, 0x06, 0x06, 0x68, 0x53, 0xFE, 0x51, 0x01, 0x7A, 0x08, 0x25, 0x16,
+            ];
+            assert_eq!(&buffer[..length as usize], expected);
+
+            assert_eq!(mbus_parse(parsed, expected.as_ptr(), expected.len()), 0);
+            let parsed = &*parsed;
+            assert_eq!(parsed.r#type, MBUS_FRAME_TYPE_LONG);
+            assert_eq!(parsed.data[..parsed.data_size], [0x01, 0x7A, 0x08]);
+        }
+        unsafe {
+            mbus_frame_free(parsed);
+            mbus_frame_free(frame);
+        }
+    }
+
+    #[test]
+    fn test_record_header() {
+        // storage number 3, tariff 1 and device 1
+        let data = [0xCC, 0x51, 0x13, 0x12, 0x34, 0x56, 0x78];
+        let records = DataRecords::try_from(data.as_slice()).unwrap();
+        let record = Box::into_raw(new_record(records.get(0).unwrap()));
+        unsafe {
+            assert_eq!(mbus_data_record_storage_number(record), 3);
+            assert_eq!(mbus_data_record_tariff(record), 1);
+            assert_eq!(mbus_data_record_device(record), 1);
+            assert_eq!((*record).drh.vib.vif, 0x13);
+            assert_eq!((*record).data_len, 4);
+            mbus_data_record_free(record);
+        }
+    }
+}
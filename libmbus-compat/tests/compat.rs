@@ -0,0 +1,195 @@
+use std::env;
+use std::ffi::{c_char, CStr};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use mbus::*;
+use walkdir::WalkDir;
+
+const HEADER: &str = "include/mbus/mbus.h";
+const CORPUS: &str = "../tests/rscada/test-frames";
+
+/// Regenerates the C header and fails if the checked in one was outdated
+#[test]
+fn test_header() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let config = cbindgen::Config::from_file(Path::new(root).join("cbindgen.toml")).unwrap();
+    let mut header = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(root)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut header);
+
+    let path = Path::new(root).join(HEADER);
+    if fs::read(&path).ok().as_ref() != Some(&header) {
+        fs::write(&path, &header).unwrap();
+        panic!("{} was outdated and has been regenerated", HEADER);
+    }
+}
+
+fn corpus() -> impl Iterator<Item = (walkdir::DirEntry, Vec<u8>)> {
+    WalkDir::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(CORPUS))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "hex"))
+        .map(|entry| {
+            let contents = fs::read_to_string(entry.path()).unwrap();
+            let bytes = hex::decode(contents.trim().replace(' ', "")).unwrap();
+            (entry, bytes)
+        })
+}
+
+// The content of the first element `tag` in `xml`, unescaped
+fn element(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(
+        xml[start..end]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+unsafe fn string(text: *const c_char) -> String {
+    CStr::from_ptr(text).to_string_lossy().into_owned()
+}
+
+// Every frame of the corpus is printed as libmbus printed it, both as XML and
+// with the accessors of the records of a variable data structure
+#[test]
+fn test_corpus() {
+    for (entry, bytes) in corpus() {
+        let name = entry.path().display();
+        let expected = fs::read_to_string(entry.path().with_extension("xml")).unwrap();
+        unsafe {
+            let frame = mbus_frame_new(MBUS_FRAME_TYPE_ANY);
+            let data = mbus_frame_data_new();
+            assert_eq!(
+                mbus_parse(frame, bytes.as_ptr(), bytes.len()),
+                0,
+                "{}",
+                name
+            );
+            assert_eq!(mbus_frame_data_parse(frame, data), 0, "{}", name);
+
+            let xml = mbus_frame_data_xml(data);
+            assert!(!xml.is_null(), "{}", name);
+            assert_eq!(string(xml), expected, "{}", name);
+            libc::free(xml.cast());
+
+            if (*data).r#type != MBUS_DATA_TYPE_VARIABLE {
+                mbus_frame_data_free(data);
+                mbus_frame_free(frame);
+                continue;
+            }
+            let mut record = (*data).data_var.record;
+            let mut records = expected.split("<DataRecord id=").skip(1);
+            let mut count = 0;
+            while !record.is_null() {
+                let expected = records.next().unwrap();
+                let field = |tag| element(expected, tag).unwrap_or_default();
+                assert_eq!(string(mbus_data_record_function(record)), field("Function"));
+                assert_eq!(string(mbus_data_record_unit(record)), field("Unit"));
+                assert_eq!(string(mbus_data_record_value(record)), field("Value"));
+                if let Some(storage_number) = element(expected, "StorageNumber") {
+                    assert_eq!(
+                        mbus_data_record_storage_number(record).to_string(),
+                        storage_number
+                    );
+                }
+                let tariff = element(expected, "Tariff").unwrap_or("-1".to_string());
+                assert_eq!(mbus_data_record_tariff(record).to_string(), tariff);
+                let device = element(expected, "Device").unwrap_or("-1".to_string());
+                assert_eq!(mbus_data_record_device(record).to_string(), device);
+                record = (*record).next;
+                count += 1;
+            }
+            assert!(records.next().is_none(), "{}", name);
+            assert_eq!((*data).data_var.nrecords, count, "{}", name);
+
+            mbus_frame_data_free(data);
+            mbus_frame_free(frame);
+        }
+    }
+}
+
+// Packing a parsed frame gives back the bytes it was parsed from
+#[test]
+fn test_pack_corpus() {
+    for (entry, bytes) in corpus() {
+        let mut packed = [0; MBUS_FRAME_LONG_BASE_SIZE + MBUS_FRAME_DATA_LENGTH];
+        unsafe {
+            let frame = mbus_frame_new(MBUS_FRAME_TYPE_ANY);
+            assert_eq!(mbus_parse(frame, bytes.as_ptr(), bytes.len()), 0);
+            let length = mbus_frame_pack(frame, packed.as_mut_ptr(), packed.len());
+            assert_eq!(
+                &packed[..length as usize],
+                bytes,
+                "{}",
+                entry.path().display()
+            );
+            mbus_frame_free(frame);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_c_example() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // cargo test does not build the cdylib, so it is built into a target
+    // directory of its own which does not block the running cargo
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libmbus-compat");
+    let build = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--offline", "--manifest-path"])
+        .arg(root.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "{}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+    let library = target.join("debug");
+    let executable = Path::new(env!("CARGO_TARGET_TMPDIR")).join("parse_hex");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let status = Command::new(compiler)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&executable)
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(root.join("examples/parse_hex.c"))
+        .arg("-L")
+        .arg(&library)
+        .arg(format!("-Wl,-rpath,{}", library.display()))
+        .arg("-lmbus")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let frame = root.join(CORPUS).join("EDC.hex");
+    let output = Command::new(&executable)
+        .arg(fs::read_to_string(&frame).unwrap().trim())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+    let expected = fs::read_to_string(frame.with_extension("xml")).unwrap();
+    let (xml, records) = output.split_at(expected.len());
+    assert_eq!(xml, expected);
+    assert_eq!(
+        records.lines().count(),
+        expected.matches("<DataRecord id=").count()
+    );
+    assert!(records.starts_with("Instantaneous value, storage 0: "));
+}
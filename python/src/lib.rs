@@ -0,0 +1,481 @@
+//! Python bindings of m-bus-parser, built with `maturin build`.
+//!
+//! ```python
+//! import m_bus_parser
+//!
+//! frame = m_bus_parser.parse_frame(bytes.fromhex("68 1F 1F 68 08 02 72 ..."))
+//! user_data = m_bus_parser.parse_user_data(frame.user_data)
+//! rows = [record.to_dict() for record in user_data.records]
+//! ```
+//!
+//! The parsed frames, user data and records are copied into Python objects,
+//! so that they outlive the bytes they were parsed from.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+
+use m_bus_parser::frames::Frame as MbusFrame;
+use m_bus_parser::user_data::data_information::DataValue;
+use m_bus_parser::user_data::date_time::DateTime;
+use m_bus_parser::user_data::variable_user_data::DataRecord;
+use m_bus_parser::user_data::{DataRecords, UserDataBlock};
+
+create_exception!(
+    m_bus_parser,
+    MBusError,
+    PyValueError,
+    "Raised for frames and user data which can not be parsed"
+);
+
+#[pyclass(frozen, module = "m_bus_parser")]
+pub struct Frame {
+    /// `single_character`, `short`, `long` or `control`
+    #[pyo3(get)]
+    kind: &'static str,
+    /// The C field, or the character of a single character frame
+    #[pyo3(get)]
+    control: u8,
+    #[pyo3(get)]
+    function: Option<String>,
+    /// The A field, `None` for a single character frame
+    #[pyo3(get)]
+    address: Option<u8>,
+    user_data: Option<Vec<u8>>,
+}
+
+#[pymethods]
+impl Frame {
+    /// The user data of a long or control frame, `None` otherwise
+    #[getter]
+    fn user_data<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.user_data
+            .as_deref()
+            .map(|user_data| PyBytes::new(py, user_data))
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("kind", self.kind)?;
+        dict.set_item("control", self.control)?;
+        dict.set_item("function", &self.function)?;
+        dict.set_item("address", self.address)?;
+        dict.set_item("user_data", self.user_data(py))?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        repr(
+            "Frame",
+            &self.to_dict(py)?,
+            &["kind", "control", "function", "address"],
+        )
+    }
+}
+
+/// The value of a record as it is handed to Python
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    None,
+    Integer(i128),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    fn from_record(record: &DataRecord) -> Self {
+        if let Some(decimal) = record.decimal_value() {
+            // integers stay exact, only scaling them down makes them floats
+            return match decimal.exponent {
+                0.. => decimal
+                    .with_exponent(0)
+                    .map_or(Value::Float(decimal.to_f64()), |decimal| {
+                        Value::Integer(decimal.mantissa)
+                    }),
+                _ => Value::Float(decimal.to_f64()),
+            };
+        }
+        match record.value {
+            DataValue::Real(_) => record.scaled_value().map_or(Value::None, Value::Float),
+            DataValue::NoData => Value::None,
+            // Meters send invalid digits like FFFF while they can not measure
+            DataValue::Bcd(_) => Value::Text(
+                record
+                    .raw_data
+                    .iter()
+                    .rev()
+                    .map(|byte| format!("{:02X}", byte))
+                    .collect(),
+            ),
+            DataValue::Date(date) => Value::Text(format!(
+                "{:04}-{:02}-{:02}",
+                date.year, date.month, date.day
+            )),
+            DataValue::Time(time) => Value::Text(format!(
+                "{:02}:{:02}:{:02}",
+                time.hour, time.minute, time.second
+            )),
+            DataValue::DateTime(date_time) => {
+                let DateTime { date, time, .. } = date_time;
+                let mut text = format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    date.year, date.month, date.day, time.hour, time.minute, time.second
+                );
+                if let Some(offset) = date_time.utc_offset {
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    let offset = offset.unsigned_abs();
+                    text += &format!("{}{:02}:{:02}", sign, offset / 60, offset % 60);
+                }
+                Value::Text(text)
+            }
+            DataValue::Manufacturer(code) => Value::Text(code.code.iter().collect()),
+            DataValue::Medium(medium) => Value::Text(medium.to_string()),
+            DataValue::Ascii(text) => Value::Text(text.to_string()),
+            DataValue::Raw(bytes) => Value::Bytes(bytes.to_vec()),
+            value => Value::Text(format!("{:?}", value)),
+        }
+    }
+
+    fn to_object<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(match self {
+            Value::None => py.None().into_bound(py),
+            Value::Integer(value) => value.into_pyobject(py)?.into_any(),
+            Value::Float(value) => value.into_pyobject(py)?.into_any(),
+            Value::Text(value) => value.into_pyobject(py)?.into_any(),
+            Value::Bytes(value) => PyBytes::new(py, value).into_any(),
+        })
+    }
+}
+
+#[pyclass(frozen, skip_from_py_object, module = "m_bus_parser")]
+#[derive(Clone)]
+pub struct Record {
+    #[pyo3(get)]
+    storage: u64,
+    #[pyo3(get)]
+    tariff: u64,
+    #[pyo3(get)]
+    subunit: u32,
+    /// `Instantaneous value`, `Maximum value`, `Minimum value` or
+    /// `Value during error state`
+    #[pyo3(get)]
+    function: String,
+    #[pyo3(get)]
+    quantity: String,
+    /// The unit of the value, the text of a plain text VIF if there is one
+    #[pyo3(get)]
+    unit: String,
+    value: Value,
+}
+
+impl From<&DataRecord<'_>> for Record {
+    fn from(record: &DataRecord) -> Self {
+        Record {
+            storage: record.storage_number,
+            tariff: record.tariff,
+            subunit: record.sub_unit,
+            function: record.function.to_string(),
+            quantity: record.quantity.to_string(),
+            unit: match record.plain_text_unit {
+                Some(text) => text.to_string(),
+                None => record.unit.to_string(),
+            },
+            value: Value::from_record(record),
+        }
+    }
+}
+
+#[pymethods]
+impl Record {
+    /// The value scaled to the unit; integers and BCD are `int` unless the
+    /// exponent is negative, dates and times are ISO 8601 strings and
+    /// undecodable BCD the hex digits as sent
+    #[getter]
+    fn value<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.value.to_object(py)
+    }
+
+    /// The attributes as a dict, one row of a pandas DataFrame
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("storage", self.storage)?;
+        dict.set_item("tariff", self.tariff)?;
+        dict.set_item("subunit", self.subunit)?;
+        dict.set_item("function", &self.function)?;
+        dict.set_item("quantity", &self.quantity)?;
+        dict.set_item("unit", &self.unit)?;
+        dict.set_item("value", self.value(py)?)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        repr(
+            "Record",
+            &self.to_dict(py)?,
+            &["quantity", "value", "unit", "function", "storage", "tariff"],
+        )
+    }
+}
+
+/// The user data of a frame; which attributes are set depends on `kind`
+#[pyclass(frozen, module = "m_bus_parser")]
+pub struct UserData {
+    /// `variable_data_structure`, `fixed_data_structure` or
+    /// `reset_at_application_level`
+    #[pyo3(get)]
+    kind: &'static str,
+    #[pyo3(get)]
+    reset_subcode: Option<String>,
+    /// The eight BCD digits of the identification number
+    #[pyo3(get)]
+    identification_number: Option<String>,
+    #[pyo3(get)]
+    manufacturer: Option<String>,
+    #[pyo3(get)]
+    version: Option<u8>,
+    #[pyo3(get)]
+    medium: Option<String>,
+    #[pyo3(get)]
+    access_number: Option<u8>,
+    #[pyo3(get)]
+    status: Option<u8>,
+    #[pyo3(get)]
+    signature: Option<u16>,
+    #[pyo3(get)]
+    medium_and_unit: Option<u16>,
+    #[pyo3(get)]
+    counter1: Option<u32>,
+    #[pyo3(get)]
+    counter2: Option<u32>,
+    records: Vec<Record>,
+    manufacturer_data: Option<Vec<u8>>,
+    #[pyo3(get)]
+    more_records_follow: bool,
+}
+
+impl UserData {
+    fn new(kind: &'static str) -> Self {
+        UserData {
+            kind,
+            reset_subcode: None,
+            identification_number: None,
+            manufacturer: None,
+            version: None,
+            medium: None,
+            access_number: None,
+            status: None,
+            signature: None,
+            medium_and_unit: None,
+            counter1: None,
+            counter2: None,
+            records: Vec::new(),
+            manufacturer_data: None,
+            more_records_follow: false,
+        }
+    }
+}
+
+#[pymethods]
+impl UserData {
+    /// The data records of a variable data structure
+    #[getter]
+    fn records<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        PyList::new(py, self.records.iter().cloned())
+    }
+
+    /// The data after the DIF 0x0F or 0x1F of a variable data structure
+    #[getter]
+    fn manufacturer_data<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.manufacturer_data
+            .as_deref()
+            .map(|data| PyBytes::new(py, data))
+    }
+
+    /// The attributes as a dict, with the records as a list of dicts
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("kind", self.kind)?;
+        dict.set_item("reset_subcode", &self.reset_subcode)?;
+        dict.set_item("identification_number", &self.identification_number)?;
+        dict.set_item("manufacturer", &self.manufacturer)?;
+        dict.set_item("version", self.version)?;
+        dict.set_item("medium", &self.medium)?;
+        dict.set_item("access_number", self.access_number)?;
+        dict.set_item("status", self.status)?;
+        dict.set_item("signature", self.signature)?;
+        dict.set_item("medium_and_unit", self.medium_and_unit)?;
+        dict.set_item("counter1", self.counter1)?;
+        dict.set_item("counter2", self.counter2)?;
+        let records = self
+            .records
+            .iter()
+            .map(|record| record.to_dict(py))
+            .collect::<PyResult<Vec<_>>>()?;
+        dict.set_item("records", records)?;
+        dict.set_item("manufacturer_data", self.manufacturer_data(py))?;
+        dict.set_item("more_records_follow", self.more_records_follow)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        repr(
+            "UserData",
+            &self.to_dict(py)?,
+            &["kind", "identification_number", "manufacturer", "medium"],
+        )
+    }
+}
+
+// `name(key=value, ...)` with the Python representations of the values
+fn repr(name: &str, dict: &Bound<'_, PyDict>, keys: &[&str]) -> PyResult<String> {
+    let mut fields = Vec::new();
+    for key in keys {
+        if let Some(value) = dict.get_item(key)? {
+            fields.push(format!("{}={}", key, value.repr()?));
+        }
+    }
+    Ok(format!("{}({})", name, fields.join(", ")))
+}
+
+/// Parses a frame of the link layer, raises `MBusError` if it is invalid
+#[pyfunction]
+fn parse_frame(data: &[u8]) -> PyResult<Frame> {
+    let frame = MbusFrame::try_from(data)
+        .map_err(|error| MBusError::new_err(format!("invalid frame: {}", error)))?;
+    Ok(match frame {
+        MbusFrame::SingleCharacter { character } => Frame {
+            kind: "single_character",
+            control: character,
+            function: None,
+            address: None,
+            user_data: None,
+        },
+        MbusFrame::ShortFrame { function, .. } => Frame {
+            kind: "short",
+            control: data[1],
+            function: Some(function.to_string()),
+            address: Some(data[2]),
+            user_data: None,
+        },
+        MbusFrame::LongFrame {
+            function,
+            data: user_data,
+            ..
+        } => Frame {
+            kind: "long",
+            control: data[4],
+            function: Some(function.to_string()),
+            address: Some(data[5]),
+            user_data: Some(user_data.to_vec()),
+        },
+        MbusFrame::ControlFrame {
+            function,
+            data: user_data,
+            ..
+        } => Frame {
+            kind: "control",
+            control: data[4],
+            function: Some(function.to_string()),
+            address: Some(data[5]),
+            user_data: Some(user_data.to_vec()),
+        },
+    })
+}
+
+/// Parses the user data of a long or control frame starting with the CI
+/// field, raises `MBusError` if it or one of its records is invalid
+#[pyfunction]
+fn parse_user_data(data: &[u8]) -> PyResult<UserData> {
+    let user_data = UserDataBlock::try_from(data)
+        .map_err(|error| MBusError::new_err(format!("invalid user data: {}", error)))?;
+    Ok(match user_data {
+        UserDataBlock::ResetAtApplicationLevel { subcode } => UserData {
+            reset_subcode: Some(subcode.to_string()),
+            ..UserData::new("reset_at_application_level")
+        },
+        UserDataBlock::FixedDataStructure {
+            identification_number,
+            access_number,
+            status,
+            medium_ad_unit,
+            counter1,
+            counter2,
+        } => UserData {
+            identification_number: Some(format!("{:08}", u32::from(identification_number))),
+            access_number: Some(access_number),
+            status: Some(status.bits()),
+            medium_and_unit: Some(medium_ad_unit),
+            counter1: Some(counter1.into()),
+            counter2: Some(counter2.into()),
+            ..UserData::new("fixed_data_structure")
+        },
+        UserDataBlock::VariableDataStructure {
+            fixed_data_header: header,
+            variable_data_block,
+        } => {
+            let records = DataRecords::try_from(variable_data_block)
+                .map_err(|error| MBusError::new_err(format!("invalid record: {}", error)))?;
+            UserData {
+                identification_number: Some(format!(
+                    "{:08}",
+                    u32::from(header.identification_number)
+                )),
+                manufacturer: Some(header.manufacturer.code.iter().collect()),
+                version: Some(header.version),
+                medium: Some(header.medium.to_string()),
+                access_number: Some(header.access_number),
+                status: Some(header.status.bits()),
+                signature: Some(header.signature),
+                records: (0..records.len())
+                    .filter_map(|index| records.get(index))
+                    .map(Record::from)
+                    .collect(),
+                manufacturer_data: records.manufacturer_data().map(<[u8]>::to_vec),
+                more_records_follow: records.more_records_follow(),
+                ..UserData::new("variable_data_structure")
+            }
+        }
+    })
+}
+
+#[pymodule]
+#[pyo3(name = "m_bus_parser")]
+fn m_bus_parser_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(parse_frame, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_user_data, m)?)?;
+    m.add_class::<Frame>()?;
+    m.add_class::<UserData>()?;
+    m.add_class::<Record>()?;
+    m.add("MBusError", m.py().get_type::<MBusError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(data: &[u8]) -> Value {
+        Value::from_record(&DataRecord::try_from(data).unwrap())
+    }
+
+    #[test]
+    fn test_value() {
+        // 35 kWh as 8 digit BCD stays an integer in Wh
+        assert_eq!(
+            value(&[0x0C, 0x06, 0x35, 0x00, 0x00, 0x00]),
+            Value::Integer(35000)
+        );
+        // 74.7 °C as 16 bit integer in 0.1 °C
+        assert_eq!(value(&[0x02, 0x5A, 0xEB, 0x02]), Value::Float(74.7));
+        assert_eq!(
+            value(&[0x0C, 0x13, 0xFF, 0xFF, 0x00, 0x00]),
+            Value::Text("0000FFFF".to_string())
+        );
+        assert_eq!(
+            value(&[0x02, 0x6C, 0x1F, 0x31]),
+            Value::Text("2024-01-31".to_string())
+        );
+    }
+}
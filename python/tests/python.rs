@@ -0,0 +1,48 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Runs the Python tests against the extension module, which is the cdylib
+// renamed to the name of the module as maturin does it
+#[cfg(target_os = "linux")]
+#[test]
+fn test_python() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // cargo test does not build the cdylib, so it is built into a target
+    // directory of its own which does not block the running cargo
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("python");
+    let build = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--offline", "--manifest-path"])
+        .arg(root.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "{}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+    let module = target.join("module");
+    fs::create_dir_all(&module).unwrap();
+    fs::copy(
+        target.join("debug/libm_bus_parser_python.so"),
+        module.join("m_bus_parser.so"),
+    )
+    .unwrap();
+
+    // the interpreter pyo3 was built for
+    let python = env::var("PYO3_PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let output = Command::new(python)
+        .args(["-m", "unittest", "discover", "-s"])
+        .arg(root.join("tests"))
+        .env("PYTHONPATH", &module)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,140 @@
+"""Tests of the Python bindings against the telegrams of the rscada corpus and
+the normalized XML which libmbus printed for them.
+
+Run with the module on the path, e.g. after `maturin develop`:
+
+    python -m unittest discover -s python/tests
+"""
+
+import math
+import pathlib
+import unittest
+import xml.etree.ElementTree as ElementTree
+
+import m_bus_parser
+
+CORPUS = pathlib.Path(__file__).parents[2] / "tests" / "rscada" / "test-frames"
+
+SPECIAL_FUNCTIONS = ("Manufacturer specific", "More records follow")
+
+# The units of the normalized XML and their symbols in the records
+UNITS = {
+    "Wh": "Wh",
+    "J": "J",
+    "W": "W",
+    "m^3": "m³",
+    "m^3/h": "m³/h",
+    "°C": "°C",
+    "K": "K",
+    "V": "V",
+    "A": "A",
+}
+
+# libmbus normalizes units like MWh to Wh
+PREFIXES = {"k": 1e3, "M": 1e6, "G": 1e9}
+
+
+def telegrams():
+    for path in sorted(CORPUS.glob("*.hex")):
+        data = bytes.fromhex(path.read_text())
+        expected = ElementTree.parse(path.with_suffix(".norm.xml")).getroot()
+        yield path.name, data, expected
+
+
+class TestCorpus(unittest.TestCase):
+    def test_header(self):
+        for name, data, expected in telegrams():
+            with self.subTest(name):
+                frame = m_bus_parser.parse_frame(data)
+                self.assertEqual(frame.kind, "long")
+                user_data = m_bus_parser.parse_user_data(frame.user_data)
+                slave = expected.find("SlaveInformation")
+                self.assertEqual(
+                    int(user_data.identification_number), int(slave.findtext("Id"))
+                )
+                self.assertEqual(
+                    user_data.access_number, int(slave.findtext("AccessNumber"))
+                )
+                self.assertEqual(user_data.status, int(slave.findtext("Status"), 16))
+                if user_data.kind == "variable_data_structure":
+                    self.assertEqual(
+                        user_data.manufacturer, slave.findtext("Manufacturer")
+                    )
+                    self.assertEqual(user_data.version, int(slave.findtext("Version")))
+
+    def test_records(self):
+        for name, data, expected in telegrams():
+            frame = m_bus_parser.parse_frame(data)
+            user_data = m_bus_parser.parse_user_data(frame.user_data)
+            if user_data.kind != "variable_data_structure":
+                continue
+            # libmbus lists the DIF 0x0F or 0x1F as a record of its own
+            records = [
+                record
+                for record in expected.findall("DataRecord")
+                if record.findtext("Function") not in SPECIAL_FUNCTIONS
+            ]
+            self.assertEqual(len(user_data.records), len(records), name)
+            for index, (record, expected) in enumerate(zip(user_data.records, records)):
+                with self.subTest(name, record=index):
+                    # which libmbus could not normalize
+                    if len(expected) == 0:
+                        continue
+                    self.assertEqual(record.function, expected.findtext("Function"))
+                    self.assertEqual(
+                        record.storage, int(expected.findtext("StorageNumber"))
+                    )
+                    self.assertEqual(record.tariff, int(expected.findtext("Tariff", "0")))
+                    self.assertEqual(record.subunit, int(expected.findtext("Device", "0")))
+                    unit = UNITS.get(expected.findtext("Unit"))
+                    if unit is None:
+                        continue
+                    record_unit, factor = record.unit, 1
+                    if record_unit[:1] in PREFIXES and record_unit[1:] == unit:
+                        record_unit, factor = unit, PREFIXES[record_unit[:1]]
+                    self.assertEqual(record_unit, unit)
+                    if not isinstance(record.value, (int, float)):
+                        continue
+                    self.assertTrue(
+                        math.isclose(
+                            record.value * factor,
+                            float(expected.findtext("Value")),
+                            rel_tol=1e-6,
+                            # libmbus prints six decimals
+                            abs_tol=1e-6,
+                        ),
+                        f"{record.value} {record.unit} != {expected.findtext('Value')}",
+                    )
+
+    def test_to_dict(self):
+        data = bytes.fromhex((CORPUS / "EDC.hex").read_text())
+        frame = m_bus_parser.parse_frame(data)
+        self.assertEqual(frame.to_dict()["address"], 1)
+        user_data = m_bus_parser.parse_user_data(frame.user_data)
+        rows = user_data.to_dict()["records"]
+        self.assertEqual(rows, [record.to_dict() for record in user_data.records])
+        self.assertEqual(
+            rows[0],
+            {
+                "storage": 0,
+                "tariff": 0,
+                "subunit": 0,
+                "function": "Instantaneous value",
+                "quantity": "Energy",
+                "unit": "Wh",
+                "value": 35000,
+            },
+        )
+
+    def test_errors(self):
+        with self.assertRaises(m_bus_parser.MBusError):
+            m_bus_parser.parse_frame(bytes.fromhex("10 40 01 42 16"))
+        with self.assertRaises(ValueError):
+            m_bus_parser.parse_user_data(b"")
+        frame = m_bus_parser.parse_frame(bytes.fromhex("10 40 01 41 16"))
+        self.assertEqual(frame.kind, "short")
+        self.assertIsNone(frame.user_data)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,785 @@
+//! Annotated byte level dissection of a frame, covering every byte with the
+//! layer and field it belongs to and what it was decoded to.
+
+use arrayvec::ArrayVec;
+use core::fmt;
+
+use crate::frames::{Address, Frame, FrameError, Function};
+use crate::user_data::data_information::{
+    AsciiText, DataFieldCoding, DataInformationField, DataValue, FunctionField,
+};
+use crate::user_data::value_information::Unit;
+use crate::user_data::variable_user_data::{DataRecord, DataRecordError, Exponent, Quantity};
+use crate::user_data::{
+    ApplicationLayerError, ApplicationResetSubcode, ControlInformation, Counter,
+    IdentificationNumber, ManufacturerCode, Medium, ParserOptions, StatusField, UserDataBlock,
+};
+
+// A long frame has 6 bytes of link layer around at most 255 bytes of user
+// data, and every span covers at least one byte
+const MAXIMUM_FRAME_SIZE: usize = 261;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Link,
+    Application,
+    /// The data record with the given index, as in `DataRecords`
+    Record(usize),
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::Link => write!(f, "link"),
+            Layer::Application => write!(f, "application"),
+            Layer::Record(index) => write!(f, "record {}", index),
+        }
+    }
+}
+
+/// Why the rest of the frame could not be decoded
+#[derive(Debug, PartialEq)]
+pub enum DecodingError {
+    ApplicationLayer(ApplicationLayerError),
+    Record(DataRecordError),
+}
+
+impl fmt::Display for DecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodingError::ApplicationLayer(error) => write!(f, "{}", error),
+            DecodingError::Record(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Meaning<'a> {
+    Acknowledgement,
+    StartByte,
+    StopByte,
+    Length(u8),
+    Checksum(u8),
+    Function(Function),
+    Address(Address),
+    ControlInformation(ControlInformation),
+    ResetSubcode(ApplicationResetSubcode),
+    IdentificationNumber(IdentificationNumber),
+    Manufacturer(ManufacturerCode),
+    Version(u8),
+    Medium(Medium),
+    AccessNumber(u8),
+    Status(StatusField),
+    Signature(u16),
+    MediumAndUnit(u16),
+    Counter(Counter),
+    DataInformation {
+        function: FunctionField,
+        coding: DataFieldCoding,
+        storage_number: u8,
+    },
+    /// The bits a DIFE adds to the storage number, tariff and subunit
+    DataInformationExtension {
+        storage_number: u8,
+        tariff: u8,
+        sub_unit: u8,
+    },
+    ValueInformation {
+        quantity: Quantity,
+        unit: Unit,
+        exponent: Exponent,
+    },
+    /// VIF 0xFB or 0xFD, the value information follows in the first VIFE
+    ExtensionTable,
+    ValueInformationExtension(u8),
+    PlainText(AsciiText<'a>),
+    Value(DataValue<'a>),
+    /// BCD data with digits above 9, which meters send while they can not
+    /// measure, printed as the transmitted digits
+    InvalidBcd(&'a [u8]),
+    IdleFiller,
+    ManufacturerSpecific {
+        more_records_follow: bool,
+    },
+    ManufacturerData,
+    Undecoded(DecodingError),
+}
+
+impl fmt::Display for Meaning<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Meaning::Acknowledgement => write!(f, "acknowledgement"),
+            Meaning::StartByte | Meaning::StopByte => Ok(()),
+            Meaning::Length(length) => write!(f, "{} bytes", length),
+            Meaning::Checksum(checksum) => write!(f, "0x{:02X}", checksum),
+            Meaning::Function(function) => write!(f, "{}", function),
+            Meaning::Address(address) => write!(f, "{}", address),
+            Meaning::ControlInformation(control_information) => {
+                write!(f, "{}", control_information)
+            }
+            Meaning::ResetSubcode(subcode) => write!(f, "{}", subcode),
+            Meaning::IdentificationNumber(number) => write!(f, "{:08}", u32::from(*number)),
+            Meaning::Manufacturer(code) => write!(f, "{}", code),
+            Meaning::Version(version) => write!(f, "{}", version),
+            Meaning::Medium(medium) => write!(f, "{}", medium),
+            Meaning::AccessNumber(access_number) => write!(f, "{}", access_number),
+            Meaning::Status(status) => write!(f, "{}", status),
+            Meaning::Signature(signature) => write!(f, "0x{:04X}", signature),
+            Meaning::MediumAndUnit(medium_and_unit) => write!(f, "0x{:04X}", medium_and_unit),
+            Meaning::Counter(counter) => write!(f, "{}", counter),
+            Meaning::DataInformation {
+                function,
+                coding,
+                storage_number,
+            } => write!(
+                f,
+                "{}, {:?}, storage bit {}",
+                function, coding, storage_number
+            ),
+            Meaning::DataInformationExtension {
+                storage_number,
+                tariff,
+                sub_unit,
+            } => write!(
+                f,
+                "storage bits {}, tariff bits {}, subunit bit {}",
+                storage_number, tariff, sub_unit
+            ),
+            Meaning::ValueInformation {
+                quantity,
+                unit,
+                exponent,
+            } => {
+                write!(f, "{}", quantity)?;
+                match (exponent.inner, unit.symbol()) {
+                    (Some(exponent), "") => write!(f, " in 10^{}", exponent),
+                    (Some(exponent), symbol) => write!(f, " in 10^{} {}", exponent, symbol),
+                    (None, "") => Ok(()),
+                    (None, symbol) => write!(f, " in {}", symbol),
+                }
+            }
+            Meaning::ExtensionTable => write!(f, "extension table"),
+            Meaning::ValueInformationExtension(code) => write!(f, "extension 0x{:02X}", code),
+            Meaning::PlainText(text) => write!(f, "\"{}\"", text),
+            Meaning::Value(DataValue::Unsigned(value)) => write!(f, "{}", value),
+            Meaning::Value(DataValue::Signed(value)) => write!(f, "{}", value),
+            Meaning::Value(DataValue::Real(value)) => write!(f, "{}", value),
+            Meaning::Value(DataValue::Bcd(bcd)) if bcd.invalid_digits == 0 => {
+                let sign = if bcd.negative { "-" } else { "" };
+                write!(f, "{}{} (BCD)", sign, bcd.value)
+            }
+            Meaning::Value(DataValue::NoData) => write!(f, "no data"),
+            Meaning::Value(DataValue::Date(date)) => write!(f, "{}", date),
+            Meaning::Value(DataValue::Time(time)) => write!(f, "{}", time),
+            Meaning::Value(DataValue::DateTime(date_time)) => write!(f, "{}", date_time),
+            Meaning::Value(DataValue::Manufacturer(code)) => write!(f, "{}", code),
+            Meaning::Value(DataValue::Medium(medium)) => write!(f, "{}", medium),
+            Meaning::Value(DataValue::Ascii(text)) => write!(f, "\"{}\"", text),
+            Meaning::Value(value) => write!(f, "{:?}", value),
+            Meaning::InvalidBcd(bytes) => {
+                for byte in bytes.iter().rev() {
+                    write!(f, "{:02X}", byte)?;
+                }
+                f.write_str(" (invalid BCD)")
+            }
+            Meaning::IdleFiller => write!(f, "idle filler"),
+            Meaning::ManufacturerSpecific {
+                more_records_follow: false,
+            } => write!(f, "manufacturer specific data follows"),
+            Meaning::ManufacturerSpecific {
+                more_records_follow: true,
+            } => write!(f, "manufacturer specific data follows, more records follow"),
+            Meaning::ManufacturerData => Ok(()),
+            Meaning::Undecoded(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// The bytes of one field of a frame, `offset` counts from the start byte
+#[derive(Debug, PartialEq)]
+pub struct Span<'a> {
+    pub offset: usize,
+    pub length: usize,
+    pub layer: Layer,
+    pub field: &'static str,
+    pub bytes: &'a [u8],
+    pub meaning: Meaning<'a>,
+}
+
+/// The spans of a frame in the order of their bytes, without gaps
+#[derive(Debug, PartialEq)]
+pub struct Dissection<'a> {
+    frame: &'a [u8],
+    spans: ArrayVec<Span<'a>, MAXIMUM_FRAME_SIZE>,
+}
+
+impl<'a> TryFrom<&'a [u8]> for Dissection<'a> {
+    type Error = FrameError;
+    fn try_from(data: &'a [u8]) -> Result<Dissection<'a>, FrameError> {
+        Dissection::parse(data, &ParserOptions::default())
+    }
+}
+
+impl<'a> Dissection<'a> {
+    /// Dissects a frame, fails only if the link layer is invalid. Bytes of the
+    /// user data which can not be decoded end up in a span with the error.
+    pub fn parse(data: &'a [u8], options: &ParserOptions) -> Result<Self, FrameError> {
+        let frame = Frame::try_from(data)?;
+        let mut dissection = Dissection {
+            frame: data,
+            spans: ArrayVec::new(),
+        };
+
+        let (function, address, user_data) = match frame {
+            Frame::SingleCharacter { .. } => {
+                dissection.push(
+                    Layer::Link,
+                    "single character",
+                    data,
+                    Meaning::Acknowledgement,
+                );
+                return Ok(dissection);
+            }
+            Frame::ShortFrame { function, address } => {
+                dissection.push(Layer::Link, "start", &data[..1], Meaning::StartByte);
+                dissection.push(
+                    Layer::Link,
+                    "C field",
+                    &data[1..2],
+                    Meaning::Function(function),
+                );
+                dissection.push(
+                    Layer::Link,
+                    "A field",
+                    &data[2..3],
+                    Meaning::Address(address),
+                );
+                dissection.push_trailer(data);
+                return Ok(dissection);
+            }
+            Frame::LongFrame {
+                function,
+                address,
+                data: user_data,
+            }
+            | Frame::ControlFrame {
+                function,
+                address,
+                data: user_data,
+            } => (function, address, user_data),
+        };
+
+        dissection.push(Layer::Link, "start", &data[..1], Meaning::StartByte);
+        dissection.push(
+            Layer::Link,
+            "L field",
+            &data[1..2],
+            Meaning::Length(data[1]),
+        );
+        dissection.push(
+            Layer::Link,
+            "L field",
+            &data[2..3],
+            Meaning::Length(data[2]),
+        );
+        dissection.push(Layer::Link, "start", &data[3..4], Meaning::StartByte);
+        dissection.push(
+            Layer::Link,
+            "C field",
+            &data[4..5],
+            Meaning::Function(function),
+        );
+        dissection.push(
+            Layer::Link,
+            "A field",
+            &data[5..6],
+            Meaning::Address(address),
+        );
+        dissection.push_user_data(user_data, options);
+        dissection.push_trailer(data);
+        Ok(dissection)
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Span<'a>> {
+        self.spans.get(index)
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, Span<'a>> {
+        self.spans.iter()
+    }
+
+    // Every span is a part of the frame, which gives its offset
+    fn push(&mut self, layer: Layer, field: &'static str, bytes: &'a [u8], meaning: Meaning<'a>) {
+        if bytes.is_empty() {
+            return;
+        }
+        let _ = self.spans.try_push(Span {
+            offset: bytes.as_ptr() as usize - self.frame.as_ptr() as usize,
+            length: bytes.len(),
+            layer,
+            field,
+            bytes,
+            meaning,
+        });
+    }
+
+    fn push_trailer(&mut self, data: &'a [u8]) {
+        let checksum = &data[data.len() - 2..data.len() - 1];
+        self.push(
+            Layer::Link,
+            "checksum",
+            checksum,
+            Meaning::Checksum(checksum[0]),
+        );
+        self.push(
+            Layer::Link,
+            "stop",
+            &data[data.len() - 1..],
+            Meaning::StopByte,
+        );
+    }
+
+    fn push_undecoded(&mut self, layer: Layer, bytes: &'a [u8], error: DecodingError) {
+        self.push(layer, "undecoded", bytes, Meaning::Undecoded(error));
+    }
+
+    fn push_user_data(&mut self, data: &'a [u8], options: &ParserOptions) {
+        // a long frame of only the C and A field
+        if data.is_empty() {
+            return;
+        }
+        let layer = Layer::Application;
+        let user_data = match UserDataBlock::try_from(data) {
+            Ok(user_data) => user_data,
+            Err(ApplicationLayerError::InvalidControlInformation { byte }) => {
+                let error = ApplicationLayerError::InvalidControlInformation { byte };
+                self.push_undecoded(layer, data, DecodingError::ApplicationLayer(error));
+                return;
+            }
+            Err(error) => {
+                self.push_control_information(&data[..1]);
+                self.push_undecoded(layer, &data[1..], DecodingError::ApplicationLayer(error));
+                return;
+            }
+        };
+        self.push_control_information(&data[..1]);
+
+        match user_data {
+            UserDataBlock::ResetAtApplicationLevel { subcode } => {
+                self.push(
+                    layer,
+                    "subcode",
+                    &data[1..2],
+                    Meaning::ResetSubcode(subcode),
+                );
+                self.push(layer, "unknown", &data[2..], Meaning::ManufacturerData);
+            }
+            UserDataBlock::FixedDataStructure {
+                identification_number,
+                access_number,
+                status,
+                medium_ad_unit,
+                counter1,
+                counter2,
+            } => {
+                self.push(
+                    layer,
+                    "identification number",
+                    &data[1..5],
+                    Meaning::IdentificationNumber(identification_number),
+                );
+                self.push(
+                    layer,
+                    "access number",
+                    &data[5..6],
+                    Meaning::AccessNumber(access_number),
+                );
+                self.push(layer, "status", &data[6..7], Meaning::Status(status));
+                self.push(
+                    layer,
+                    "medium and unit",
+                    &data[7..9],
+                    Meaning::MediumAndUnit(medium_ad_unit),
+                );
+                self.push(layer, "counter 1", &data[9..13], Meaning::Counter(counter1));
+                self.push(
+                    layer,
+                    "counter 2",
+                    &data[13..17],
+                    Meaning::Counter(counter2),
+                );
+                self.push(layer, "unknown", &data[17..], Meaning::ManufacturerData);
+            }
+            UserDataBlock::VariableDataStructure {
+                fixed_data_header: header,
+                variable_data_block,
+            } => {
+                self.push(
+                    layer,
+                    "identification number",
+                    &data[1..5],
+                    Meaning::IdentificationNumber(header.identification_number),
+                );
+                self.push(
+                    layer,
+                    "manufacturer",
+                    &data[5..7],
+                    Meaning::Manufacturer(header.manufacturer),
+                );
+                self.push(
+                    layer,
+                    "version",
+                    &data[7..8],
+                    Meaning::Version(header.version),
+                );
+                self.push(layer, "medium", &data[8..9], Meaning::Medium(header.medium));
+                self.push(
+                    layer,
+                    "access number",
+                    &data[9..10],
+                    Meaning::AccessNumber(header.access_number),
+                );
+                self.push(
+                    layer,
+                    "status",
+                    &data[10..11],
+                    Meaning::Status(header.status),
+                );
+                self.push(
+                    layer,
+                    "signature",
+                    &data[11..13],
+                    Meaning::Signature(header.signature),
+                );
+                self.push_records(variable_data_block, options);
+            }
+        }
+    }
+
+    fn push_control_information(&mut self, data: &'a [u8]) {
+        // the error of an unknown CI field is pushed instead
+        if let Ok(control_information) = ControlInformation::from(data[0]) {
+            let meaning = Meaning::ControlInformation(control_information);
+            self.push(Layer::Application, "CI field", data, meaning);
+        }
+    }
+
+    // Follows `DataRecords::parse`, but keeps the bytes which can not be
+    // parsed. The special DIFs are not records and belong to the application
+    // layer.
+    fn push_records(&mut self, data: &'a [u8], options: &ParserOptions) {
+        let mut offset = 0;
+        let mut index = 0;
+        while offset < data.len() {
+            let dif = &data[offset..offset + 1];
+            match dif[0] {
+                0x0F | 0x1F => {
+                    let meaning = Meaning::ManufacturerSpecific {
+                        more_records_follow: dif[0] == 0x1F,
+                    };
+                    self.push(Layer::Application, "DIF", dif, meaning);
+                    let manufacturer_data = &data[offset + 1..];
+                    self.push(
+                        Layer::Application,
+                        "manufacturer data",
+                        manufacturer_data,
+                        Meaning::ManufacturerData,
+                    );
+                    return;
+                }
+                0x2F => {
+                    self.push(Layer::Application, "DIF", dif, Meaning::IdleFiller);
+                    offset += 1;
+                }
+                _ => match DataRecord::parse(&data[offset..], options) {
+                    Ok(record) => {
+                        self.push_record(Layer::Record(index), &record);
+                        offset += record.size;
+                        index += 1;
+                    }
+                    Err(error) => {
+                        let error = DecodingError::Record(error);
+                        self.push_undecoded(Layer::Record(index), &data[offset..], error);
+                        return;
+                    }
+                },
+            }
+        }
+    }
+
+    fn push_record(&mut self, layer: Layer, record: &DataRecord<'a>) {
+        let dib = record.raw_data_information;
+        let dif = dib[0];
+        let coding = DataInformationField::try_from(dib)
+            .map_or(DataFieldCoding::NoData, |field| field.data_field_coding);
+        let meaning = Meaning::DataInformation {
+            function: record.function,
+            coding,
+            storage_number: (dif >> 6) & 1,
+        };
+        self.push(layer, "DIF", &dib[..1], meaning);
+        for dife in dib[1..].chunks(1) {
+            let meaning = Meaning::DataInformationExtension {
+                storage_number: dife[0] & 0x0F,
+                tariff: (dife[0] >> 4) & 0b11,
+                sub_unit: (dife[0] >> 6) & 1,
+            };
+            self.push(layer, "DIFE", dife, meaning);
+        }
+
+        let vib = record.raw_value_information;
+        let value_information = Meaning::ValueInformation {
+            quantity: record.quantity,
+            unit: record.unit,
+            exponent: record.exponent,
+        };
+        match (vib[0], record.plain_text_unit) {
+            (_, Some(text)) => {
+                // the text is in front of the VIFEs or behind them, depending
+                // on the layout the parser settled on
+                let start = text.bytes.as_ptr() as usize - vib.as_ptr() as usize;
+                self.push(layer, "VIF", &vib[..1], value_information);
+                self.push_extensions(layer, &vib[1..start - 1]);
+                let length = &vib[start - 1..start];
+                self.push(
+                    layer,
+                    "plain text length",
+                    length,
+                    Meaning::Length(length[0]),
+                );
+                self.push(
+                    layer,
+                    "plain text unit",
+                    text.bytes,
+                    Meaning::PlainText(text),
+                );
+                self.push_extensions(layer, &vib[start + text.bytes.len()..]);
+            }
+            (0xFB | 0xFD, None) => {
+                self.push(layer, "VIF", &vib[..1], Meaning::ExtensionTable);
+                self.push(layer, "VIFE", &vib[1..2], value_information);
+                self.push_extensions(layer, &vib[2..]);
+            }
+            (_, None) => {
+                self.push(layer, "VIF", &vib[..1], value_information);
+                self.push_extensions(layer, &vib[1..]);
+            }
+        }
+
+        let meaning = match record.value {
+            DataValue::Bcd(bcd) if bcd.invalid_digits > 0 => Meaning::InvalidBcd(record.raw_data),
+            value => Meaning::Value(value),
+        };
+        self.push(layer, "data", record.raw_data, meaning);
+    }
+
+    fn push_extensions(&mut self, layer: Layer, vifes: &'a [u8]) {
+        for vife in vifes.chunks(1) {
+            let meaning = Meaning::ValueInformationExtension(vife[0] & 0x7F);
+            self.push(layer, "VIFE", vife, meaning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covers_frame(dissection: &Dissection, frame: &[u8]) {
+        let mut offset = 0;
+        for span in dissection.iter() {
+            assert_eq!(span.offset, offset);
+            assert_eq!(span.bytes, &frame[offset..offset + span.length]);
+            offset += span.length;
+        }
+        assert_eq!(offset, frame.len());
+    }
+
+    #[test]
+    fn test_short_frame() {
+        let frame = [0x10, 0x7B, 0x8B, 0x06, 0x16];
+        let dissection = Dissection::try_from(frame.as_slice()).unwrap();
+        covers_frame(&dissection, &frame);
+        let fields: ArrayVec<&str, 5> = dissection.iter().map(|span| span.field).collect();
+        assert_eq!(
+            fields.as_slice(),
+            ["start", "C field", "A field", "checksum", "stop"]
+        );
+        assert_eq!(
+            dissection.get(2).map(|span| &span.meaning),
+            Some(&Meaning::Address(Address::Primary(0x8B)))
+        );
+    }
+
+    #[test]
+    fn test_long_frame() {
+        let frame = [
+            0x68, 0x17, 0x17, 0x68, 0x08, 0x05, 0x72, 0x78, 0x56, 0x34, 0x12, 0x24, 0x40, 0x01,
+            0x07, 0x13, 0x00, 0x00, 0x00, 0x84, 0x10, 0x03, 0x54, 0x05, 0x00, 0x00, 0x2F, 0x31,
+            0x16,
+        ];
+        let dissection = Dissection::try_from(frame.as_slice()).unwrap();
+        covers_frame(&dissection, &frame);
+
+        let record: ArrayVec<(&str, &Meaning), 5> = dissection
+            .iter()
+            .filter(|span| span.layer == Layer::Record(0))
+            .map(|span| (span.field, &span.meaning))
+            .collect();
+        assert_eq!(
+            record.as_slice(),
+            [
+                (
+                    "DIF",
+                    &Meaning::DataInformation {
+                        function: FunctionField::InstantaneousValue,
+                        coding: DataFieldCoding::Integer32Bit,
+                        storage_number: 0,
+                    }
+                ),
+                (
+                    "DIFE",
+                    &Meaning::DataInformationExtension {
+                        storage_number: 0,
+                        tariff: 1,
+                        sub_unit: 0,
+                    }
+                ),
+                (
+                    "VIF",
+                    &Meaning::ValueInformation {
+                        quantity: Quantity::Energy,
+                        unit: Unit::WattHour,
+                        exponent: Exponent::from(0),
+                    }
+                ),
+                ("data", &Meaning::Value(DataValue::Signed(0x0554))),
+            ]
+        );
+        assert_eq!(
+            dissection.iter().nth_back(2).map(|span| &span.meaning),
+            Some(&Meaning::IdleFiller)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        extern crate std;
+        use std::string::ToString;
+        use std::vec::Vec;
+
+        // an energy in BCD and a volume with the invalid digits FFFF
+        let frame = [
+            0x68, 0x19, 0x19, 0x68, 0x08, 0x05, 0x72, 0x78, 0x56, 0x34, 0x12, 0x24, 0x40, 0x01,
+            0x07, 0x13, 0x00, 0x00, 0x00, 0x0C, 0x06, 0x37, 0x21, 0x00, 0x00, 0x0A, 0x13, 0xFF,
+            0xFF, 0x97, 0x16,
+        ];
+        let dissection = Dissection::try_from(frame.as_slice()).unwrap();
+        covers_frame(&dissection, &frame);
+        let meanings: Vec<_> = dissection
+            .iter()
+            .map(|span| span.meaning.to_string())
+            .collect();
+        assert_eq!(
+            meanings,
+            [
+                "",
+                "25 bytes",
+                "25 bytes",
+                "",
+                "RSP_UD (ACD 0, DFC 0)",
+                "5",
+                "Response with variable data structure",
+                "12345678",
+                "PAD",
+                "1",
+                "Water",
+                "19",
+                "OK",
+                "0x0000",
+                "Instantaneous value, BCD8Digit, storage bit 0",
+                "Energy in 10^3 Wh",
+                "2137 (BCD)",
+                "Instantaneous value, BCD4Digit, storage bit 0",
+                "Volume in 10^-3 m³",
+                "FFFF (invalid BCD)",
+                "0x97",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_frame_without_user_data() {
+        let frame = [0x68, 0x02, 0x02, 0x68, 0x08, 0x01, 0x09, 0x16];
+        let dissection = Dissection::try_from(frame.as_slice()).unwrap();
+        covers_frame(&dissection, &frame);
+        assert!(dissection.iter().all(|span| span.layer == Layer::Link));
+    }
+
+    #[test]
+    fn test_undecoded_records() {
+        // the second record announces 4 bytes but only 2 are left
+        let frame = [
+            0x68, 0x16, 0x16, 0x68, 0x08, 0x05, 0x72, 0x78, 0x56, 0x34, 0x12, 0x24, 0x40, 0x01,
+            0x07, 0x13, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x04, 0x06, 0x01, 0x02, 0x27, 0x16,
+        ];
+        let frame = &frame[..];
+        let dissection = Dissection::try_from(frame).unwrap();
+        covers_frame(&dissection, frame);
+        let undecoded = dissection.iter().nth_back(2).unwrap();
+        assert_eq!(undecoded.layer, Layer::Record(1));
+        assert_eq!(undecoded.bytes, [0x04, 0x06, 0x01, 0x02]);
+        assert!(matches!(
+            undecoded.meaning,
+            Meaning::Undecoded(DecodingError::Record(_))
+        ));
+    }
+
+    #[test]
+    fn test_plain_text() {
+        // a record with the text VIF 0xFC, its text "kHz" and the VIFE 0x74
+        // behind it, as most meters send it
+        let user_data = [
+            0x08, 0x05, 0x72, 0x78, 0x56, 0x34, 0x12, 0x24, 0x40, 0x01, 0x07, 0x13, 0x00, 0x00,
+            0x00, 0x02, 0xFC, 0x03, b'z', b'H', b'k', 0x74, 0x10, 0x00,
+        ];
+        let mut frame: ArrayVec<u8, 32> = ArrayVec::new();
+        let length = user_data.len() as u8;
+        frame.extend([0x68, length, length, 0x68]);
+        frame.extend(user_data);
+        frame.push(
+            user_data
+                .iter()
+                .fold(0, |sum: u8, byte| sum.wrapping_add(*byte)),
+        );
+        frame.push(0x16);
+
+        let dissection = Dissection::try_from(frame.as_slice()).unwrap();
+        covers_frame(&dissection, &frame);
+        let fields: ArrayVec<(&str, &[u8]), 8> = dissection
+            .iter()
+            .filter(|span| span.layer == Layer::Record(0))
+            .map(|span| (span.field, span.bytes))
+            .collect();
+        assert_eq!(
+            fields.as_slice(),
+            [
+                ("DIF", &[0x02][..]),
+                ("VIF", &[0xFC]),
+                ("plain text length", &[0x03]),
+                ("plain text unit", b"zHk"),
+                ("VIFE", &[0x74]),
+                ("data", &[0x10, 0x00]),
+            ]
+        );
+    }
+}
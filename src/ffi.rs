@@ -0,0 +1,620 @@
+//! C interface for firmware which links the static or dynamic library, the
+//! header is `include/m_bus_parser.h`.
+//!
+//! Nothing is allocated: every result is written to a struct owned by the
+//! caller and points into the buffer that was parsed, which therefore has to
+//! outlive the results.
+
+use core::ffi::c_char;
+use core::ptr;
+use core::slice;
+
+use crate::frames::Frame;
+use crate::user_data::data_information::{DataValue, FunctionField};
+use crate::user_data::value_information::Unit;
+use crate::user_data::variable_user_data::{DataRecord, Quantity};
+use crate::user_data::{ManufacturerCode, Medium, ParserOptions, UserDataBlock};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MbusStatus {
+    Ok = 0,
+    /// A pointer argument was null
+    NullPointer,
+    InvalidFrame,
+    InvalidUserData,
+    InvalidRecord,
+    /// There are no more records, see the manufacturer data of the iterator
+    EndOfRecords,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MbusFrameType {
+    SingleCharacter,
+    ShortFrame,
+    LongFrame,
+    ControlFrame,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MbusFrame {
+    pub frame_type: MbusFrameType,
+    /// The C field, 0xE5 for the single character
+    pub control: u8,
+    /// The A field, 0 for the single character
+    pub address: u8,
+    /// The user data of long and control frames starting with the CI field,
+    /// null for the other frames
+    pub user_data: *const u8,
+    pub user_data_length: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MbusUserDataType {
+    ResetAtApplicationLevel,
+    FixedDataStructure,
+    VariableDataStructure,
+}
+
+/// The fields which are not transmitted by the structure are zero
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MbusUserData {
+    pub user_data_type: MbusUserDataType,
+    /// The byte following the CI field of a reset at application level
+    pub reset_subcode: u8,
+    pub identification_number: u32,
+    /// The three letters of the manufacturer, null terminated
+    pub manufacturer: [u8; 4],
+    pub version: u8,
+    pub medium: Medium,
+    pub access_number: u8,
+    pub status: u8,
+    pub signature: u16,
+    pub medium_and_unit: u16,
+    pub counter1: u32,
+    pub counter2: u32,
+    /// The data records of the variable data structure, to be passed to
+    /// `mbus_records_begin`
+    pub records: *const u8,
+    pub records_length: usize,
+}
+
+/// The position in the data records, owned by the caller
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MbusRecordIterator {
+    pub data: *const u8,
+    pub length: usize,
+    pub offset: usize,
+    /// The data following the DIF 0x0F or 0x1F, set once `mbus_records_next`
+    /// returned `EndOfRecords`
+    pub manufacturer_data: *const u8,
+    pub manufacturer_data_length: usize,
+    /// Whether the DIF 0x1F announced that more records follow in the next
+    /// telegram
+    pub more_records_follow: bool,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MbusValueType {
+    NoData,
+    Unsigned,
+    Signed,
+    Bcd,
+    Real,
+    Date,
+    Time,
+    DateTime,
+    /// ASCII text, see `mbus_record_text`
+    Text,
+    /// A manufacturer code, see `mbus_record_text`
+    Manufacturer,
+    /// Only the raw bytes of the value are available
+    Other,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MbusDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// The time invalid bit (IV) was set by the meter
+    pub invalid: bool,
+    /// The summer time bit (SU) was set by the meter
+    pub summer_time: bool,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MbusRecord {
+    pub function: FunctionField,
+    pub storage_number: u64,
+    pub tariff: u64,
+    pub sub_unit: u32,
+    pub quantity: Quantity,
+    pub unit: Unit,
+    pub exponent: i32,
+    pub value_type: MbusValueType,
+    /// The numeric value multiplied by 10^exponent, NaN for other values and
+    /// BCD with invalid digits
+    pub value: f64,
+    /// The value of `Unsigned` records
+    pub unsigned_value: u64,
+    /// The value of `Signed` and `Bcd` records
+    pub signed_value: i64,
+    /// The value of `Date`, `Time` and `DateTime` records
+    pub date_time: MbusDateTime,
+    /// The bytes of the value as transmitted
+    pub data: *const u8,
+    pub data_length: usize,
+    /// The unit of records with the unit `PlainText` as transmitted, that is
+    /// with the last character first, see `mbus_record_unit_text`
+    pub plain_text_unit: *const u8,
+    pub plain_text_unit_length: usize,
+}
+
+/// Parses the frame in `data` into `frame`
+///
+/// # Safety
+///
+/// `data` has to point to `length` readable bytes and `frame` to writable
+/// memory for an `MbusFrame`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_parse_frame(
+    data: *const u8,
+    length: usize,
+    frame: *mut MbusFrame,
+) -> MbusStatus {
+    if data.is_null() || frame.is_null() {
+        return MbusStatus::NullPointer;
+    }
+    let data = slice::from_raw_parts(data, length);
+    let result = match Frame::try_from(data) {
+        Ok(Frame::SingleCharacter { character }) => MbusFrame {
+            frame_type: MbusFrameType::SingleCharacter,
+            control: character,
+            address: 0,
+            user_data: ptr::null(),
+            user_data_length: 0,
+        },
+        Ok(Frame::ShortFrame { .. }) => MbusFrame {
+            frame_type: MbusFrameType::ShortFrame,
+            control: data[1],
+            address: data[2],
+            user_data: ptr::null(),
+            user_data_length: 0,
+        },
+        Ok(Frame::LongFrame {
+            data: user_data, ..
+        }) => MbusFrame {
+            frame_type: MbusFrameType::LongFrame,
+            control: data[4],
+            address: data[5],
+            user_data: user_data.as_ptr(),
+            user_data_length: user_data.len(),
+        },
+        Ok(Frame::ControlFrame {
+            data: user_data, ..
+        }) => MbusFrame {
+            frame_type: MbusFrameType::ControlFrame,
+            control: data[4],
+            address: data[5],
+            user_data: user_data.as_ptr(),
+            user_data_length: user_data.len(),
+        },
+        Err(_) => return MbusStatus::InvalidFrame,
+    };
+    frame.write(result);
+    MbusStatus::Ok
+}
+
+/// Parses the user data of a frame, starting with the CI field, into
+/// `user_data`
+///
+/// # Safety
+///
+/// `data` has to point to `length` readable bytes and `user_data` to writable
+/// memory for an `MbusUserData`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_parse_user_data(
+    data: *const u8,
+    length: usize,
+    user_data: *mut MbusUserData,
+) -> MbusStatus {
+    if data.is_null() || user_data.is_null() {
+        return MbusStatus::NullPointer;
+    }
+    let data = slice::from_raw_parts(data, length);
+    let Ok(block) = UserDataBlock::try_from(data) else {
+        return MbusStatus::InvalidUserData;
+    };
+    let mut result = MbusUserData {
+        user_data_type: MbusUserDataType::ResetAtApplicationLevel,
+        reset_subcode: 0,
+        identification_number: 0,
+        manufacturer: [0; 4],
+        version: 0,
+        medium: Medium::Other,
+        access_number: 0,
+        status: 0,
+        signature: 0,
+        medium_and_unit: 0,
+        counter1: 0,
+        counter2: 0,
+        records: ptr::null(),
+        records_length: 0,
+    };
+    match block {
+        UserDataBlock::ResetAtApplicationLevel { .. } => {
+            result.reset_subcode = data[1];
+        }
+        UserDataBlock::FixedDataStructure {
+            identification_number,
+            access_number,
+            status,
+            medium_ad_unit,
+            counter1,
+            counter2,
+        } => {
+            result.user_data_type = MbusUserDataType::FixedDataStructure;
+            result.identification_number = identification_number.into();
+            result.access_number = access_number;
+            result.status = status.bits();
+            result.medium_and_unit = medium_ad_unit;
+            result.counter1 = counter1.into();
+            result.counter2 = counter2.into();
+        }
+        UserDataBlock::VariableDataStructure {
+            fixed_data_header: header,
+            variable_data_block,
+        } => {
+            result.user_data_type = MbusUserDataType::VariableDataStructure;
+            result.identification_number = header.identification_number.into();
+            for (letter, code) in result.manufacturer.iter_mut().zip(header.manufacturer.code) {
+                *letter = code as u8;
+            }
+            result.version = header.version;
+            result.medium = header.medium;
+            result.access_number = header.access_number;
+            result.status = header.status.bits();
+            result.signature = header.signature;
+            result.records = variable_data_block.as_ptr();
+            result.records_length = variable_data_block.len();
+        }
+    }
+    user_data.write(result);
+    MbusStatus::Ok
+}
+
+/// Starts iterating over the data records in `data`, usually the `records`
+/// of an `MbusUserData`
+///
+/// # Safety
+///
+/// `iterator` has to point to writable memory for an `MbusRecordIterator` and
+/// `data` to `length` bytes which stay readable while iterating.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_records_begin(
+    data: *const u8,
+    length: usize,
+    iterator: *mut MbusRecordIterator,
+) -> MbusStatus {
+    if data.is_null() || iterator.is_null() {
+        return MbusStatus::NullPointer;
+    }
+    iterator.write(MbusRecordIterator {
+        data,
+        length,
+        offset: 0,
+        manufacturer_data: ptr::null(),
+        manufacturer_data_length: 0,
+        more_records_follow: false,
+    });
+    MbusStatus::Ok
+}
+
+/// Parses the next data record into `record`, skipping idle fillers, returns
+/// `EndOfRecords` after the last one
+///
+/// # Safety
+///
+/// `iterator` has to be initialised by `mbus_records_begin` and `record` has
+/// to point to writable memory for an `MbusRecord`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_records_next(
+    iterator: *mut MbusRecordIterator,
+    record: *mut MbusRecord,
+) -> MbusStatus {
+    if iterator.is_null() || record.is_null() {
+        return MbusStatus::NullPointer;
+    }
+    let iterator = &mut *iterator;
+    let data = slice::from_raw_parts(iterator.data, iterator.length);
+
+    while let Some(&dif) = data.get(iterator.offset) {
+        match dif {
+            0x0F | 0x1F => {
+                let manufacturer_data = &data[iterator.offset + 1..];
+                iterator.manufacturer_data = manufacturer_data.as_ptr();
+                iterator.manufacturer_data_length = manufacturer_data.len();
+                iterator.more_records_follow = dif == 0x1F;
+                iterator.offset = data.len();
+            }
+            0x2F => iterator.offset += 1,
+            _ => {
+                let Ok(parsed) =
+                    DataRecord::parse(&data[iterator.offset..], &ParserOptions::default())
+                else {
+                    return MbusStatus::InvalidRecord;
+                };
+                iterator.offset += parsed.size;
+                record.write(MbusRecord::from(&parsed));
+                return MbusStatus::Ok;
+            }
+        }
+    }
+    MbusStatus::EndOfRecords
+}
+
+/// Writes the text of a `Text` or `Manufacturer` record to `buffer`, null
+/// terminated and truncated to `size` bytes. Returns the length of the whole
+/// text, like `snprintf`.
+///
+/// # Safety
+///
+/// `record` has to be filled by `mbus_records_next` from data which is still
+/// readable, and `buffer` has to point to `size` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_record_text(
+    record: *const MbusRecord,
+    buffer: *mut c_char,
+    size: usize,
+) -> usize {
+    if record.is_null() {
+        return 0;
+    }
+    let record = &*record;
+    let data = slice::from_raw_parts(record.data, record.data_length);
+    match record.value_type {
+        MbusValueType::Text => write_text(data.iter().rev().copied(), buffer, size),
+        MbusValueType::Manufacturer => {
+            let code = ManufacturerCode::from_id(u16::from_le_bytes([data[0], data[1]]))
+                .map(|manufacturer| manufacturer.code)
+                .unwrap_or_default();
+            write_text(code.iter().map(|&letter| letter as u8), buffer, size)
+        }
+        _ => write_text(core::iter::empty(), buffer, size),
+    }
+}
+
+/// Writes the plain text unit of a record to `buffer` like `mbus_record_text`
+///
+/// # Safety
+///
+/// See `mbus_record_text`.
+#[no_mangle]
+pub unsafe extern "C" fn mbus_record_unit_text(
+    record: *const MbusRecord,
+    buffer: *mut c_char,
+    size: usize,
+) -> usize {
+    if record.is_null() || (*record).plain_text_unit.is_null() {
+        return write_text(core::iter::empty(), buffer, size);
+    }
+    let record = &*record;
+    let unit = slice::from_raw_parts(record.plain_text_unit, record.plain_text_unit_length);
+    write_text(unit.iter().rev().copied(), buffer, size)
+}
+
+unsafe fn write_text(
+    text: impl ExactSizeIterator<Item = u8>,
+    buffer: *mut c_char,
+    size: usize,
+) -> usize {
+    let length = text.len();
+    if !buffer.is_null() && size > 0 {
+        let buffer = slice::from_raw_parts_mut(buffer, size);
+        let end = length.min(size - 1);
+        for (target, byte) in buffer.iter_mut().zip(text.take(end)) {
+            *target = byte as c_char;
+        }
+        buffer[end] = 0;
+    }
+    length
+}
+
+impl From<&DataRecord<'_>> for MbusRecord {
+    fn from(record: &DataRecord) -> Self {
+        let mut result = MbusRecord {
+            function: record.function,
+            storage_number: record.storage_number,
+            tariff: record.tariff,
+            sub_unit: record.sub_unit,
+            quantity: record.quantity,
+            unit: record.unit,
+            exponent: record.exponent.inner.unwrap_or(0) as i32,
+            value_type: MbusValueType::Other,
+            value: record.scaled_value().unwrap_or(f64::NAN),
+            unsigned_value: 0,
+            signed_value: 0,
+            date_time: MbusDateTime::default(),
+            data: record.raw_data.as_ptr(),
+            data_length: record.raw_data.len(),
+            plain_text_unit: ptr::null(),
+            plain_text_unit_length: 0,
+        };
+        if let Some(unit) = record.plain_text_unit {
+            result.plain_text_unit = unit.bytes.as_ptr();
+            result.plain_text_unit_length = unit.bytes.len();
+        }
+        match record.value {
+            DataValue::NoData => result.value_type = MbusValueType::NoData,
+            DataValue::Unsigned(value) => {
+                result.value_type = MbusValueType::Unsigned;
+                result.unsigned_value = value;
+            }
+            DataValue::Signed(value) => {
+                result.value_type = MbusValueType::Signed;
+                result.signed_value = value;
+            }
+            DataValue::Bcd(bcd) => {
+                result.value_type = MbusValueType::Bcd;
+                result.signed_value = if bcd.negative {
+                    -(bcd.value as i64)
+                } else {
+                    bcd.value as i64
+                };
+            }
+            DataValue::Real(_) => result.value_type = MbusValueType::Real,
+            DataValue::Date(date) => {
+                result.value_type = MbusValueType::Date;
+                result.date_time.year = date.year;
+                result.date_time.month = date.month;
+                result.date_time.day = date.day;
+            }
+            DataValue::Time(time) => {
+                result.value_type = MbusValueType::Time;
+                result.date_time.hour = time.hour;
+                result.date_time.minute = time.minute;
+                result.date_time.second = time.second;
+            }
+            DataValue::DateTime(date_time) => {
+                result.value_type = MbusValueType::DateTime;
+                result.date_time = MbusDateTime {
+                    year: date_time.date.year,
+                    month: date_time.date.month,
+                    day: date_time.date.day,
+                    hour: date_time.time.hour,
+                    minute: date_time.time.minute,
+                    second: date_time.time.second,
+                    invalid: date_time.invalid,
+                    summer_time: date_time.summer_time,
+                };
+            }
+            DataValue::Ascii(text) => {
+                result.value_type = MbusValueType::Text;
+                result.data = text.bytes.as_ptr();
+                result.data_length = text.bytes.len();
+            }
+            DataValue::Manufacturer(_) => result.value_type = MbusValueType::Manufacturer,
+            DataValue::DaylightSaving(_)
+            | DataValue::ListeningWindow(_)
+            | DataValue::Medium(_)
+            | DataValue::Raw(_) => {}
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The frame of the example in the crate documentation
+    const FRAME: [u8; 83] = [
+        0x68, 0x4D, 0x4D, 0x68, 0x08, 0x01, 0x72, 0x01, 0x00, 0x00, 0x00, 0x96, 0x15, 0x01, 0x00,
+        0x18, 0x00, 0x00, 0x00, 0x0C, 0x78, 0x56, 0x00, 0x00, 0x00, 0x01, 0xFD, 0x1B, 0x00, 0x02,
+        0xFC, 0x03, 0x48, 0x52, 0x25, 0x74, 0x44, 0x0D, 0x22, 0xFC, 0x03, 0x48, 0x52, 0x25, 0x74,
+        0xF1, 0x0C, 0x12, 0xFC, 0x03, 0x48, 0x52, 0x25, 0x74, 0x63, 0x11, 0x02, 0x65, 0xB4, 0x09,
+        0x22, 0x65, 0x86, 0x09, 0x12, 0x65, 0xB7, 0x09, 0x01, 0x72, 0x00, 0x72, 0x65, 0x00, 0x00,
+        0xB2, 0x01, 0x65, 0x00, 0x00, 0x1F, 0xB3, 0x16,
+    ];
+
+    #[test]
+    fn test_parse_frame_and_records() {
+        let mut frame = core::mem::MaybeUninit::<MbusFrame>::uninit();
+        let status = unsafe { mbus_parse_frame(FRAME.as_ptr(), FRAME.len(), frame.as_mut_ptr()) };
+        assert_eq!(status, MbusStatus::Ok);
+        let frame = unsafe { frame.assume_init() };
+        assert_eq!(frame.frame_type, MbusFrameType::LongFrame);
+        assert_eq!((frame.control, frame.address), (0x08, 0x01));
+        assert_eq!(frame.user_data_length, 0x4D - 2);
+
+        let mut user_data = core::mem::MaybeUninit::<MbusUserData>::uninit();
+        let status = unsafe {
+            mbus_parse_user_data(
+                frame.user_data,
+                frame.user_data_length,
+                user_data.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, MbusStatus::Ok);
+        let user_data = unsafe { user_data.assume_init() };
+        assert_eq!(user_data.identification_number, 1);
+        assert_eq!(&user_data.manufacturer, b"ELV\0");
+        assert_eq!(user_data.medium, Medium::Other);
+
+        let mut iterator = core::mem::MaybeUninit::<MbusRecordIterator>::uninit();
+        let mut record = core::mem::MaybeUninit::<MbusRecord>::uninit();
+        let mut records = 0;
+        unsafe {
+            mbus_records_begin(
+                user_data.records,
+                user_data.records_length,
+                iterator.as_mut_ptr(),
+            );
+            while mbus_records_next(iterator.as_mut_ptr(), record.as_mut_ptr()) == MbusStatus::Ok {
+                if records == 0 {
+                    let record = record.assume_init();
+                    assert_eq!(record.value_type, MbusValueType::Bcd);
+                    assert_eq!(record.signed_value, 56);
+                    assert_eq!(record.quantity, Quantity::IdentificationNumber);
+                }
+                records += 1;
+            }
+        }
+        let iterator = unsafe { iterator.assume_init() };
+        let data = unsafe { slice::from_raw_parts(user_data.records, user_data.records_length) };
+        assert_eq!(
+            records,
+            crate::user_data::DataRecords::try_from(data).unwrap().len()
+        );
+        assert!(iterator.more_records_follow);
+        assert_eq!(iterator.manufacturer_data_length, 0);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut frame = core::mem::MaybeUninit::<MbusFrame>::uninit();
+        let data = [0x10, 0x7B, 0x8B, 0x07, 0x16];
+        let status = unsafe { mbus_parse_frame(data.as_ptr(), data.len(), frame.as_mut_ptr()) };
+        assert_eq!(status, MbusStatus::InvalidFrame);
+        let status = unsafe { mbus_parse_frame(ptr::null(), 0, frame.as_mut_ptr()) };
+        assert_eq!(status, MbusStatus::NullPointer);
+
+        let mut user_data = core::mem::MaybeUninit::<MbusUserData>::uninit();
+        let data = [0x72, 0x01];
+        let status =
+            unsafe { mbus_parse_user_data(data.as_ptr(), data.len(), user_data.as_mut_ptr()) };
+        assert_eq!(status, MbusStatus::InvalidUserData);
+    }
+
+    #[test]
+    fn test_record_text() {
+        let data = [0x0D, 0xFD, 0x11, 0x03, b'C', b'B', b'A'];
+        let mut iterator = core::mem::MaybeUninit::<MbusRecordIterator>::uninit();
+        let mut record = core::mem::MaybeUninit::<MbusRecord>::uninit();
+        let mut buffer = [0xFFu8; 3];
+        unsafe {
+            mbus_records_begin(data.as_ptr(), data.len(), iterator.as_mut_ptr());
+            let status = mbus_records_next(iterator.as_mut_ptr(), record.as_mut_ptr());
+            assert_eq!(status, MbusStatus::Ok);
+            assert_eq!(record.assume_init().value_type, MbusValueType::Text);
+            let length =
+                mbus_record_text(record.as_ptr(), buffer.as_mut_ptr().cast(), buffer.len());
+            assert_eq!(length, 3);
+            assert_eq!(&buffer, b"AB\0");
+            let status = mbus_records_next(iterator.as_mut_ptr(), record.as_mut_ptr());
+            assert_eq!(status, MbusStatus::EndOfRecords);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 //! is part of the MBUS data link layer
 //! It is used to encapsulate the application layer data
 
+use core::fmt;
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Frame<'a> {
     SingleCharacter {
         character: u8,
@@ -13,16 +16,19 @@ pub enum Frame<'a> {
     LongFrame {
         function: Function,
         address: Address,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_helpers::hex"))]
         data: &'a [u8],
     },
     ControlFrame {
         function: Function,
         address: Address,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_helpers::hex"))]
         data: &'a [u8],
     },
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Function {
     SndNk,
     SndUd { fcb: bool },
@@ -64,7 +70,24 @@ impl TryFrom<u8> for Function {
     }
 }
 
+/// The name of the norm with the flags of the control field, e.g.
+/// `RSP_UD (ACD 0, DFC 1)`
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Function::SndNk => write!(f, "SND_NKE"),
+            Function::SndUd { fcb } => write!(f, "SND_UD (FCB {})", *fcb as u8),
+            Function::ReqUd2 { fcb } => write!(f, "REQ_UD2 (FCB {})", *fcb as u8),
+            Function::ReqUd1 { fcb } => write!(f, "REQ_UD1 (FCB {})", *fcb as u8),
+            Function::RspUd { acd, dfc } => {
+                write!(f, "RSP_UD (ACD {}, DFC {})", *acd as u8, *dfc as u8)
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Address {
     Uninitalized,
     Primary(u8),
@@ -88,7 +111,25 @@ impl Address {
     }
 }
 
+/// The address byte, with the meaning of the reserved ones
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Address::Uninitalized => write!(f, "0 (unconfigured)"),
+            Address::Primary(address) => write!(f, "{}", address),
+            Address::Secondary => write!(f, "253 (secondary addressing)"),
+            Address::Broadcast {
+                reply_required: true,
+            } => write!(f, "254 (broadcast, reply required)"),
+            Address::Broadcast {
+                reply_required: false,
+            } => write!(f, "255 (broadcast, no reply)"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrameError {
     EmptyData,
     InvalidStartByte,
@@ -126,7 +167,8 @@ impl<'a> TryFrom<&'a [u8]> for Frame<'a> {
 
                 let length = data[1] as usize;
 
-                if data[1] != data[2] || data.len() != length + 6 {
+                // the length covers at least the control and address field
+                if data[1] != data[2] || data.len() != length + 6 || length < 2 {
                     return Err(FrameError::LengthMismatch);
                 }
 
@@ -145,15 +187,14 @@ impl<'a> TryFrom<&'a [u8]> for Frame<'a> {
                 }
             }
             0x10 => {
-                validate_checksum(&data[1..])?;
-                if data.len() == 5 && data[4] == 0x16 {
-                    Ok(Frame::ShortFrame {
-                        function: Function::try_from(data[1])?,
-                        address: Address::from(data[2]),
-                    })
-                } else {
-                    Err(FrameError::LengthMismatch)
+                if data.len() != 5 || data[4] != 0x16 {
+                    return Err(FrameError::LengthMismatch);
                 }
+                validate_checksum(&data[1..])?;
+                Ok(Frame::ShortFrame {
+                    function: Function::try_from(data[1])?,
+                    address: Address::from(data[2]),
+                })
             }
             _ => Err(FrameError::InvalidStartByte),
         }
@@ -182,9 +223,8 @@ fn validate_checksum(data: &[u8]) -> Result<(), FrameError> {
 #[cfg(feature = "std")]
 impl std::error::Error for FrameError {}
 
-#[cfg(feature = "std")]
-impl std::fmt::Display for FrameError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             FrameError::EmptyData => write!(f, "Data is empty"),
             FrameError::InvalidStartByte => write!(f, "Invalid start byte"),
@@ -208,8 +248,38 @@ impl std::fmt::Display for FrameError {
 
 #[cfg(test)]
 mod tests {
+    extern crate std;
+    use std::string::ToString;
+
     use super::*;
 
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            FrameError::WrongChecksum {
+                expected: 0x42,
+                actual: 0x41
+            }
+            .to_string(),
+            "Wrong checksum, expected: 66, actual: 65"
+        );
+        assert_eq!(FrameError::LengthMismatch.to_string(), "Length mismatch");
+        assert_eq!(
+            Function::RspUd {
+                acd: false,
+                dfc: true
+            }
+            .to_string(),
+            "RSP_UD (ACD 0, DFC 1)"
+        );
+        assert_eq!(Function::SndNk.to_string(), "SND_NKE");
+        assert_eq!(Address::from(0x11).to_string(), "17");
+        assert_eq!(
+            Address::from(0xFD).to_string(),
+            "253 (secondary addressing)"
+        );
+    }
+
     #[test]
     fn test_detect_frame_type() {
         let single_character_frame: &[u8] = &[0xE5];
@@ -259,4 +329,29 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_truncated_short_frame() {
+        assert_eq!(
+            Frame::try_from([0x10].as_slice()),
+            Err(FrameError::LengthMismatch)
+        );
+        assert_eq!(
+            Frame::try_from([0x10, 0x7B, 0x8B, 0x06].as_slice()),
+            Err(FrameError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn test_long_frame_without_address() {
+        /* L 1 holds the control field only, L 0 not even that */
+        assert_eq!(
+            Frame::try_from([0x68, 0x01, 0x01, 0x68, 0x08, 0x08, 0x16].as_slice()),
+            Err(FrameError::LengthMismatch)
+        );
+        assert_eq!(
+            Frame::try_from([0x68, 0x00, 0x00, 0x68, 0x00, 0x16].as_slice()),
+            Err(FrameError::LengthMismatch)
+        );
+    }
 }
@@ -39,5 +39,14 @@
 //! ```
 
 #![no_std]
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod dissection;
+pub mod ffi;
 pub mod frames;
+#[cfg(feature = "serde")]
+mod serde_helpers;
 pub mod user_data;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,84 @@
+//! Representations shared by the serde implementations of several types
+
+use core::fmt;
+
+use serde::de::{self, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+use crate::user_data::DataRecords;
+
+/// Bytes written as one hex string, e.g. `0F1A`
+pub(crate) struct HexBytes<'a>(pub &'a [u8]);
+
+impl fmt::Display for HexBytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for HexBytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The digits of BCD data, most significant first, e.g. `FFFF` for invalid
+/// digits
+pub(crate) struct BcdDigits<'a>(pub &'a [u8]);
+
+impl fmt::Display for BcdDigits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter().rev() {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for BcdDigits<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+pub(crate) fn hex<S: Serializer>(bytes: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    HexBytes(bytes).serialize(serializer)
+}
+
+/// Serializes the records of a variable data block instead of its bytes
+pub(crate) fn records<S: Serializer>(data: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let records = DataRecords::try_from(*data).map_err(ser::Error::custom)?;
+    records.serialize(serializer)
+}
+
+struct StrVisitor<T> {
+    expecting: &'static str,
+    parse: fn(&str) -> Option<T>,
+}
+
+impl<T> Visitor<'_> for StrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        (self.parse)(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// Deserializes a value from its string representation
+pub(crate) fn deserialize_str<'de, D, T>(
+    deserializer: D,
+    expecting: &'static str,
+    parse: fn(&str) -> Option<T>,
+) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_str(StrVisitor { expecting, parse })
+}
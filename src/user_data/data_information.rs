@@ -1,11 +1,18 @@
+use super::date_time::{Date, DateTime, DaylightSaving, ListeningWindow, Time};
+use super::{ManufacturerCode, Medium};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataInformationBlock {
     pub _data_information_field: DataInformationField,
     pub _data_information_field_extension: Option<DataInformationExtensionField>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataInformationField {
     pub storage_number: u64,
+    pub tariff: u64,
+    pub sub_unit: u32,
     pub function_field: FunctionField,
     pub data_field_coding: DataFieldCoding,
     pub data_information_extension: Option<DataInformationExtensionField>,
@@ -15,15 +22,34 @@ pub struct DataInformationField {
 const MAXIMUM_DATA_INFORMATION_SIZE: usize = 11;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataInformationExtensionField {}
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataInformationError {
     NoData,
     DataTooLong,
     DataTooShort,
+    InvalidVariableLength { byte: u8 },
+}
+
+impl core::fmt::Display for DataInformationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DataInformationError::NoData => write!(f, "No data"),
+            DataInformationError::DataTooLong => write!(f, "Data too long"),
+            DataInformationError::DataTooShort => write!(f, "Data too short"),
+            DataInformationError::InvalidVariableLength { byte } => {
+                write!(f, "Invalid variable length: {}", byte)
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for DataInformationError {}
+
 impl TryFrom<&[u8]> for DataInformationField {
     type Error = DataInformationError;
 
@@ -34,8 +60,8 @@ impl TryFrom<&[u8]> for DataInformationField {
 
         let mut extension_bit = data[0] & 0x80 != 0;
         let mut extension_index = 1;
-        let mut _tariff = 0;
-        let mut _sub_unit = 0;
+        let mut tariff = 0;
+        let mut sub_unit = 0;
 
         while extension_bit {
             if extension_index > MAXIMUM_DATA_INFORMATION_SIZE {
@@ -45,9 +71,12 @@ impl TryFrom<&[u8]> for DataInformationField {
             let next_byte = *data
                 .get(extension_index)
                 .ok_or(DataInformationError::DataTooShort)?;
-            storage_number += ((next_byte & 0x0f) as u64) << ((extension_index * 4) + 1);
-            _sub_unit += (((next_byte & 0x40) >> 6) as u32) << extension_index;
-            _tariff += (((next_byte & 0x30) >> 4) as u64) << (extension_index * 2);
+            // every DIFE adds the next more significant bits, after the
+            // storage number bit of the DIF
+            let shift = extension_index - 1;
+            storage_number += ((next_byte & 0x0f) as u64) << (shift * 4 + 1);
+            sub_unit += (((next_byte & 0x40) >> 6) as u32) << shift;
+            tariff += (((next_byte & 0x30) >> 4) as u64) << (shift * 2);
             extension_bit = next_byte & 0x80 != 0;
             extension_index += 1;
         }
@@ -74,7 +103,7 @@ impl TryFrom<&[u8]> for DataInformationField {
             0b1100 => DataFieldCoding::BCD8Digit,
             0b1101 => DataFieldCoding::VariableLength,
             0b1110 => DataFieldCoding::BCDDigit12,
-            0b1111 => DataFieldCoding::SpecialFunctions(match data[1] {
+            0b1111 => DataFieldCoding::SpecialFunctions(match data[0] {
                 0x0F => SpecialFunctions::ManufacturerSpecific,
                 0x1F => SpecialFunctions::MoreRecordsFollow,
                 0x2F => SpecialFunctions::IdleFiller,
@@ -86,6 +115,8 @@ impl TryFrom<&[u8]> for DataInformationField {
 
         Ok(DataInformationField {
             storage_number,
+            tariff,
+            sub_unit,
             function_field,
             data_field_coding,
             data_information_extension: if extension_bit {
@@ -104,7 +135,9 @@ impl DataInformationField {
     }
 }
 
+#[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FunctionField {
     InstantaneousValue,
     MaximumValue,
@@ -112,19 +145,19 @@ pub enum FunctionField {
     ValueDuringErrorState,
 }
 
-#[cfg(feature = "std")]
-impl std::fmt::Display for FunctionField {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for FunctionField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            FunctionField::InstantaneousValue => write!(f, "Instantaneous Value"),
-            FunctionField::MaximumValue => write!(f, "Maximum Value"),
-            FunctionField::MinimumValue => write!(f, "Minimum Value"),
-            FunctionField::ValueDuringErrorState => write!(f, "Value During Error State"),
+            FunctionField::InstantaneousValue => write!(f, "Instantaneous value"),
+            FunctionField::MaximumValue => write!(f, "Maximum value"),
+            FunctionField::MinimumValue => write!(f, "Minimum value"),
+            FunctionField::ValueDuringErrorState => write!(f, "Value during error state"),
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpecialFunctions {
     ManufacturerSpecific,
     MoreRecordsFollow,
@@ -133,124 +166,247 @@ pub enum SpecialFunctions {
     GlobalReadoutRequest,
 }
 
-pub struct Value {
-    pub data: f64,
+pub struct Value<'a> {
+    pub data: DataValue<'a>,
     pub byte_size: usize,
 }
 
+/// The decoded data field of a record, typed according to its data field coding
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DataValue<'a> {
+    NoData,
+    Unsigned(u64),
+    Signed(i64),
+    Bcd(Bcd),
+    Real(f32),
+    DateTime(DateTime),
+    Date(Date),
+    Time(Time),
+    DaylightSaving(DaylightSaving),
+    ListeningWindow(ListeningWindow<'a>),
+    Manufacturer(ManufacturerCode),
+    Medium(Medium),
+    Ascii(AsciiText<'a>),
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_helpers::hex"))]
+    Raw(&'a [u8]),
+}
+
+impl DataValue<'_> {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DataValue::Unsigned(x) => Some(*x as f64),
+            DataValue::Signed(x) => Some(*x as f64),
+            DataValue::Bcd(x) => x.as_f64(),
+            DataValue::Real(x) => Some(*x as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Binary coded decimal, `invalid_digits` has a bit set for every digit
+/// (least significant first) that was transmitted as a hex digit A-F
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bcd {
+    pub value: u64,
+    pub negative: bool,
+    pub invalid_digits: u32,
+}
+
+impl Bcd {
+    /// An upper nibble of 0xF in the most significant byte marks a negative
+    /// number, every other nibble above 9 is an invalid digit (meters use
+    /// 0xA-0xE to signal errors) and is counted as zero.
+    pub fn from_le_bytes(data: &[u8]) -> Self {
+        let mut value = 0u64;
+        let mut negative = false;
+        let mut invalid_digits = 0u32;
+        for (index, &byte) in data.iter().enumerate().rev() {
+            for (position, digit) in [(2 * index + 1, byte >> 4), (2 * index, byte & 0x0F)] {
+                let digit = match digit {
+                    0xF if position == 2 * data.len() - 1 => {
+                        negative = true;
+                        0
+                    }
+                    0..=9 => digit,
+                    _ => {
+                        invalid_digits |= 1 << position;
+                        0
+                    }
+                };
+                value = value * 10 + digit as u64;
+            }
+        }
+        Bcd {
+            value,
+            negative,
+            invalid_digits,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.invalid_digits == 0
+    }
+
+    /// Returns `None` if any digit is invalid
+    pub fn as_f64(&self) -> Option<f64> {
+        if !self.is_valid() {
+            None
+        } else if self.negative {
+            Some(-(self.value as f64))
+        } else {
+            Some(self.value as f64)
+        }
+    }
+}
+
+/// ASCII text as transmitted on the bus, that is with the last character first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsciiText<'a> {
+    pub bytes: &'a [u8],
+}
+
+impl<'a> AsciiText<'a> {
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+        self.bytes.iter().rev().map(|&byte| byte as char)
+    }
+}
+
+impl core::fmt::Display for AsciiText<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
+        for c in self.chars() {
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AsciiText<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+fn le_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .rev()
+        .fold(0u64, |acc, &byte| (acc << 8) | byte as u64)
+}
+
+// Integers are transmitted as two's complement (type B), so the most
+// significant bit of the last byte has to be extended to the full width
+fn le_int(data: &[u8]) -> i64 {
+    let unused_bits = 64 - 8 * data.len() as u32;
+    ((le_uint(data) << unused_bits) as i64) >> unused_bits
+}
+
+// Length of the data following the LVAR byte, see EN 13757-3 Table 5
+fn variable_length(lvar: u8) -> Result<usize, DataInformationError> {
+    match lvar {
+        0x00..=0xBF => Ok(lvar as usize),
+        0xC0..=0xC9 => Ok((lvar - 0xC0) as usize),
+        0xD0..=0xD9 => Ok((lvar - 0xD0) as usize),
+        0xE0..=0xEF => Ok((lvar - 0xE0) as usize),
+        0xF0..=0xF4 => Ok(4 * (lvar - 0xEC) as usize),
+        0xF5 => Ok(48),
+        0xF6 => Ok(64),
+        0xF8 => Ok(4),
+        _ => Err(DataInformationError::InvalidVariableLength { byte: lvar }),
+    }
+}
+
+fn extract_variable_length(lvar: u8, data: &[u8]) -> DataValue<'_> {
+    match lvar {
+        0x00..=0xBF => DataValue::Ascii(AsciiText { bytes: data }),
+        0xC0..=0xC9 | 0xD0..=0xD9 => {
+            let mut bcd = Bcd::from_le_bytes(data);
+            bcd.negative |= lvar >= 0xD0;
+            DataValue::Bcd(bcd)
+        }
+        0xE0 => DataValue::NoData,
+        0xE1..=0xE8 => DataValue::Signed(le_int(data)),
+        0xF8 => DataValue::Real(f32::from_le_bytes([data[0], data[1], data[2], data[3]])),
+        _ => DataValue::Raw(data),
+    }
+}
+
 impl DataFieldCoding {
-    pub fn extract_from_bytes(&self, data: &[u8]) -> Value {
-        match *self {
-            DataFieldCoding::Real32Bit => Value {
-                data: f32::from_le_bytes([data[0], data[1], data[2], data[3]]) as f64,
-                byte_size: 4,
-            },
-            DataFieldCoding::Integer8Bit => Value {
-                data: data[0] as f64,
-                byte_size: 1,
-            },
-            DataFieldCoding::Integer16Bit => Value {
-                data: ((data[1] as u16) << 8 | data[0] as u16) as f64,
-                byte_size: 2,
-            },
-            DataFieldCoding::Integer24Bit => Value {
-                data: ((data[2] as u32) << 16 | (data[1] as u32) << 8 | data[0] as u32) as f64,
-                byte_size: 3,
-            },
-            DataFieldCoding::Integer32Bit => Value {
-                data: ((data[3] as u32) << 24
-                    | (data[2] as u32) << 16
-                    | (data[1] as u32) << 8
-                    | data[0] as u32) as f64,
-                byte_size: 4,
-            },
-            DataFieldCoding::Integer48Bit => Value {
-                data: ((data[5] as u64) << 40
-                    | (data[4] as u64) << 32
-                    | (data[3] as u64) << 24
-                    | (data[2] as u64) << 16
-                    | (data[1] as u64) << 8
-                    | data[0] as u64) as f64,
-                byte_size: 6,
-            },
-            DataFieldCoding::Integer64Bit => Value {
-                data: ((data[7] as u64) << 56
-                    | (data[6] as u64) << 48
-                    | (data[5] as u64) << 40
-                    | (data[4] as u64) << 32
-                    | (data[3] as u64) << 24
-                    | (data[2] as u64) << 16
-                    | (data[1] as u64) << 8
-                    | data[0] as u64) as f64,
-                byte_size: 8,
-            },
-            DataFieldCoding::BCD2Digit => Value {
-                data: ((data[0] >> 4) as f64 * 10.0) + (data[0] & 0x0F) as f64,
-                byte_size: 1,
-            },
-            DataFieldCoding::BCD4Digit => Value {
-                data: ((data[1] >> 4) as f64 * 1000.0)
-                    + ((data[1] & 0x0F) as f64 * 100.0)
-                    + ((data[0] >> 4) as f64 * 10.0)
-                    + (data[0] & 0x0F) as f64,
-                byte_size: 2,
-            },
-            DataFieldCoding::BCD6Digit => Value {
-                data: ((data[2] >> 4) as f64 * 100000.0)
-                    + ((data[2] & 0x0F) as f64 * 10000.0)
-                    + ((data[1] >> 4) as f64 * 1000.0)
-                    + ((data[1] & 0x0F) as f64 * 100.0)
-                    + ((data[0] >> 4) as f64 * 10.0)
-                    + (data[0] & 0x0F) as f64,
-                byte_size: 3,
-            },
-            DataFieldCoding::BCD8Digit => Value {
-                data: ((data[3] >> 4) as f64 * 10000000.0)
-                    + ((data[3] & 0x0F) as f64 * 1000000.0)
-                    + ((data[2] >> 4) as f64 * 100000.0)
-                    + ((data[2] & 0x0F) as f64 * 10000.0)
-                    + ((data[1] >> 4) as f64 * 1000.0)
-                    + ((data[1] & 0x0F) as f64 * 100.0)
-                    + ((data[0] >> 4) as f64 * 10.0)
-                    + (data[0] & 0x0F) as f64,
-                byte_size: 4,
-            },
-            DataFieldCoding::BCDDigit12 => Value {
-                data: ((data[5] >> 4) as f64 * 100000000000.0)
-                    + ((data[5] & 0x0F) as f64 * 10000000000.0)
-                    + ((data[4] >> 4) as f64 * 1000000000.0)
-                    + ((data[4] & 0x0F) as f64 * 100000000.0)
-                    + ((data[3] >> 4) as f64 * 10000000.0)
-                    + ((data[3] & 0x0F) as f64 * 1000000.0)
-                    + ((data[2] >> 4) as f64 * 100000.0)
-                    + ((data[2] & 0x0F) as f64 * 10000.0)
-                    + ((data[1] >> 4) as f64 * 1000.0)
-                    + ((data[1] & 0x0F) as f64 * 100.0)
-                    + ((data[0] >> 4) as f64 * 10.0)
-                    + (data[0] & 0x0F) as f64,
-                byte_size: 6,
-            },
-            DataFieldCoding::NoData => Value {
-                data: 0.0,
-                byte_size: 0,
-            },
-            DataFieldCoding::SelectionForReadout => Value {
-                data: 0.0,
-                byte_size: 0,
-            },
-            DataFieldCoding::SpecialFunctions(_) => Value {
-                data: 0.0,
-                byte_size: 0,
-            },
-            DataFieldCoding::VariableLength => Value {
-                data: 0.0,
-                byte_size: 0,
-            },
+    pub fn byte_size(&self, data: &[u8]) -> Result<usize, DataInformationError> {
+        Ok(match *self {
+            DataFieldCoding::NoData
+            | DataFieldCoding::SelectionForReadout
+            | DataFieldCoding::SpecialFunctions(_) => 0,
+            DataFieldCoding::Integer8Bit | DataFieldCoding::BCD2Digit => 1,
+            DataFieldCoding::Integer16Bit | DataFieldCoding::BCD4Digit => 2,
+            DataFieldCoding::Integer24Bit | DataFieldCoding::BCD6Digit => 3,
+            DataFieldCoding::Integer32Bit
+            | DataFieldCoding::Real32Bit
+            | DataFieldCoding::BCD8Digit => 4,
+            DataFieldCoding::Integer48Bit | DataFieldCoding::BCDDigit12 => 6,
+            DataFieldCoding::Integer64Bit => 8,
+            DataFieldCoding::VariableLength => {
+                let lvar = *data.first().ok_or(DataInformationError::DataTooShort)?;
+                1 + variable_length(lvar)?
+            }
+        })
+    }
+
+    /// Same as `extract_from_bytes`, but reads integers as unsigned (type C)
+    /// or bit fields (type D) instead of two's complement
+    pub fn extract_unsigned_from_bytes<'a>(
+        &self,
+        data: &'a [u8],
+    ) -> Result<Value<'a>, DataInformationError> {
+        let value = self.extract_from_bytes(data)?;
+        match value.data {
+            DataValue::Signed(_) => Ok(Value {
+                data: DataValue::Unsigned(le_uint(match self {
+                    DataFieldCoding::VariableLength => &data[1..value.byte_size],
+                    _ => &data[..value.byte_size],
+                })),
+                byte_size: value.byte_size,
+            }),
+            _ => Ok(value),
         }
     }
+
+    pub fn extract_from_bytes<'a>(
+        &self,
+        data: &'a [u8],
+    ) -> Result<Value<'a>, DataInformationError> {
+        let byte_size = self.byte_size(data)?;
+        let bytes = data
+            .get(..byte_size)
+            .ok_or(DataInformationError::DataTooShort)?;
+        let data = match *self {
+            DataFieldCoding::Real32Bit => {
+                DataValue::Real(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            DataFieldCoding::Integer8Bit
+            | DataFieldCoding::Integer16Bit
+            | DataFieldCoding::Integer24Bit
+            | DataFieldCoding::Integer32Bit
+            | DataFieldCoding::Integer48Bit
+            | DataFieldCoding::Integer64Bit => DataValue::Signed(le_int(bytes)),
+            DataFieldCoding::BCD2Digit
+            | DataFieldCoding::BCD4Digit
+            | DataFieldCoding::BCD6Digit
+            | DataFieldCoding::BCD8Digit
+            | DataFieldCoding::BCDDigit12 => DataValue::Bcd(Bcd::from_le_bytes(bytes)),
+            DataFieldCoding::VariableLength => extract_variable_length(bytes[0], &bytes[1..]),
+            DataFieldCoding::NoData
+            | DataFieldCoding::SelectionForReadout
+            | DataFieldCoding::SpecialFunctions(_) => DataValue::NoData,
+        };
+        Ok(Value { data, byte_size })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataFieldCoding {
     NoData,
     Integer8Bit,
@@ -276,12 +432,14 @@ mod tests {
     use super::*;
     #[test]
     fn test_data_information() {
-        let data = [0x13 as u8];
+        let data = [0x13_u8];
         let result = DataInformationField::try_from(data.as_slice());
         assert_eq!(
             result,
             Ok(DataInformationField {
                 storage_number: 0,
+                tariff: 0,
+                sub_unit: 0,
                 function_field: FunctionField::MaximumValue,
                 data_field_coding: DataFieldCoding::Integer24Bit,
                 data_information_extension: None,
@@ -290,6 +448,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_data_information_extensions() {
+        /* storage No 5 and tariff 2 of subunit 1, see the examples of the
+        variable data structure at m-bus.com */
+        let data = [0xDA, 0x02];
+        let result = DataInformationField::try_from(data.as_slice()).unwrap();
+        assert_eq!(result.storage_number, 5);
+        assert_eq!(result.size, 2);
+
+        let data = [0x8B, 0x60];
+        let result = DataInformationField::try_from(data.as_slice()).unwrap();
+        assert_eq!(
+            (result.storage_number, result.tariff, result.sub_unit),
+            (0, 2, 1)
+        );
+
+        /* the second DIFE adds the next bits of every field */
+        let data = [0x84, 0xA1, 0x72];
+        let result = DataInformationField::try_from(data.as_slice()).unwrap();
+        assert_eq!(result.storage_number, 2 | (2 << 5));
+        assert_eq!(result.tariff, 2 | (3 << 2));
+        assert_eq!(result.sub_unit, 1 << 1);
+        assert_eq!(result.size, 3);
+    }
+
+    #[test]
+    fn test_special_functions() {
+        for (dif, function) in [
+            (0x0F, SpecialFunctions::ManufacturerSpecific),
+            (0x1F, SpecialFunctions::MoreRecordsFollow),
+            (0x2F, SpecialFunctions::IdleFiller),
+            (0x7F, SpecialFunctions::GlobalReadoutRequest),
+            (0x3F, SpecialFunctions::Reserved),
+        ] {
+            /* the DIF is the last byte of the telegram, e.g. 0x1F */
+            let result = DataInformationField::try_from([dif].as_slice()).unwrap();
+            assert_eq!(
+                result.data_field_coding,
+                DataFieldCoding::SpecialFunctions(function)
+            );
+            assert_eq!(result.size, 1);
+        }
+    }
+
     #[test]
     fn test_invalid_data_information() {
         let data = [
@@ -308,6 +510,116 @@ mod tests {
         assert_ne!(result, Err(DataInformationError::DataTooLong));
     }
 
+    #[test]
+    fn test_extract_integer_64_bit_without_precision_loss() {
+        let data = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x3F, 0x00];
+        let value = DataFieldCoding::Integer64Bit
+            .extract_from_bytes(&data)
+            .unwrap();
+        assert_eq!(value.data, DataValue::Signed((1 << 54) - 1));
+        assert_eq!(value.byte_size, 8);
+    }
+
+    #[test]
+    fn test_extract_signed_integers() {
+        let value = DataFieldCoding::Integer16Bit
+            .extract_from_bytes(&[0x38, 0xFF])
+            .unwrap();
+        assert_eq!(value.data, DataValue::Signed(-200));
+        let value = DataFieldCoding::Integer24Bit
+            .extract_from_bytes(&[0xFF, 0xFF, 0x7F])
+            .unwrap();
+        assert_eq!(value.data, DataValue::Signed(0x7FFFFF));
+        let value = DataFieldCoding::Integer64Bit
+            .extract_from_bytes(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80])
+            .unwrap();
+        assert_eq!(value.data, DataValue::Signed(i64::MIN));
+        let value = DataFieldCoding::Integer8Bit
+            .extract_unsigned_from_bytes(&[0xFF])
+            .unwrap();
+        assert_eq!(value.data, DataValue::Unsigned(255));
+    }
+
+    #[test]
+    fn test_extract_negative_and_invalid_bcd() {
+        let value = DataFieldCoding::BCD4Digit
+            .extract_from_bytes(&[0x23, 0xF1])
+            .unwrap();
+        assert_eq!(value.data.as_f64(), Some(-123.0));
+        let value = DataFieldCoding::BCD4Digit
+            .extract_from_bytes(&[0x2E, 0x01])
+            .unwrap();
+        assert_eq!(
+            value.data,
+            DataValue::Bcd(Bcd {
+                value: 120,
+                negative: false,
+                invalid_digits: 0b0001,
+            })
+        );
+        assert_eq!(value.data.as_f64(), None);
+        let value = DataFieldCoding::BCD4Digit
+            .extract_from_bytes(&[0xF3, 0x01])
+            .unwrap();
+        assert_eq!(value.data.as_f64(), None);
+    }
+
+    #[test]
+    fn test_extract_bcd() {
+        let data = [0x37, 0x18, 0x02];
+        let value = DataFieldCoding::BCD6Digit
+            .extract_from_bytes(&data)
+            .unwrap();
+        assert_eq!(value.data.as_f64(), Some(21837.0));
+        assert_eq!(value.byte_size, 3);
+    }
+
+    #[test]
+    fn test_extract_variable_length() {
+        let coding = DataFieldCoding::VariableLength;
+        // fabrication number as ASCII, transmitted last character first
+        let value = coding
+            .extract_from_bytes(&[0x04, b'4', b'3', b'2', b'1'])
+            .unwrap();
+        assert_eq!(value.byte_size, 5);
+        match value.data {
+            DataValue::Ascii(text) => assert!(text.chars().eq("1234".chars())),
+            _ => panic!("expected ascii text, got {:?}", value.data),
+        }
+        let value = coding.extract_from_bytes(&[0xC2, 0x34, 0x12]).unwrap();
+        assert_eq!(value.data.as_f64(), Some(1234.0));
+        let value = coding.extract_from_bytes(&[0xD2, 0x34, 0x12]).unwrap();
+        assert_eq!(value.data.as_f64(), Some(-1234.0));
+        let value = coding.extract_from_bytes(&[0xE2, 0x38, 0xFF]).unwrap();
+        assert_eq!(value.data, DataValue::Signed(-200));
+        let value = coding
+            .extract_from_bytes(&[0xF8, 0x00, 0x00, 0x20, 0x41])
+            .unwrap();
+        assert_eq!(value.data, DataValue::Real(10.0));
+        let mut large = [0u8; 17];
+        large[0] = 0xF0;
+        let value = coding.extract_from_bytes(&large).unwrap();
+        assert_eq!(value.byte_size, 17);
+        assert_eq!(value.data, DataValue::Raw(&large[1..]));
+    }
+
+    #[test]
+    fn test_extract_variable_length_errors() {
+        let coding = DataFieldCoding::VariableLength;
+        assert_eq!(
+            coding
+                .extract_from_bytes(&[0x05, b'A'])
+                .map(|value| value.byte_size),
+            Err(DataInformationError::DataTooShort)
+        );
+        assert_eq!(
+            coding
+                .extract_from_bytes(&[0xCA])
+                .map(|value| value.byte_size),
+            Err(DataInformationError::InvalidVariableLength { byte: 0xCA })
+        );
+    }
+
     #[test]
     fn test_short_data_information() {
         let data = [0xFF];
@@ -0,0 +1,501 @@
+//! is a part of the application layer
+//! decodes the compound date and time data types of EN 13757-3 Annex A
+
+use core::fmt;
+
+/// Date as encoded by data type G (CP16)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// Time of day as encoded by data type J (CP24)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+/// Date and time as encoded by data types F (CP32), I (CP48) and M (LVAR)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+    /// the time invalid bit (IV) was set by the meter
+    pub invalid: bool,
+    /// the summer time bit (SU) was set by the meter
+    pub summer_time: bool,
+    /// offset to UTC in minutes, only transmitted by data type M
+    pub utc_offset: Option<i16>,
+}
+
+/// Begin and end of the daylight saving period as encoded by data type K
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DaylightSaving {
+    pub begin_month: u8,
+    pub begin_day: u8,
+    pub begin_hour: u8,
+    pub end_month: u8,
+    pub end_day: u8,
+    /// deviation from standard time in hours during the daylight saving period
+    pub deviation: u8,
+}
+
+/// Listening window management as encoded by data type L, every bit marks
+/// one time slot (least significant bit of the first byte first) in which
+/// the meter is listening
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ListeningWindow<'a> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_helpers::hex"))]
+    pub slots: &'a [u8],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DateTimeError {
+    InvalidLength { length: usize },
+    InvalidDate,
+    InvalidTime,
+    MissingUtcOffset,
+}
+
+// The year is transmitted as a two digit number, values below 81 belong to
+// this century, everything else to the previous one (same as libmbus). Newer
+// editions of the norm also transmit hundreds of years, which take precedence.
+fn full_year(year: u8, hundred_years: u8) -> u16 {
+    if hundred_years != 0 {
+        1900 + 100 * hundred_years as u16 + year as u16
+    } else if year < 81 {
+        2000 + year as u16
+    } else {
+        1900 + year as u16
+    }
+}
+
+/// The year of a date of data type G, F or I as transmitted, that is 0-127
+/// without the century, from the bytes with the day and the month
+pub fn transmitted_year(date: [u8; 2]) -> u8 {
+    ((date[0] & 0xE0) >> 5) | ((date[1] & 0xF0) >> 1)
+}
+
+impl Date {
+    pub fn from_type_g(data: [u8; 2]) -> Self {
+        let year = transmitted_year(data);
+        Date {
+            year: full_year(year, 0),
+            month: data[1] & 0x0F,
+            day: data[0] & 0x1F,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let days_in_month = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0) => 29,
+            2 => 28,
+            _ => return false,
+        };
+        (1..=days_in_month).contains(&self.day)
+    }
+}
+
+impl Time {
+    pub fn from_type_j(data: [u8; 3]) -> Self {
+        Time {
+            hour: data[2] & 0x1F,
+            minute: data[1] & 0x3F,
+            second: data[0] & 0x3F,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.hour < 24 && self.minute < 60 && self.second < 60
+    }
+}
+
+impl DateTime {
+    pub fn from_type_f(data: [u8; 4]) -> Self {
+        let year = transmitted_year([data[2], data[3]]);
+        let hundred_years = (data[1] & 0x60) >> 5;
+        DateTime {
+            date: Date {
+                year: full_year(year, hundred_years),
+                month: data[3] & 0x0F,
+                day: data[2] & 0x1F,
+            },
+            time: Time {
+                hour: data[1] & 0x1F,
+                minute: data[0] & 0x3F,
+                second: 0,
+            },
+            invalid: data[0] & 0x80 != 0,
+            summer_time: data[1] & 0x80 != 0,
+            utc_offset: None,
+        }
+    }
+
+    pub fn from_type_i(data: [u8; 6]) -> Self {
+        let year = transmitted_year([data[3], data[4]]);
+        DateTime {
+            date: Date {
+                year: full_year(year, 0),
+                month: data[4] & 0x0F,
+                day: data[3] & 0x1F,
+            },
+            time: Time {
+                hour: data[2] & 0x1F,
+                minute: data[1] & 0x3F,
+                second: data[0] & 0x3F,
+            },
+            invalid: data[1] & 0x80 != 0,
+            summer_time: data[1] & 0x40 != 0,
+            utc_offset: None,
+        }
+    }
+
+    /// Data type M is transmitted as LVAR binary and consists of a type F or
+    /// type I date and time followed by the signed offset to UTC in units of
+    /// 15 minutes.
+    pub fn from_type_m(data: &[u8]) -> Result<Self, DateTimeError> {
+        let (mut date_time, offset) = match data.len() {
+            5 => (
+                DateTime::from_type_f([data[0], data[1], data[2], data[3]]),
+                data[4],
+            ),
+            7 => (
+                DateTime::from_type_i([data[0], data[1], data[2], data[3], data[4], data[5]]),
+                data[6],
+            ),
+            length => return Err(DateTimeError::InvalidLength { length }),
+        };
+        date_time.utc_offset = Some(offset as i8 as i16 * 15);
+        Ok(date_time)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.invalid && self.date.is_valid() && self.time.is_valid()
+    }
+}
+
+/// ISO 8601, e.g. `2024-01-31`
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// ISO 8601, e.g. `12:30:00`
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+/// ISO 8601, e.g. `2024-01-31T12:30:00`, with the offset to UTC if the meter
+/// transmitted it
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        if let Some(offset) = self.utc_offset {
+            let sign = if offset < 0 { '-' } else { '+' };
+            let offset = offset.unsigned_abs();
+            write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)?;
+        }
+        Ok(())
+    }
+}
+
+impl DaylightSaving {
+    pub fn from_type_k(data: [u8; 4]) -> Self {
+        DaylightSaving {
+            begin_month: data[2] & 0x0F,
+            begin_day: data[1] & 0x1F,
+            begin_hour: data[0] & 0x1F,
+            end_month: (data[2] & 0xF0) >> 4,
+            end_day: data[3] & 0x1F,
+            deviation: (data[0] & 0xC0) >> 6,
+        }
+    }
+}
+
+impl<'a> ListeningWindow<'a> {
+    pub fn from_type_l(data: &'a [u8]) -> Self {
+        ListeningWindow { slots: data }
+    }
+
+    pub fn is_listening(&self, slot: usize) -> bool {
+        self.slots
+            .get(slot / 8)
+            .is_some_and(|byte| byte & (1 << (slot % 8)) != 0)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Date> for chrono::NaiveDate {
+    type Error = DateTimeError;
+
+    fn try_from(date: Date) -> Result<Self, DateTimeError> {
+        chrono::NaiveDate::from_ymd_opt(date.year as i32, date.month as u32, date.day as u32)
+            .ok_or(DateTimeError::InvalidDate)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Time> for chrono::NaiveTime {
+    type Error = DateTimeError;
+
+    fn try_from(time: Time) -> Result<Self, DateTimeError> {
+        chrono::NaiveTime::from_hms_opt(time.hour as u32, time.minute as u32, time.second as u32)
+            .ok_or(DateTimeError::InvalidTime)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<DateTime> for chrono::NaiveDateTime {
+    type Error = DateTimeError;
+
+    fn try_from(date_time: DateTime) -> Result<Self, DateTimeError> {
+        if date_time.invalid {
+            return Err(DateTimeError::InvalidTime);
+        }
+        Ok(chrono::NaiveDateTime::new(
+            date_time.date.try_into()?,
+            date_time.time.try_into()?,
+        ))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<DateTime> for chrono::DateTime<chrono::FixedOffset> {
+    type Error = DateTimeError;
+
+    fn try_from(date_time: DateTime) -> Result<Self, DateTimeError> {
+        let offset = date_time
+            .utc_offset
+            .ok_or(DateTimeError::MissingUtcOffset)?;
+        let offset =
+            chrono::FixedOffset::east_opt(offset as i32 * 60).ok_or(DateTimeError::InvalidTime)?;
+        let local: chrono::NaiveDateTime = date_time.try_into()?;
+        local
+            .and_local_timezone(offset)
+            .single()
+            .ok_or(DateTimeError::InvalidTime)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Date> for time::Date {
+    type Error = DateTimeError;
+
+    fn try_from(date: Date) -> Result<Self, DateTimeError> {
+        let month = time::Month::try_from(date.month).map_err(|_| DateTimeError::InvalidDate)?;
+        time::Date::from_calendar_date(date.year as i32, month, date.day)
+            .map_err(|_| DateTimeError::InvalidDate)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Time> for time::Time {
+    type Error = DateTimeError;
+
+    fn try_from(time: Time) -> Result<Self, DateTimeError> {
+        time::Time::from_hms(time.hour, time.minute, time.second)
+            .map_err(|_| DateTimeError::InvalidTime)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for time::PrimitiveDateTime {
+    type Error = DateTimeError;
+
+    fn try_from(date_time: DateTime) -> Result<Self, DateTimeError> {
+        if date_time.invalid {
+            return Err(DateTimeError::InvalidTime);
+        }
+        Ok(time::PrimitiveDateTime::new(
+            date_time.date.try_into()?,
+            date_time.time.try_into()?,
+        ))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for time::OffsetDateTime {
+    type Error = DateTimeError;
+
+    fn try_from(date_time: DateTime) -> Result<Self, DateTimeError> {
+        let offset = date_time
+            .utc_offset
+            .ok_or(DateTimeError::MissingUtcOffset)?;
+        let offset = time::UtcOffset::from_whole_seconds(offset as i32 * 60)
+            .map_err(|_| DateTimeError::InvalidTime)?;
+        let local: time::PrimitiveDateTime = date_time.try_into()?;
+        Ok(local.assume_offset(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let date_time = DateTime::from_type_m(&[0x1E, 0x8C, 0xBF, 0x1C, 0x04]).unwrap();
+        assert_eq!(date_time.to_string(), "2013-12-31T12:30:00+01:00");
+        assert_eq!(date_time.date.to_string(), "2013-12-31");
+        assert_eq!(date_time.time.to_string(), "12:30:00");
+    }
+
+    #[test]
+    fn test_type_g() {
+        // EFE_Engelmann-Elster-SensoStar-2: 02 6C BF 1C => 2013-12-31
+        assert_eq!(
+            Date::from_type_g([0xBF, 0x1C]),
+            Date {
+                year: 2013,
+                month: 12,
+                day: 31
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_f() {
+        // EFE_Engelmann-Elster-SensoStar-2: 04 6D 17 2E CC 13 => 2014-03-12T14:23
+        assert_eq!(
+            DateTime::from_type_f([0x17, 0x2E, 0xCC, 0x13]),
+            DateTime {
+                date: Date {
+                    year: 2014,
+                    month: 3,
+                    day: 12
+                },
+                time: Time {
+                    hour: 14,
+                    minute: 23,
+                    second: 0
+                },
+                invalid: false,
+                summer_time: false,
+                utc_offset: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_f_invalid_and_summer_time() {
+        let date_time = DateTime::from_type_f([0x97, 0x8E, 0xCC, 0x13]);
+        assert!(date_time.invalid);
+        assert!(date_time.summer_time);
+        assert!(!date_time.is_valid());
+    }
+
+    #[test]
+    fn test_type_i() {
+        // 2021-06-15T08:30:45, summer time
+        let date_time = DateTime::from_type_i([0x2D, 0x5E, 0x08, 0xAF, 0x26, 0x18]);
+        assert_eq!(
+            date_time.date,
+            Date {
+                year: 2021,
+                month: 6,
+                day: 15
+            }
+        );
+        assert_eq!(
+            date_time.time,
+            Time {
+                hour: 8,
+                minute: 30,
+                second: 45
+            }
+        );
+        assert!(date_time.summer_time);
+        assert!(!date_time.invalid);
+    }
+
+    #[test]
+    fn test_type_j() {
+        assert_eq!(
+            Time::from_type_j([0x2D, 0x1E, 0x08]),
+            Time {
+                hour: 8,
+                minute: 30,
+                second: 45
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_k() {
+        // last sunday of march 02:00 until the end of october, one hour deviation
+        assert_eq!(
+            DaylightSaving::from_type_k([0x42, 0x1F, 0xA3, 0x1F]),
+            DaylightSaving {
+                begin_month: 3,
+                begin_day: 31,
+                begin_hour: 2,
+                end_month: 10,
+                end_day: 31,
+                deviation: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_l() {
+        let window = ListeningWindow::from_type_l(&[0x01, 0x80]);
+        assert!(window.is_listening(0));
+        assert!(!window.is_listening(1));
+        assert!(window.is_listening(15));
+        assert!(!window.is_listening(16));
+    }
+
+    #[test]
+    fn test_type_m() {
+        let date_time = DateTime::from_type_m(&[0x17, 0x2E, 0xCC, 0x13, 0x04]).unwrap();
+        assert_eq!(date_time.time.hour, 14);
+        assert_eq!(date_time.utc_offset, Some(60));
+        assert_eq!(
+            DateTime::from_type_m(&[0x17, 0x2E, 0xCC]),
+            Err(DateTimeError::InvalidLength { length: 3 })
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_conversion() {
+        let date_time = DateTime::from_type_m(&[0x17, 0x2E, 0xCC, 0x13, 0x04]).unwrap();
+        use chrono::{Datelike, Offset, Timelike};
+        let local = chrono::NaiveDateTime::try_from(date_time).unwrap();
+        assert_eq!((local.year(), local.month(), local.day()), (2014, 3, 12));
+        assert_eq!((local.hour(), local.minute()), (14, 23));
+        let zoned = chrono::DateTime::<chrono::FixedOffset>::try_from(date_time).unwrap();
+        assert_eq!(zoned.offset().fix().local_minus_utc(), 3600);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_conversion() {
+        let date_time = DateTime::from_type_f([0x17, 0x2E, 0xCC, 0x13]);
+        let local = time::PrimitiveDateTime::try_from(date_time).unwrap();
+        assert_eq!(local.hour(), 14);
+        assert_eq!(local.day(), 12);
+        assert_eq!(
+            time::OffsetDateTime::try_from(date_time),
+            Err(DateTimeError::MissingUtcOffset)
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Exact decimal values, `mantissa * 10^exponent`, so that meter readings
+//! can be displayed, compared and summed without passing through floating
+//! point.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use super::unit_conversion::scale;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    pub mantissa: i128,
+    pub exponent: isize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecimalError {
+    /// The value is not an integer or a BCD without invalid digits
+    NonDecimalValue,
+    /// The records have different quantities or units
+    DifferentUnits,
+    Overflow,
+    /// The text is not a decimal number like `-218.30`
+    InvalidFormat,
+}
+
+fn power_of_ten(exponent: usize) -> Option<i128> {
+    let mut power: i128 = 1;
+    for _ in 0..exponent {
+        power = power.checked_mul(10)?;
+    }
+    Some(power)
+}
+
+impl Decimal {
+    pub const fn new(mantissa: i128, exponent: isize) -> Self {
+        Decimal { mantissa, exponent }
+    }
+
+    /// The same value with a smaller exponent, `None` if the exponent is
+    /// larger or the mantissa overflows
+    pub fn with_exponent(self, exponent: isize) -> Option<Self> {
+        if exponent > self.exponent {
+            return None;
+        }
+        if self.mantissa == 0 {
+            return Some(Decimal::new(0, exponent));
+        }
+        let shift = (self.exponent - exponent) as usize;
+        let mantissa = self.mantissa.checked_mul(power_of_ten(shift)?)?;
+        Some(Decimal { mantissa, exponent })
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let exponent = self.exponent.min(other.exponent);
+        let mantissa = self
+            .with_exponent(exponent)?
+            .mantissa
+            .checked_add(other.with_exponent(exponent)?.mantissa)?;
+        Some(Decimal { mantissa, exponent })
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        let exponent = self.exponent.min(other.exponent);
+        let mantissa = self
+            .with_exponent(exponent)?
+            .mantissa
+            .checked_sub(other.with_exponent(exponent)?.mantissa)?;
+        Some(Decimal { mantissa, exponent })
+    }
+
+    pub fn to_f64(self) -> f64 {
+        scale(self.mantissa as f64, self.exponent)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let exponent = self.exponent.min(other.exponent);
+        match (self.with_exponent(exponent), other.with_exponent(exponent)) {
+            (Some(left), Some(right)) => left.mantissa.cmp(&right.mantissa),
+            // Only the value with the larger exponent can overflow, its
+            // magnitude then exceeds the other value
+            (None, _) => self.mantissa.cmp(&0),
+            (_, None) => 0.cmp(&other.mantissa),
+        }
+    }
+}
+
+/// Prints all digits of the mantissa, e.g. 21830 * 10^-2 as `218.30`, so the
+/// resolution of the meter is kept
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0u8; 39];
+        let mut remaining = self.mantissa.unsigned_abs();
+        let mut start = buffer.len();
+        loop {
+            start -= 1;
+            buffer[start] = b'0' + (remaining % 10) as u8;
+            remaining /= 10;
+            if remaining == 0 {
+                break;
+            }
+        }
+        let digits = core::str::from_utf8(&buffer[start..]).map_err(|_| fmt::Error)?;
+
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+        if self.exponent >= 0 {
+            f.write_str(digits)?;
+            if self.mantissa != 0 {
+                for _ in 0..self.exponent {
+                    f.write_str("0")?;
+                }
+            }
+            return Ok(());
+        }
+
+        let fraction = self.exponent.unsigned_abs();
+        if digits.len() > fraction {
+            let (integer, fractional) = digits.split_at(digits.len() - fraction);
+            write!(f, "{}.{}", integer, fractional)
+        } else {
+            f.write_str("0.")?;
+            for _ in digits.len()..fraction {
+                f.write_str("0")?;
+            }
+            f.write_str(digits)
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    fn from_str(text: &str) -> Result<Self, DecimalError> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (integer, fractional) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer.is_empty() && fractional.is_empty() {
+            return Err(DecimalError::InvalidFormat);
+        }
+
+        let mut mantissa: i128 = 0;
+        for byte in integer.bytes().chain(fractional.bytes()) {
+            if !byte.is_ascii_digit() {
+                return Err(DecimalError::InvalidFormat);
+            }
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|mantissa| mantissa.checked_add((byte - b'0') as i128))
+                .ok_or(DecimalError::Overflow)?;
+        }
+        if negative {
+            mantissa = -mantissa;
+        }
+        Ok(Decimal::new(mantissa, -(fractional.len() as isize)))
+    }
+}
+
+/// Serialized as its exact text, so no precision is lost in formats like JSON
+#[cfg(feature = "serde")]
+impl serde::Serialize for Decimal {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Decimal {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_helpers::deserialize_str(deserializer, "a decimal number", |value| {
+            value.parse().ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::string::ToString;
+
+    use super::{Decimal, DecimalError};
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Decimal::new(21837, -2).to_string(), "218.37");
+        assert_eq!(Decimal::new(21830, -2).to_string(), "218.30");
+        assert_eq!(Decimal::new(-5, -2).to_string(), "-0.05");
+        assert_eq!(Decimal::new(123, 3).to_string(), "123000");
+        assert_eq!(Decimal::new(0, 3).to_string(), "0");
+        assert_eq!(Decimal::new(7, 0).to_string(), "7");
+        assert_eq!(Decimal::new(100, -3).to_string(), "0.100");
+    }
+
+    #[test]
+    fn test_parse() {
+        let decimal: Decimal = "-218.30".parse().unwrap();
+        assert_eq!((decimal.mantissa, decimal.exponent), (-21830, -2));
+        let decimal: Decimal = "12".parse().unwrap();
+        assert_eq!((decimal.mantissa, decimal.exponent), (12, 0));
+        assert_eq!(".5".parse(), Ok(Decimal::new(5, -1)));
+        for text in ["", "-", ".", "1.2.3", "1e3", "12a"] {
+            assert_eq!(text.parse::<Decimal>(), Err(DecimalError::InvalidFormat));
+        }
+    }
+
+    #[test]
+    fn test_comparison() {
+        assert_eq!(Decimal::new(2183, -1), Decimal::new(218300, -3));
+        assert!(Decimal::new(21837, -2) < Decimal::new(2184, -1));
+        assert!(Decimal::new(-1, 0) < Decimal::new(1, -5));
+        // 10^60 does not fit the mantissa of the smaller exponent
+        assert!(Decimal::new(1, 60) > Decimal::new(i128::MAX, -10));
+        assert!(Decimal::new(-1, 60) < Decimal::new(i128::MIN, -10));
+        assert!(Decimal::new(0, 60) < Decimal::new(1, -10));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let sum = Decimal::new(21837, -2)
+            .checked_add(Decimal::new(3, 0))
+            .unwrap();
+        assert_eq!(sum.mantissa, 22137);
+        assert_eq!(sum.exponent, -2);
+        assert_eq!(
+            Decimal::new(1, 3).checked_sub(Decimal::new(1, -1)),
+            Some(Decimal::new(9999, -1))
+        );
+        assert_eq!(
+            Decimal::new(i128::MAX, 0).checked_add(Decimal::new(1, 0)),
+            None
+        );
+        assert_eq!(Decimal::new(1, 60).checked_add(Decimal::new(1, 0)), None);
+        assert_eq!(Decimal::new(21837, -2).to_f64(), 218.37);
+    }
+}
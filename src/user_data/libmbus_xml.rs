@@ -0,0 +1,1104 @@
+//! Writer for the XML printed by rscada libmbus (`mbus-serial-request-data -x`),
+//! with the units and values as libmbus prints them or normalized to the
+//! units of its normalization table.
+//!
+//! The records are written from their parsed quantity, unit and value, this
+//! module only holds the names and quirks of libmbus. libmbus decodes the
+//! data field of a record from its DIF alone, e.g. a BCD is printed as hex and
+//! a 32 bit integer is only a date for VIF 0x6D, so apart from the time points
+//! the values are decoded again with the data field coding of the record.
+
+use core::fmt::{self, Write};
+
+use super::data_information::{AsciiText, Bcd, DataFieldCoding, DataInformationField, DataValue};
+use super::date_time::{transmitted_year, Date, DateTime};
+use super::value_information::{Unit, VIFExtension, ValueInformation};
+use super::variable_user_data::{DataRecord, Exponent, Quantity, VariableUserDataError};
+use super::{DataRecords, FixedDataHeader, Medium, StatusField, UserDataBlock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum XmlFormat {
+    /// Units like `Energy (10 kWh)` and the values as transmitted
+    Plain,
+    /// Units like `Wh` with a quantity and the values scaled to that unit
+    Normalized,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum XmlError {
+    Format,
+    VariableUserDataError(VariableUserDataError),
+    /// The user data block holds no meter data, e.g. an application reset
+    NoMeterData,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlError::Format => write!(f, "Formatting failed"),
+            XmlError::VariableUserDataError(error) => write!(f, "{}", error),
+            XmlError::NoMeterData => write!(f, "No meter data"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for XmlError {}
+
+impl From<fmt::Error> for XmlError {
+    fn from(_: fmt::Error) -> Self {
+        XmlError::Format
+    }
+}
+
+impl From<VariableUserDataError> for XmlError {
+    fn from(error: VariableUserDataError) -> Self {
+        XmlError::VariableUserDataError(error)
+    }
+}
+
+/// Writes the fixed data header and the records of a variable data
+/// structure, the data following the DIF 0x0F or 0x1F is written as the last
+/// record like libmbus does
+pub fn write_xml<W: Write>(
+    out: &mut W,
+    header: &FixedDataHeader,
+    records: &DataRecords,
+    format: XmlFormat,
+) -> fmt::Result {
+    out.write_str("<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<MBusData>\n\n")?;
+    write_header(out, header)?;
+
+    let mut id = 0;
+    for index in 0..records.len() {
+        if let Some(record) = records.get(index) {
+            write_record(out, id, record, format)?;
+            id += 1;
+        }
+    }
+    if let Some(data) = records.manufacturer_data() {
+        let dif = if records.more_records_follow() {
+            0x1F
+        } else {
+            0x0F
+        };
+        write_special_record(out, id, dif, data, format)?;
+    }
+    out.write_str("</MBusData>\n")
+}
+
+/// Writes a variable or fixed data structure, the records of a variable data
+/// structure are parsed with the default options
+pub fn write_user_data_xml<W: Write>(
+    out: &mut W,
+    user_data: &UserDataBlock,
+    format: XmlFormat,
+) -> Result<(), XmlError> {
+    match user_data {
+        UserDataBlock::VariableDataStructure {
+            fixed_data_header,
+            variable_data_block,
+        } => {
+            let records = DataRecords::try_from(*variable_data_block)?;
+            write_xml(out, fixed_data_header, &records, format)?;
+        }
+        UserDataBlock::FixedDataStructure {
+            identification_number,
+            access_number,
+            status,
+            medium_ad_unit,
+            counter1,
+            counter2,
+        } => {
+            // The fixed data structure has no units to normalize, both
+            // formats are the same
+            let [first, second] = medium_ad_unit.to_be_bytes();
+            let medium = ((first & 0xC0) >> 6) | ((second & 0xC0) >> 4);
+            write!(
+                out,
+                "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\n<MBusData>\n\n    \
+                 <SlaveInformation>\n        <Id>{}</Id>\n        <Medium>{}</Medium>\n        \
+                 <AccessNumber>{}</AccessNumber>\n        <Status>{:02X}</Status>\n    \
+                 </SlaveInformation>\n\n",
+                identification_number.number,
+                fixed_medium(medium),
+                access_number,
+                status.bits()
+            )?;
+            let function = if status.contains(StatusField::COUNTER_FIXED_DATE) {
+                "Stored value"
+            } else {
+                "Actual value"
+            };
+            for (id, unit, counter) in [(0, first, counter1), (1, second, counter2)] {
+                write!(
+                    out,
+                    "    <DataRecord id=\"{}\">\n        <Function>{}</Function>\n        \
+                     <Unit>{}</Unit>\n        <Value>{}</Value>\n    </DataRecord>\n\n",
+                    id,
+                    function,
+                    fixed_unit(unit & 0x3F),
+                    counter.count
+                )?;
+            }
+            out.write_str("</MBusData>\n")?;
+        }
+        UserDataBlock::ResetAtApplicationLevel { .. } => return Err(XmlError::NoMeterData),
+    }
+    Ok(())
+}
+
+fn write_header<W: Write>(out: &mut W, header: &FixedDataHeader) -> fmt::Result {
+    write!(
+        out,
+        "    <SlaveInformation>\n        <Id>{}</Id>\n        \
+         <Manufacturer>{}</Manufacturer>\n        <Version>{}</Version>\n        \
+         <ProductName>",
+        header.identification_number.number, header.manufacturer, header.version
+    )?;
+    XmlEscape(out).write_str(header.product_name().unwrap_or(""))?;
+    write!(
+        out,
+        "</ProductName>\n        <Medium>{}</Medium>\n        \
+         <AccessNumber>{}</AccessNumber>\n        <Status>{:02X}</Status>\n        \
+         <Signature>{:04X}</Signature>\n    </SlaveInformation>\n\n",
+        variable_medium(header.medium),
+        header.access_number,
+        header.status.bits(),
+        header.signature
+    )
+}
+
+fn write_record<W: Write>(
+    out: &mut W,
+    id: usize,
+    record: &DataRecord,
+    format: XmlFormat,
+) -> fmt::Result {
+    writeln!(out, "    <DataRecord id=\"{}\">", id)?;
+    // A record without its raw DIF and VIF, e.g. one made up by a
+    // manufacturer decoder, is left empty like libmbus leaves a record whose
+    // unit is not in its normalization table
+    let Some(record) = LibmbusRecord::new(record) else {
+        return out.write_str("    </DataRecord>\n\n");
+    };
+    let normalized = record.normalized();
+    if format == XmlFormat::Normalized && normalized.is_none() {
+        return out.write_str("    </DataRecord>\n\n");
+    }
+    let dif = record.record.raw_data_information[0];
+    write!(
+        out,
+        "        <Function>{}</Function>\n        <StorageNumber>{}</StorageNumber>\n",
+        record_function(dif),
+        record.record.storage_number
+    )?;
+    if record.record.raw_data_information.len() > 1 {
+        write!(
+            out,
+            "        <Tariff>{}</Tariff>\n        <Device>{}</Device>\n",
+            record.record.tariff, record.record.sub_unit
+        )?;
+    }
+
+    match (format, normalized) {
+        (XmlFormat::Normalized, Some((factor, unit, quantity))) => {
+            out.write_str("        <Unit>")?;
+            XmlEscape(out).write_str(unit)?;
+            out.write_str("</Unit>\n        <Quantity>")?;
+            match record.record.plain_text_unit {
+                Some(text) => write!(XmlEscape(out), "{}", text)?,
+                None => XmlEscape(out).write_str(quantity)?,
+            }
+            out.write_str("</Quantity>\n        <Value>")?;
+            record.write_value(&mut XmlEscape(out), Some(factor))?;
+        }
+        _ => {
+            out.write_str("        <Unit>")?;
+            record.write_unit(&mut XmlEscape(out))?;
+            out.write_str("</Unit>\n        <Value>")?;
+            record.write_value(&mut XmlEscape(out), None)?;
+        }
+    }
+    out.write_str("</Value>\n    </DataRecord>\n\n")
+}
+
+// libmbus prints the storage number and the unit of the block following the
+// DIF 0x0F or 0x1F only in the normalized format
+fn write_special_record<W: Write>(
+    out: &mut W,
+    id: usize,
+    dif: u8,
+    data: &[u8],
+    format: XmlFormat,
+) -> fmt::Result {
+    write!(
+        out,
+        "    <DataRecord id=\"{}\">\n        <Function>{}</Function>\n",
+        id,
+        record_function(dif)
+    )?;
+    if format == XmlFormat::Normalized {
+        out.write_str(
+            "        <StorageNumber>0</StorageNumber>\n        <Unit></Unit>\n        \
+             <Quantity></Quantity>\n",
+        )?;
+    }
+    out.write_str("        <Value>")?;
+    write_manufacturer_data(out, data)?;
+    out.write_str("</Value>\n    </DataRecord>\n\n")
+}
+
+/// The function of a record as libmbus prints it, e.g. `Maximum value`
+pub fn record_function(dif: u8) -> &'static str {
+    match dif {
+        0x0F => "Manufacturer specific",
+        0x1F => "More records follow",
+        _ => match dif & 0x30 {
+            0x00 => "Instantaneous value",
+            0x10 => "Maximum value",
+            0x20 => "Minimum value",
+            _ => "Value during error state",
+        },
+    }
+}
+
+/// Writes the unit of a record as libmbus prints it, e.g. `Energy (10 kWh)`
+pub fn write_record_unit<W: Write>(out: &mut W, record: &DataRecord) -> fmt::Result {
+    match LibmbusRecord::new(record) {
+        Some(record) => record.write_unit(out),
+        None => Ok(()),
+    }
+}
+
+/// Writes the value of a record as libmbus prints it
+pub fn write_record_value<W: Write>(out: &mut W, record: &DataRecord) -> fmt::Result {
+    match LibmbusRecord::new(record) {
+        Some(record) => record.write_value(out, None),
+        None => Ok(()),
+    }
+}
+
+/// Writes the data following the DIF 0x0F or 0x1F as libmbus prints it, that
+/// is the bytes in hex separated by spaces
+pub fn write_manufacturer_data<W: Write>(out: &mut W, data: &[u8]) -> fmt::Result {
+    for (index, byte) in data.iter().enumerate() {
+        if index > 0 {
+            out.write_str(" ")?;
+        }
+        write!(out, "{:02X}", byte)?;
+    }
+    Ok(())
+}
+
+/// A record with the parts of its DIF and VIF libmbus looks at
+struct LibmbusRecord<'r, 'a> {
+    record: &'r DataRecord<'a>,
+    value_information: ValueInformation<'a>,
+    /// libmbus ignores the correction factors of the VIFEs, except for a
+    /// plain text unit which has no exponent of its own
+    exponent: Option<isize>,
+    coding: DataFieldCoding,
+}
+
+impl<'r, 'a> LibmbusRecord<'r, 'a> {
+    fn new(record: &'r DataRecord<'a>) -> Option<Self> {
+        let value_information = match record.plain_text_unit {
+            Some(text) => ValueInformation::PlainText(text),
+            None => ValueInformation::try_from(record.raw_value_information).ok()?,
+        };
+        let exponent = match value_information {
+            ValueInformation::PlainText(_) => record.exponent,
+            _ => Exponent::from(&value_information),
+        };
+        let coding = DataInformationField::try_from(record.raw_data_information)
+            .ok()?
+            .data_field_coding;
+        Some(LibmbusRecord {
+            record,
+            value_information,
+            exponent: exponent.inner,
+            coding,
+        })
+    }
+
+    fn prefix(&self) -> Prefix {
+        Prefix(self.exponent.unwrap_or(0) as i32)
+    }
+
+    fn write_unit<W: Write>(&self, out: &mut W) -> fmt::Result {
+        match &self.value_information {
+            ValueInformation::PlainText(text) => {
+                if self.exponent.is_some() {
+                    write!(out, "{} ", self.prefix())?;
+                }
+                write!(out, "{}", text)
+            }
+            ValueInformation::Primary(vif) => self.write_primary_unit(out, *vif),
+            ValueInformation::Extended(extension) => self.write_extended_unit(out, extension),
+            ValueInformation::Any => out.write_str("Unknown (VIF=0x7E)"),
+            ValueInformation::ManufacturerSpecific => out.write_str("Manufacturer specific"),
+        }
+    }
+
+    fn write_primary_unit<W: Write>(&self, out: &mut W, vif: u8) -> fmt::Result {
+        let prefix = self.prefix();
+        let unit = self.record.unit;
+        match self.record.quantity {
+            Quantity::Energy if unit == Unit::WattHour => write!(out, "Energy ({}Wh)", prefix),
+            Quantity::Energy => write!(out, "Energy ({}J)", prefix),
+            Quantity::Volume => write!(out, "Volume ({} m^3)", prefix),
+            Quantity::Mass => write!(out, "Mass ({}kg)", prefix),
+            Quantity::OnTime => write!(out, "On time ({})", duration(unit)),
+            Quantity::OperatingTime => write!(out, "Operating time ({})", duration(unit)),
+            Quantity::Power if unit == Unit::Watt => write!(out, "Power ({}W)", prefix),
+            Quantity::Power => write!(out, "Power ({}J/h)", prefix),
+            Quantity::VolumeFlow => match unit {
+                Unit::CubicMeterPerHour => write!(out, "Volume flow ({} m^3/h)", prefix),
+                Unit::CubicMeterPerMinute => write!(out, "Volume flow ext. ({} m^3/min)", prefix),
+                _ => write!(out, "Volume flow ext. ({} m^3/s)", prefix),
+            },
+            Quantity::MassFlow => write!(out, "Mass flow ({} kg/h)", prefix),
+            Quantity::FlowTemperature => write!(out, "Flow temperature ({}deg C)", prefix),
+            Quantity::ReturnTemperature => write!(out, "Return temperature ({}deg C)", prefix),
+            Quantity::TemperatureDifference => {
+                write!(out, "Temperature Difference ({} deg C)", prefix)
+            }
+            Quantity::ExternalTemperature => {
+                write!(out, "External temperature ({} deg C)", prefix)
+            }
+            Quantity::Pressure => write!(out, "Pressure ({} bar)", prefix),
+            Quantity::TimePoint if unit == Unit::DayMonthYear => out.write_str("Time Point (date)"),
+            Quantity::TimePoint => out.write_str("Time Point (time & date)"),
+            Quantity::HCA => out.write_str("Units for H.C.A."),
+            Quantity::AveragingDuration => {
+                write!(out, "Averaging Duration ({})", duration(unit))
+            }
+            Quantity::ActualityDuration => {
+                write!(out, "Actuality Duration ({})", duration(unit))
+            }
+            Quantity::IdentificationNumber => out.write_str("Fabrication number"),
+            _ => write!(out, "Unknown (VIF=0x{:02X})", vif & 0x7F),
+        }
+    }
+
+    // The names of the VIF extensions following 0xFD and 0xFB, libmbus names
+    // the durations of the last cumulation and of the battery in seconds to
+    // days although they count hours to years
+    fn write_extended_unit<W: Write>(&self, out: &mut W, extension: &VIFExtension) -> fmt::Result {
+        let prefix = self.prefix();
+        let text = match *extension {
+            VIFExtension::CreditOfCurrencyUnits(_) => {
+                return write!(
+                    out,
+                    "Credit of {}the nominal local legal currency units",
+                    prefix
+                )
+            }
+            VIFExtension::DebitOfCurrencyUnits(_) => {
+                return write!(
+                    out,
+                    "Debit of {}the nominal local legal currency units",
+                    prefix
+                )
+            }
+            VIFExtension::AccessNumber => "Access Number (transmission count)",
+            VIFExtension::Medium => "Medium (as in fixed header)",
+            VIFExtension::Manufacturer => "Manufacturer (as in fixed header)",
+            VIFExtension::ParameterSetIdentification => "Parameter set identification",
+            VIFExtension::ModelVersion => "Model / Version",
+            VIFExtension::HardwareVersion => "Hardware version",
+            VIFExtension::FirmwareVersion => "Firmware version",
+            VIFExtension::SoftwareVersion => "Software version",
+            VIFExtension::CustomerLocation => "Customer location",
+            VIFExtension::Customer => "Customer",
+            VIFExtension::AccessCodeUser => "Access Code User",
+            VIFExtension::AccessCodeOperator => "Access Code Operator",
+            VIFExtension::AccessCodeSystemOperator => "Access Code System Operator",
+            VIFExtension::AccessCodeDeveloper => "Access Code Developer",
+            VIFExtension::Password => "Password",
+            VIFExtension::ErrorFlags => "Error flags",
+            VIFExtension::ErrorMask => "Error mask",
+            VIFExtension::SecurityKey => "Reserved",
+            VIFExtension::DigitalOutput => "Digital output (binary)",
+            VIFExtension::DigitalInput => "Digital input (binary)",
+            VIFExtension::BaudRate => "Baudrate",
+            VIFExtension::ResponseDelayTime => "Response delay time",
+            VIFExtension::Retry => "Retry",
+            VIFExtension::FirstStorage => "First storage # for cyclic storage",
+            VIFExtension::LastStorage => "Last storage # for cyclic storage",
+            VIFExtension::SizeOfStorageBlock => "Size of storage block",
+            VIFExtension::StorageIntervalSecondsToDays(nn) => {
+                return write!(out, "Storage interval ({})", DURATIONS[nn as usize])
+            }
+            VIFExtension::StorageIntervalMonths => "Storage interval (months)",
+            VIFExtension::StorageIntervalYears => "Storage interval (years)",
+            VIFExtension::DurationSinceLastReadout(nn) => {
+                return write!(
+                    out,
+                    "Duration since last readout ({})",
+                    DURATIONS[nn as usize]
+                )
+            }
+            VIFExtension::StartOfTariff => "Start (date/time) of tariff",
+            VIFExtension::DurationOfTariff(nn) => {
+                return write!(out, "Duration of tariff ({})", DURATIONS[nn as usize])
+            }
+            VIFExtension::PeriodOfTariff(nn) => {
+                return write!(out, "Period of tariff ({})", DURATIONS[nn as usize])
+            }
+            VIFExtension::PeriodOfTarrifMonths => "Period of tariff (months)",
+            VIFExtension::PeriodOfTTariffYears => "Period of tariff (years)",
+            VIFExtension::Dimensionless => "dimensionless / no VIF",
+            VIFExtension::Volts(_) => return write!(out, "{} V", prefix),
+            VIFExtension::Ampere(_) => return write!(out, "{} A", prefix),
+            VIFExtension::ResetCounter => "Reset counter",
+            VIFExtension::CumulationCounter => "Cumulation counter",
+            VIFExtension::ControlSignal => "Control signal",
+            VIFExtension::DayOfWeek => "Day of week",
+            VIFExtension::WeekNumber => "Week number",
+            VIFExtension::TimePointOfDay => "Time point of day change",
+            VIFExtension::StateOfParameterActivation => "State of parameter activation",
+            VIFExtension::SpecialSupervision => "Special supplier information",
+            VIFExtension::DurationSinceLastCumulation(nn) => {
+                return write!(
+                    out,
+                    "Duration since last cumulation ({})",
+                    DURATIONS[nn as usize]
+                )
+            }
+            VIFExtension::OperatingTimeBattery(nn) => {
+                return write!(out, "Operating time battery ({})", DURATIONS[nn as usize])
+            }
+            VIFExtension::DateAndTimeOfBatteryChange => "Date and time of battery change",
+            VIFExtension::RemoteControl
+            | VIFExtension::OperatorSpecificData
+            | VIFExtension::TimePointSecond
+            | VIFExtension::DataContainerWirelessMBus
+            | VIFExtension::PeriodOfNominalDataTransmissions(_) => {
+                return self.write_unrecognized_extension(out)
+            }
+            // 0xFD 0x23 is the only reserved code of the first table libmbus
+            // does not know at all
+            VIFExtension::Reserved if self.is_first_table() && self.vife() == 0x23 => {
+                return self.write_unrecognized_extension(out)
+            }
+            VIFExtension::RFLevel
+            | VIFExtension::DaylightSaving
+            | VIFExtension::ListeningWindowManagement
+            | VIFExtension::RemainingBatteryLifeTime
+            | VIFExtension::NumberOfTimesMeterStopped
+            | VIFExtension::DataContainerManufacturerSpecific
+            | VIFExtension::Reserved => "Reserved VIF extension",
+
+            VIFExtension::EnergyMWh(n) => ["Energy (0.1 MWh)", "Energy (1 MWh)"][n as usize],
+            VIFExtension::EnergyGJ(n) => ["Energy (0.1 GJ)", "Energy (1 GJ)"][n as usize],
+            VIFExtension::VolumeM3(n) => ["Volume (100 m^3)", "Volume (1000 m^3)"][n as usize],
+            VIFExtension::MassTons(n) => ["Mass (100 t)", "Mass (1000 t)"][n as usize],
+            VIFExtension::VolumeFeet3Tenth => "Volume (0.1 feet^3)",
+            VIFExtension::VolumeAmericanGallonTenth => "Volume (0.1 american gallon)",
+            VIFExtension::VolumeAmericanGallon => "Volume (american gallon)",
+            VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth => {
+                "Volume flow (0.001 american gallon/min)"
+            }
+            VIFExtension::VolumeFlowAmericanGallonPerMinute => "Volume flow (american gallon/min)",
+            VIFExtension::VolumeFlowAmericanGallonPerHour => "Volume flow (american gallon/h)",
+            VIFExtension::PowerMW(n) => ["Power (0.1 MW)", "Power (1 MW)"][n as usize],
+            VIFExtension::PowerGJH(n) => ["Power (0.1 GJ/h)", "Power (1 GJ/h)"][n as usize],
+            VIFExtension::FlowTemperature(_) => {
+                return write!(out, "Flow temperature ({}deg F)", prefix)
+            }
+            VIFExtension::ReturnTemperature(_) => {
+                return write!(out, "Return temperature ({}deg F)", prefix)
+            }
+            VIFExtension::TemperatureDifference(_) => {
+                return write!(out, "Temperature Difference ({} deg F)", prefix)
+            }
+            VIFExtension::ExternalTemperature(_) => {
+                return write!(out, "External temperature ({} deg F)", prefix)
+            }
+            VIFExtension::ColdWarmTemperatureLimitFarenheit(_) => {
+                return write!(out, "Cold / Warm Temperature Limit ({} deg F)", prefix)
+            }
+            VIFExtension::ColdWarmTemperatureLimitCelsius(_) => {
+                return write!(out, "Cold / Warm Temperature Limit ({} deg C)", prefix)
+            }
+            VIFExtension::CumulativeCountMaxPower(_) => {
+                return write!(out, "Cumul count max power ({}W)", prefix)
+            }
+            VIFExtension::ReactiveEnergy(_)
+            | VIFExtension::ApparentEnergy(_)
+            | VIFExtension::EnergyMCal(_)
+            | VIFExtension::ReactivePower(_)
+            | VIFExtension::RelativeHumidity(_)
+            | VIFExtension::VolumeFeet3
+            | VIFExtension::PhaseVoltageToVoltage
+            | VIFExtension::PhaseVoltageToCurrent
+            | VIFExtension::Frequency(_)
+            | VIFExtension::ApparentPower(_) => "Reserved VIF extension",
+        };
+        out.write_str(text)
+    }
+
+    fn write_unrecognized_extension<W: Write>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "Unrecognized VIF extension: 0x{:02x}", self.vife())
+    }
+
+    // Whether the VIF is 0xFD, the reserved codes of both tables of VIF
+    // extensions share the same variant
+    fn is_first_table(&self) -> bool {
+        self.record.raw_value_information.first() == Some(&0xFD)
+    }
+
+    fn vife(&self) -> u8 {
+        self.record
+            .raw_value_information
+            .get(1)
+            .map_or(0, |vife| vife & 0x7F)
+    }
+
+    /// The factor to the normalized unit, the unit and the quantity as in the
+    /// normalization table of libmbus, `None` for the units missing in it
+    fn normalized(&self) -> Option<(f64, &'static str, &'static str)> {
+        let exponent = self.exponent.unwrap_or(0) as i32;
+        let factor = power_of_ten(exponent);
+        let unit = self.record.unit;
+        let seconds = seconds(unit);
+        let extension = match &self.value_information {
+            ValueInformation::PlainText(_) => {
+                return Some((self.exponent.map_or(1.0, |_| factor), "-", ""))
+            }
+            ValueInformation::Any => return Some((1.0, "", "Any VIF")),
+            ValueInformation::ManufacturerSpecific => {
+                return Some((1.0, "", "Manufacturer specific"))
+            }
+            ValueInformation::Primary(vif) => {
+                return Some(match self.record.quantity {
+                    Quantity::Energy if unit == Unit::WattHour => (factor, "Wh", "Energy"),
+                    Quantity::Energy => (factor, "J", "Energy"),
+                    Quantity::Volume => (factor, "m^3", "Volume"),
+                    Quantity::Mass => (factor, "kg", "Mass"),
+                    Quantity::OnTime => (seconds, "s", "On time"),
+                    Quantity::OperatingTime => (seconds, "s", "Operating time"),
+                    Quantity::Power if unit == Unit::Watt => (factor, "W", "Power"),
+                    Quantity::Power => (factor, "J/h", "Power"),
+                    Quantity::VolumeFlow => match unit {
+                        Unit::CubicMeterPerHour => (factor, "m^3/h", "Volume flow"),
+                        Unit::CubicMeterPerMinute => (factor, "m^3/min", "Volume flow"),
+                        _ => (factor, "m^3/s", "Volume flow"),
+                    },
+                    Quantity::MassFlow => (factor, "kg/h", "Mass flow"),
+                    Quantity::FlowTemperature => (factor, "°C", "Flow temperature"),
+                    Quantity::ReturnTemperature => (factor, "°C", "Return temperature"),
+                    Quantity::TemperatureDifference => (factor, "K", "Temperature difference"),
+                    Quantity::ExternalTemperature => (factor, "°C", "External temperature"),
+                    Quantity::Pressure => (factor, "bar", "Pressure"),
+                    Quantity::TimePoint if unit == Unit::DayMonthYear => {
+                        (1.0, "-", "Time point (date)")
+                    }
+                    Quantity::TimePoint => (1.0, "-", "Time point (date & time)"),
+                    Quantity::HCA => (1.0, "Units for H.C.A.", "H.C.A."),
+                    Quantity::AveragingDuration => (seconds, "s", "Averaging Duration"),
+                    Quantity::ActualityDuration => (seconds, "s", "Actuality Duration"),
+                    Quantity::IdentificationNumber => (1.0, "", "Fabrication No"),
+                    Quantity::EnhancedIdentification => (1.0, "", "(Enhanced) Identification"),
+                    Quantity::BusAddress => (1.0, "", "Bus Address"),
+                    _ if vif & 0x7F == 0x6F => (0.0, "Reserved", "Reserved"),
+                    _ => return None,
+                })
+            }
+            ValueInformation::Extended(extension) => extension,
+        };
+        Some(match *extension {
+            VIFExtension::CreditOfCurrencyUnits(_) => (factor, "Currency units", "Credit"),
+            VIFExtension::DebitOfCurrencyUnits(_) => (factor, "Currency units", "Debit"),
+            VIFExtension::AccessNumber => (1.0, "", "Access Number (transmission count)"),
+            VIFExtension::Medium => (1.0, "", "Medium"),
+            VIFExtension::Manufacturer => (1.0, "", "Manufacturer"),
+            VIFExtension::ParameterSetIdentification => (1.0, "", "Parameter set identification"),
+            VIFExtension::ModelVersion => (1.0, "", "Model / Version"),
+            VIFExtension::HardwareVersion => (1.0, "", "Hardware version"),
+            VIFExtension::FirmwareVersion => (1.0, "", "Firmware version"),
+            VIFExtension::SoftwareVersion => (1.0, "", "Software version"),
+            VIFExtension::CustomerLocation => (1.0, "", "Customer location"),
+            VIFExtension::Customer => (1.0, "", "Customer"),
+            VIFExtension::AccessCodeUser => (1.0, "", "Access Code User"),
+            VIFExtension::AccessCodeOperator => (1.0, "", "Access Code Operator"),
+            VIFExtension::AccessCodeSystemOperator => (1.0, "", "Access Code System Operator"),
+            VIFExtension::AccessCodeDeveloper => (1.0, "", "Access Code Developer"),
+            VIFExtension::Password => (1.0, "", "Password"),
+            VIFExtension::ErrorFlags => (1.0, "", "Error flags"),
+            VIFExtension::ErrorMask => (1.0, "", "Error mask"),
+            VIFExtension::DigitalOutput => (1.0, "", "Digital Output"),
+            VIFExtension::DigitalInput => (1.0, "", "Digital Input"),
+            VIFExtension::BaudRate => (1.0, "Baud", "Baudrate"),
+            VIFExtension::ResponseDelayTime => (1.0, "Bittimes", "Response delay time"),
+            VIFExtension::Retry => (1.0, "", "Retry"),
+            VIFExtension::FirstStorage => (1.0, "", "First storage # for cyclic storage"),
+            VIFExtension::LastStorage => (1.0, "", "Last storage # for cyclic storage"),
+            VIFExtension::SizeOfStorageBlock => (1.0, "", "Size of storage block"),
+            VIFExtension::StorageIntervalSecondsToDays(_)
+            | VIFExtension::StorageIntervalMonths
+            | VIFExtension::StorageIntervalYears => (seconds, "s", "Storage interval"),
+            VIFExtension::DurationSinceLastReadout(_) => {
+                (seconds, "s", "Duration since last readout")
+            }
+            VIFExtension::StartOfTariff => (1.0, "", "Start (date/time) of tariff"),
+            VIFExtension::DurationOfTariff(_) => (seconds, "s", "Duration of tariff"),
+            VIFExtension::PeriodOfTariff(_)
+            | VIFExtension::PeriodOfTarrifMonths
+            | VIFExtension::PeriodOfTTariffYears => (seconds, "s", "Period of tariff"),
+            VIFExtension::Dimensionless => (1.0, "", "Dimensionless"),
+            VIFExtension::Volts(_) => (factor, "V", "Voltage"),
+            VIFExtension::Ampere(_) => (factor, "A", "Current"),
+            VIFExtension::ResetCounter => (1.0, "", "Reset counter"),
+            VIFExtension::CumulationCounter => (1.0, "", "Cumulation counter"),
+            VIFExtension::ControlSignal => (1.0, "", "Control signal"),
+            VIFExtension::DayOfWeek => (1.0, "", "Day of week"),
+            VIFExtension::WeekNumber => (1.0, "", "Week number"),
+            VIFExtension::TimePointOfDay => (1.0, "", "Time point of day change"),
+            VIFExtension::StateOfParameterActivation => (1.0, "", "State of parameter activation"),
+            VIFExtension::SpecialSupervision => (1.0, "", "Special supplier information"),
+            VIFExtension::DurationSinceLastCumulation(_) => {
+                (seconds, "s", "Duration since last cumulation")
+            }
+            VIFExtension::OperatingTimeBattery(_) => (seconds, "s", "Operating time battery"),
+            VIFExtension::DateAndTimeOfBatteryChange => {
+                (1.0, "", "Date and time of battery change")
+            }
+            VIFExtension::SecurityKey
+            | VIFExtension::RemoteControl
+            | VIFExtension::OperatorSpecificData
+            | VIFExtension::TimePointSecond
+            | VIFExtension::DataContainerWirelessMBus
+            | VIFExtension::PeriodOfNominalDataTransmissions(_)
+            | VIFExtension::RFLevel
+            | VIFExtension::DaylightSaving
+            | VIFExtension::ListeningWindowManagement
+            | VIFExtension::RemainingBatteryLifeTime
+            | VIFExtension::NumberOfTimesMeterStopped
+            | VIFExtension::DataContainerManufacturerSpecific => (1.0, "Reserved", "Reserved"),
+            VIFExtension::Reserved if self.is_first_table() => (1.0, "Reserved", "Reserved"),
+
+            // libmbus normalizes the large units of the second table to the
+            // units of the primary VIFs
+            VIFExtension::EnergyMWh(_) => (power_of_ten(exponent + 6), "Wh", "Energy"),
+            VIFExtension::EnergyGJ(_) => (power_of_ten(exponent + 9), "J", "Energy"),
+            VIFExtension::VolumeM3(_) => (factor, "m^3", "Volume"),
+            VIFExtension::MassTons(_) => (power_of_ten(exponent + 3), "kg", "Mass"),
+            VIFExtension::VolumeFeet3Tenth => (factor, "feet^3", "Volume"),
+            VIFExtension::VolumeAmericanGallonTenth | VIFExtension::VolumeAmericanGallon => {
+                (factor, "American gallon", "Volume")
+            }
+            VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth
+            | VIFExtension::VolumeFlowAmericanGallonPerMinute => {
+                (factor, "American gallon/min", "Volume flow")
+            }
+            VIFExtension::VolumeFlowAmericanGallonPerHour => {
+                (factor, "American gallon/h", "Volume flow")
+            }
+            VIFExtension::PowerMW(_) => (power_of_ten(exponent + 6), "W", "Power"),
+            VIFExtension::PowerGJH(_) => (power_of_ten(exponent + 9), "J/h", "Power"),
+            VIFExtension::FlowTemperature(_) => (factor, "°F", "Flow temperature"),
+            VIFExtension::ReturnTemperature(_) => (factor, "°F", "Return temperature"),
+            VIFExtension::TemperatureDifference(_) => (factor, "°F", "Temperature difference"),
+            VIFExtension::ExternalTemperature(_) => (factor, "°F", "External temperature"),
+            VIFExtension::ColdWarmTemperatureLimitFarenheit(_) => {
+                (factor, "°F", "Cold / Warm Temperature Limit")
+            }
+            VIFExtension::ColdWarmTemperatureLimitCelsius(_) => {
+                (factor, "°C", "Cold / Warm Temperature Limit")
+            }
+            VIFExtension::CumulativeCountMaxPower(_) => (factor, "W", "Cumul count max power"),
+            VIFExtension::Reserved
+            | VIFExtension::ReactiveEnergy(_)
+            | VIFExtension::ApparentEnergy(_)
+            | VIFExtension::EnergyMCal(_)
+            | VIFExtension::ReactivePower(_)
+            | VIFExtension::RelativeHumidity(_)
+            | VIFExtension::VolumeFeet3
+            | VIFExtension::PhaseVoltageToVoltage
+            | VIFExtension::PhaseVoltageToCurrent
+            | VIFExtension::Frequency(_)
+            | VIFExtension::ApparentPower(_) => return None,
+        })
+    }
+
+    /// Writes the value as transmitted or, with the factor of the normalized
+    /// unit, scaled to that unit
+    fn write_value<W: Write>(&self, out: &mut W, factor: Option<f64>) -> fmt::Result {
+        let data = self.record.raw_data;
+        match (self.record.value, self.coding) {
+            // Only the VIF 0x6C is a date for libmbus
+            (DataValue::Date(date), _) if self.record.unit == Unit::DayMonthYear => {
+                return self.write_date(out, &date)
+            }
+            (
+                DataValue::DateTime(date_time),
+                DataFieldCoding::Integer32Bit | DataFieldCoding::Integer48Bit,
+            ) if self.record.unit == Unit::TimePoint => {
+                self.write_date_time(out, &date_time)?;
+                if factor.is_some() {
+                    out.write_str("Z")?;
+                }
+                return Ok(());
+            }
+            // libmbus prints every variable length data field as text
+            (_, DataFieldCoding::VariableLength) => {
+                let bytes = data.get(1..).unwrap_or(&[]);
+                return write!(out, "{}", AsciiText { bytes });
+            }
+            (_, DataFieldCoding::SpecialFunctions(_)) => return write_manufacturer_data(out, data),
+            _ => {}
+        }
+        let Ok(value) = self.coding.extract_from_bytes(data) else {
+            return Ok(());
+        };
+        match (value.data, factor) {
+            (DataValue::Signed(value), None) => write!(out, "{}", value),
+            (DataValue::Signed(value), Some(factor)) => {
+                write!(out, "{:.6}", value as f64 * factor)
+            }
+            (DataValue::Real(value), factor) => {
+                write!(out, "{:.6}", value as f64 * factor.unwrap_or(1.0))
+            }
+            (DataValue::Bcd(_), None) => write_bcd_digits(out, data),
+            (DataValue::Bcd(bcd), Some(factor)) => {
+                write!(out, "{:.6}", libmbus_bcd(&bcd, data) as f64 * factor)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The prefix libmbus puts in front of a unit for 10^exponent
+struct Prefix(i32);
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => Ok(()),
+            -3 => f.write_str("m"),
+            -6 => f.write_str("my"),
+            1 => f.write_str("10 "),
+            2 => f.write_str("100 "),
+            3 => f.write_str("k"),
+            4 => f.write_str("10 k"),
+            5 => f.write_str("100 k"),
+            6 => f.write_str("M"),
+            9 => f.write_str("G"),
+            exponent => write!(f, "1e{} ", exponent),
+        }
+    }
+}
+
+const DURATIONS: [&str; 4] = ["seconds", "minutes", "hours", "days"];
+
+fn duration(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Seconds => DURATIONS[0],
+        Unit::Minutes => DURATIONS[1],
+        Unit::Hours => DURATIONS[2],
+        _ => DURATIONS[3],
+    }
+}
+
+const POWERS_OF_TEN: [f64; 22] = [
+    1e-12, 1e-11, 1e-10, 1e-9, 1e-8, 1e-7, 1e-6, 1e-5, 1e-4, 1e-3, 1e-2, 1e-1, 1e0, 1e1, 1e2, 1e3,
+    1e4, 1e5, 1e6, 1e7, 1e8, 1e9,
+];
+
+// The factors are literals in libmbus, so they are taken from a table instead
+// of being computed to get the same results
+fn power_of_ten(exponent: i32) -> f64 {
+    POWERS_OF_TEN[(exponent + 12).clamp(0, 21) as usize]
+}
+
+const MONTH: f64 = 2629743.83;
+const YEAR: f64 = 31556926.0;
+
+// The factor of a duration to seconds, with the length of a month and a year
+// libmbus uses
+fn seconds(unit: Unit) -> f64 {
+    match unit {
+        Unit::Minutes => 60.0,
+        Unit::Hours => 3600.0,
+        Unit::Days => 86400.0,
+        Unit::Months => MONTH,
+        Unit::Years => YEAR,
+        _ => 1.0,
+    }
+}
+
+// libmbus adds the year as transmitted to 2000 and ignores the hundreds of
+// years, so the years 100-127 become 2100-2127 where the parser cannot tell
+// them from 2000-2027
+fn libmbus_year(data: &[u8], coding: DataFieldCoding) -> u16 {
+    let offset = match coding {
+        DataFieldCoding::Integer16Bit => 0,
+        DataFieldCoding::Integer32Bit => 2,
+        _ => 3,
+    };
+    match data.get(offset..offset + 2) {
+        Some(&[day, month]) => 2000 + transmitted_year([day, month]) as u16,
+        _ => 2000,
+    }
+}
+
+impl LibmbusRecord<'_, '_> {
+    fn write_date<W: Write>(&self, out: &mut W, date: &Date) -> fmt::Result {
+        write!(
+            out,
+            "{:04}-{:02}-{:02}",
+            libmbus_year(self.record.raw_data, self.coding),
+            date.month,
+            date.day
+        )
+    }
+
+    // libmbus leaves a time point of type F zeroed if its time invalid bit is
+    // set
+    fn write_date_time<W: Write>(&self, out: &mut W, date_time: &DateTime) -> fmt::Result {
+        if date_time.invalid && self.coding == DataFieldCoding::Integer32Bit {
+            return out.write_str("1900-01-00T00:00:00");
+        }
+        self.write_date(out, &date_time.date)?;
+        let time = &date_time.time;
+        write!(
+            out,
+            "T{:02}:{:02}:{:02}",
+            time.hour, time.minute, time.second
+        )
+    }
+}
+
+// libmbus prints a BCD as it prints a hex number, so the digits A-F are
+// printed as they are
+fn write_bcd_digits<W: Write>(out: &mut W, data: &[u8]) -> fmt::Result {
+    let mut digits = data.iter().rev().skip_while(|&&byte| byte == 0);
+    match digits.next() {
+        Some(first) => write!(out, "{:X}", first)?,
+        None => return out.write_str("0"),
+    }
+    for byte in digits {
+        write!(out, "{:02X}", byte)?;
+    }
+    Ok(())
+}
+
+// libmbus adds the digits A-F of the least significant nibble of each byte
+// as 10-15 but skips them in the most significant one, the parser counts both
+// as zero and marks them as invalid
+fn libmbus_bcd(bcd: &Bcd, data: &[u8]) -> i64 {
+    let mut value = bcd.value as i64;
+    for (index, byte) in data.iter().enumerate() {
+        if bcd.invalid_digits & (1 << (2 * index)) != 0 {
+            value += (byte & 0x0F) as i64 * 10i64.pow(2 * index as u32);
+        }
+    }
+    if bcd.negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Escapes the characters with a special meaning in XML
+struct XmlEscape<'a, W>(&'a mut W);
+
+impl<W: Write> Write for XmlEscape<'_, W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for c in text.chars() {
+            self.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        match c {
+            '&' => self.0.write_str("&amp;"),
+            '<' => self.0.write_str("&lt;"),
+            '>' => self.0.write_str("&gt;"),
+            '"' => self.0.write_str("&quot;"),
+            '\'' => self.0.write_str("&apos;"),
+            c => self.0.write_char(c),
+        }
+    }
+}
+
+fn variable_medium(medium: Medium) -> &'static str {
+    match medium {
+        Medium::Other => "Other",
+        Medium::Oil => "Oil",
+        Medium::Electricity => "Electricity",
+        Medium::Gas => "Gas",
+        Medium::Heat => "Heat: Outlet",
+        Medium::Steam => "Steam",
+        Medium::HotWater => "Warm water (30-90°C)",
+        Medium::Water => "Water",
+        Medium::HeatCostAllocator => "Heat Cost Allocator",
+        Medium::Reserved => "Breaker: Electricity",
+        Medium::GasMode2 => "Cooling load meter: Outlet",
+        Medium::HeatMode2 => "Cooling load meter: Inlet",
+        Medium::HotWaterMode2 => "Heat: Inlet",
+        Medium::WaterMode2 => "Heat / Cooling load meter",
+        Medium::HeatCostAllocator2 => "Bus/System",
+        Medium::ReservedMode2 => "Unknown Medium",
+        Medium::Unknown => "Unknown Medium",
+        Medium::ColdWater => "Cold water",
+        Medium::DualWater => "Dual water",
+        Medium::Pressure => "Pressure",
+        Medium::ADConverter => "A/D Converter",
+    }
+}
+
+fn fixed_medium(medium: u8) -> &'static str {
+    match medium {
+        0x00 => "Other",
+        0x01 => "Oil",
+        0x02 => "Electricity",
+        0x03 => "Gas",
+        0x04 => "Heat",
+        0x05 => "Steam",
+        0x06 => "Hot Water",
+        0x07 => "Water",
+        0x08 => "H.C.A.",
+        0x0A => "Gas Mode 2",
+        0x0B => "Heat Mode 2",
+        0x0C => "Hot Water Mode 2",
+        0x0D => "Water Mode 2",
+        0x0E => "H.C.A. Mode 2",
+        _ => "Reserved",
+    }
+}
+
+fn fixed_unit(unit: u8) -> &'static str {
+    const UNITS: [&str; 64] = [
+        "h,m,s",
+        "D,M,Y",
+        "Wh",
+        "10 Wh",
+        "100 Wh",
+        "kWh",
+        "10 kWh",
+        "100 kWh",
+        "MWh",
+        "10 MWh",
+        "100 MWh",
+        "kJ",
+        "10 kJ",
+        "100 kJ",
+        "MJ",
+        "10 MJ",
+        "100 MJ",
+        "GJ",
+        "10 GJ",
+        "100 GJ",
+        "W",
+        "10 W",
+        "100 W",
+        "kW",
+        "10 kW",
+        "100 kW",
+        "MW",
+        "10 MW",
+        "100 MW",
+        "kJ/h",
+        "10 kJ/h",
+        "100 kJ/h",
+        "MJ/h",
+        "10 MJ/h",
+        "100 MJ/h",
+        "GJ/h",
+        "10 GJ/h",
+        "100 GJ/h",
+        "ml",
+        "10 ml",
+        "100 ml",
+        "l",
+        "10 l",
+        "100 l",
+        "m^3",
+        "10 m^3",
+        "100 m^3",
+        "ml/h",
+        "10 ml/h",
+        "100 ml/h",
+        "l/h",
+        "10 l/h",
+        "100 l/h",
+        "m^3/h",
+        "10 m^3/h",
+        "100 m^3/h",
+        "1e-3 °C",
+        "units for HCA",
+        "reserved",
+        "reserved",
+        "reserved",
+        "reserved",
+        "reserved but historic",
+        "without units",
+    ];
+    UNITS[(unit & 0x3F) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use core::fmt::Write;
+    use std::string::{String, ToString};
+
+    use super::{libmbus_bcd, write_record_value, Prefix, XmlEscape};
+    use crate::user_data::data_information::{AsciiText, Bcd};
+    use crate::user_data::variable_user_data::DataRecord;
+    use crate::user_data::ParserOptions;
+
+    #[test]
+    fn test_prefix() {
+        assert_eq!(Prefix(0).to_string(), "");
+        assert_eq!(Prefix(-3).to_string(), "m");
+        assert_eq!(Prefix(-6).to_string(), "my");
+        assert_eq!(Prefix(4).to_string(), "10 k");
+        assert_eq!(Prefix(-2).to_string(), "1e-2 ");
+        assert_eq!(Prefix(7).to_string(), "1e7 ");
+    }
+
+    #[test]
+    fn test_bcd_with_invalid_digits() {
+        let bcd = |data: &[u8]| libmbus_bcd(&Bcd::from_le_bytes(data), data);
+        assert_eq!(bcd(&[0x37, 0x18, 0x02, 0x00]), 21837);
+        assert_eq!(bcd(&[0x12, 0xF0]), -12);
+        /* the upper nibbles A-F count as zero, the lower ones as 10-15 */
+        assert_eq!(bcd(&[0xBD, 0xEB, 0xDD, 0xDD]), 13131113);
+    }
+
+    #[test]
+    fn test_time_points() {
+        let value = |data: &[u8]| {
+            let record = DataRecord::parse(data, &ParserOptions::default()).unwrap();
+            let mut text = String::new();
+            write_record_value(&mut text, &record).unwrap();
+            text
+        };
+        assert_eq!(
+            value(&[0x04, 0x6D, 0x09, 0x0D, 0xCD, 0x13]),
+            "2014-03-13T13:09:00"
+        );
+        /* invalid date and time */
+        assert_eq!(
+            value(&[0x04, 0x6D, 0x89, 0x0D, 0xCD, 0x13]),
+            "1900-01-00T00:00:00"
+        );
+        /* a date is only a date for the VIF 0x6C */
+        assert_eq!(value(&[0x02, 0x6C, 0xCD, 0x13]), "2014-03-13");
+        assert_eq!(value(&[0x04, 0x6C, 0x09, 0x0D, 0xCD, 0x13]), "332205321");
+    }
+
+    #[test]
+    fn test_escape() {
+        let mut text = String::new();
+        write!(XmlEscape(&mut text), "{}", AsciiText { bytes: b">B&A<" }).unwrap();
+        assert_eq!(text, "&lt;A&amp;B&gt;");
+    }
+}
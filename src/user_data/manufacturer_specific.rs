@@ -0,0 +1,314 @@
+//! Hooks to decode the manufacturer specific parts of a telegram, that is
+//! records with a manufacturer specific VIF (0x7F/0xFF) and the data which
+//! follows the DIF 0x0F or 0x1F.
+//!
+//! Decoders are registered in a static slice, which is handed to the parser
+//! through [`ParserOptions`](super::ParserOptions), so no allocation is needed:
+//!
+//! ```
+//! use m_bus_parser::user_data::manufacturer_specific::{
+//!     ManufacturerDecoder, ManufacturerDecoderEntry,
+//! };
+//! use m_bus_parser::user_data::{DataRecords, FixedDataHeader, ManufacturerCode, ParserOptions};
+//!
+//! struct Decoder;
+//!
+//! impl ManufacturerDecoder for Decoder {
+//!     fn decode_manufacturer_data<'a>(
+//!         &self,
+//!         _header: &FixedDataHeader,
+//!         _data: &'a [u8],
+//!         _records: &mut DataRecords<'a>,
+//!     ) {
+//!         // push the decoded records
+//!     }
+//! }
+//!
+//! static DECODERS: &[ManufacturerDecoderEntry] = &[ManufacturerDecoderEntry::new(
+//!     ManufacturerCode { code: ['A', 'C', 'W'] },
+//!     &Decoder,
+//! )
+//! .with_version(0x0E)];
+//!
+//! let options = ParserOptions {
+//!     manufacturer_decoders: DECODERS,
+//!     ..Default::default()
+//! };
+//! ```
+
+use core::fmt;
+
+use super::variable_user_data::DataRecord;
+use super::{DataRecords, FixedDataHeader, ManufacturerCode, Medium};
+
+/// Decodes manufacturer specific data into additional records, which are
+/// appended to the records of the telegram. Records which do not fit anymore
+/// are dropped and reported by [`DataRecords::records_dropped`].
+pub trait ManufacturerDecoder: Sync {
+    /// Called for every record with a manufacturer specific VIF, after the
+    /// record itself has been added
+    fn decode_record<'a>(
+        &self,
+        _header: &FixedDataHeader,
+        _record: &DataRecord<'a>,
+        _records: &mut DataRecords<'a>,
+    ) {
+    }
+
+    /// Called with the data following the DIF 0x0F or 0x1F
+    fn decode_manufacturer_data<'a>(
+        &self,
+        _header: &FixedDataHeader,
+        _data: &'a [u8],
+        _records: &mut DataRecords<'a>,
+    ) {
+    }
+}
+
+/// Registers a decoder for the telegrams of a manufacturer, optionally
+/// restricted to a version and medium
+#[derive(Clone, Copy)]
+pub struct ManufacturerDecoderEntry {
+    pub manufacturer: ManufacturerCode,
+    pub version: Option<u8>,
+    pub medium: Option<Medium>,
+    pub decoder: &'static dyn ManufacturerDecoder,
+}
+
+impl ManufacturerDecoderEntry {
+    pub const fn new(
+        manufacturer: ManufacturerCode,
+        decoder: &'static dyn ManufacturerDecoder,
+    ) -> Self {
+        ManufacturerDecoderEntry {
+            manufacturer,
+            version: None,
+            medium: None,
+            decoder,
+        }
+    }
+
+    pub const fn with_version(self, version: u8) -> Self {
+        ManufacturerDecoderEntry {
+            version: Some(version),
+            ..self
+        }
+    }
+
+    pub const fn with_medium(self, medium: Medium) -> Self {
+        ManufacturerDecoderEntry {
+            medium: Some(medium),
+            ..self
+        }
+    }
+
+    pub fn matches(&self, header: &FixedDataHeader) -> bool {
+        self.manufacturer == header.manufacturer
+            && self.version.is_none_or(|version| version == header.version)
+            && self.medium.is_none_or(|medium| medium == header.medium)
+    }
+}
+
+impl fmt::Debug for ManufacturerDecoderEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManufacturerDecoderEntry")
+            .field("manufacturer", &self.manufacturer)
+            .field("version", &self.version)
+            .field("medium", &self.medium)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The decoder of the first entry matching the header, so more specific
+/// entries have to be registered before the general ones
+pub fn find_decoder(
+    entries: &[ManufacturerDecoderEntry],
+    header: &FixedDataHeader,
+) -> Option<&'static dyn ManufacturerDecoder> {
+    entries
+        .iter()
+        .find(|entry| entry.matches(header))
+        .map(|entry| entry.decoder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ManufacturerDecoder, ManufacturerDecoderEntry};
+    use crate::user_data::data_information::{DataValue, FunctionField};
+    use crate::user_data::value_information::Unit;
+    use crate::user_data::variable_user_data::{
+        DataRecord, Exponent, Quantity, VariableUserDataError,
+    };
+    use crate::user_data::{
+        DataRecords, FixedDataHeader, IdentificationNumber, ManufacturerCode, Medium,
+        ParserOptions, StatusField,
+    };
+
+    // Reports the first byte of the manufacturer data as error flags and the
+    // data of every manufacturer specific record as operating time in hours
+    struct TestDecoder;
+
+    impl ManufacturerDecoder for TestDecoder {
+        fn decode_record<'a>(
+            &self,
+            _header: &FixedDataHeader,
+            record: &DataRecord<'a>,
+            records: &mut DataRecords<'a>,
+        ) {
+            if let DataValue::Raw(&[hours, ..]) = record.value {
+                let _ = records.add_record(DataRecord {
+                    unit: Unit::Hours,
+                    exponent: Exponent::from(0),
+                    quantity: Quantity::OperatingTime,
+                    value: DataValue::Unsigned(hours as u64),
+                    ..*record
+                });
+            }
+        }
+
+        fn decode_manufacturer_data<'a>(
+            &self,
+            _header: &FixedDataHeader,
+            data: &'a [u8],
+            records: &mut DataRecords<'a>,
+        ) {
+            let _ = records.add_record(DataRecord {
+                function: FunctionField::InstantaneousValue,
+                storage_number: 0,
+                tariff: 0,
+                sub_unit: 0,
+                unit: Unit::WithoutUnits,
+                plain_text_unit: None,
+                raw_data_information: &[],
+                raw_value_information: &[],
+                raw_data: &[],
+                exponent: Exponent { inner: None },
+                quantity: Quantity::ErrorFlags,
+                value: DataValue::Unsigned(data[0] as u64),
+                size: 0,
+            });
+        }
+    }
+
+    // Repeats the last record until the records are full
+    struct FloodingDecoder;
+
+    impl FloodingDecoder {
+        fn flood(records: &mut DataRecords<'_>) {
+            if let Some(record) = records.last().copied() {
+                while records.add_record(record).is_ok() {}
+            }
+        }
+    }
+
+    impl ManufacturerDecoder for FloodingDecoder {
+        fn decode_record<'a>(
+            &self,
+            _header: &FixedDataHeader,
+            _record: &DataRecord<'a>,
+            records: &mut DataRecords<'a>,
+        ) {
+            Self::flood(records);
+        }
+
+        fn decode_manufacturer_data<'a>(
+            &self,
+            _header: &FixedDataHeader,
+            _data: &'a [u8],
+            records: &mut DataRecords<'a>,
+        ) {
+            Self::flood(records);
+        }
+    }
+
+    static FLOODING_DECODERS: &[ManufacturerDecoderEntry] = &[ManufacturerDecoderEntry::new(
+        ManufacturerCode {
+            code: ['A', 'B', 'C'],
+        },
+        &FloodingDecoder,
+    )];
+
+    static DECODERS: &[ManufacturerDecoderEntry] = &[ManufacturerDecoderEntry::new(
+        ManufacturerCode {
+            code: ['A', 'B', 'C'],
+        },
+        &TestDecoder,
+    )
+    .with_version(0x01)
+    .with_medium(Medium::Water)];
+
+    fn header(code: [char; 3], version: u8) -> FixedDataHeader {
+        FixedDataHeader {
+            identification_number: IdentificationNumber { number: 12345678 },
+            manufacturer: ManufacturerCode { code },
+            version,
+            medium: Medium::Water,
+            access_number: 0,
+            status: StatusField::empty(),
+            signature: 0,
+        }
+    }
+
+    #[test]
+    fn test_manufacturer_decoders_are_invoked() {
+        /* volume, manufacturer specific record and manufacturer data */
+        let data = [
+            0x03, 0x13, 0x15, 0x31, 0x00, 0x01, 0xFF, 0x21, 0x0A, 0x0F, 0x42, 0x00,
+        ];
+        let options = ParserOptions {
+            manufacturer_decoders: DECODERS,
+            ..Default::default()
+        };
+
+        let records =
+            DataRecords::parse_with_header(&data, &header(['A', 'B', 'C'], 0x01), &options)
+                .unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records.get(1).unwrap().value, DataValue::Raw(&[0x0A]));
+        assert_eq!(records.get(2).unwrap().quantity, Quantity::OperatingTime);
+        assert_eq!(records.get(2).unwrap().value, DataValue::Unsigned(10));
+        assert_eq!(records.get(3).unwrap().quantity, Quantity::ErrorFlags);
+        assert_eq!(records.get(3).unwrap().value, DataValue::Unsigned(0x42));
+        assert_eq!(records.manufacturer_data(), Some([0x42, 0x00].as_slice()));
+        assert!(!records.more_records_follow());
+        assert!(!records.records_dropped());
+    }
+
+    #[test]
+    fn test_manufacturer_decoders_are_matched_by_header() {
+        let data = [0x01, 0xFF, 0x21, 0x0A, 0x1F, 0x42];
+        let options = ParserOptions {
+            manufacturer_decoders: DECODERS,
+            ..Default::default()
+        };
+
+        for header in [header(['A', 'B', 'D'], 0x01), header(['A', 'B', 'C'], 0x02)] {
+            let records = DataRecords::parse_with_header(&data, &header, &options).unwrap();
+            assert_eq!(records.len(), 1);
+            assert_eq!(records.manufacturer_data(), Some([0x42].as_slice()));
+            assert!(records.more_records_follow());
+        }
+    }
+
+    #[test]
+    fn test_records_dropped_by_a_decoder_are_reported() {
+        let options = ParserOptions {
+            manufacturer_decoders: FLOODING_DECODERS,
+            ..Default::default()
+        };
+        let header = header(['A', 'B', 'C'], 0x01);
+
+        /* manufacturer specific record and manufacturer data */
+        let data = [0x01, 0xFF, 0x21, 0x0A, 0x0F, 0x42];
+        let records = DataRecords::parse_with_header(&data, &header, &options).unwrap();
+        assert!(records.is_full());
+        assert!(records.records_dropped());
+
+        /* a volume following the manufacturer specific record does not fit */
+        let data = [0x01, 0xFF, 0x21, 0x0A, 0x03, 0x13, 0x15, 0x31, 0x00];
+        assert_eq!(
+            DataRecords::parse_with_header(&data, &header, &options),
+            Err(VariableUserDataError::TooManyRecords)
+        );
+    }
+}
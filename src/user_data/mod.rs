@@ -1,30 +1,56 @@
 //! is a part of the application layer
 
 use arrayvec::ArrayVec;
+use core::fmt;
 
+use self::manufacturer_specific::ManufacturerDecoderEntry;
+use self::value_information::PlainTextLayout;
 use self::variable_user_data::DataRecord;
 pub mod data_information;
+pub mod date_time;
+pub mod decimal;
+pub mod libmbus_xml;
+pub mod manufacturer_specific;
+#[cfg(feature = "uom")]
+pub mod physical_quantity;
+pub mod unit_conversion;
 pub mod value_information;
 pub mod variable_user_data;
 
+/// Options to adapt the parser to meters which do not follow EN 13757-3
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParserOptions {
+    pub plain_text_layout: PlainTextLayout,
+    /// Decoders for the manufacturer specific parts of a telegram, only used
+    /// when the fixed data header is known
+    pub manufacturer_decoders: &'static [ManufacturerDecoderEntry],
+}
+
 // Maximum 234 bytes for variable data blocks, each block consists of a minimum of 2 bytes
 // therefore the maximum number of blocks is 117, see https://m-bus.com/documentation-wired/06-application-layer
 const MAXIMUM_VARIABLE_DATA_BLOCKS: usize = 117;
 // Define a new struct that wraps ArrayVec
 #[derive(Debug, PartialEq)]
-pub struct DataRecords {
-    inner: ArrayVec<DataRecord, MAXIMUM_VARIABLE_DATA_BLOCKS>,
+pub struct DataRecords<'a> {
+    inner: ArrayVec<DataRecord<'a>, MAXIMUM_VARIABLE_DATA_BLOCKS>,
+    manufacturer_data: Option<&'a [u8]>,
+    more_records_follow: bool,
+    records_dropped: bool,
 }
 
-impl DataRecords {
+impl<'a> DataRecords<'a> {
     pub fn new() -> Self {
         DataRecords {
             inner: ArrayVec::new(),
+            manufacturer_data: None,
+            more_records_follow: false,
+            records_dropped: false,
         }
     }
 
-    pub fn add_record(&mut self, record: DataRecord) -> Result<(), &'static str> {
+    pub fn add_record(&mut self, record: DataRecord<'a>) -> Result<(), &'static str> {
         if self.inner.try_push(record).is_err() {
+            self.records_dropped = true;
             Err("Maximum capacity reached")
         } else {
             Ok(())
@@ -43,16 +69,51 @@ impl DataRecords {
         self.inner.len() == self.inner.capacity()
     }
 
-    pub fn last(&self) -> Option<&DataRecord> {
+    pub fn last(&self) -> Option<&DataRecord<'a>> {
         self.inner.last()
     }
 
-    pub fn get(&self, index: usize) -> Option<&DataRecord> {
+    pub fn get(&self, index: usize) -> Option<&DataRecord<'a>> {
         self.inner.get(index)
     }
+
+    /// The data following the DIF 0x0F or 0x1F
+    pub fn manufacturer_data(&self) -> Option<&'a [u8]> {
+        self.manufacturer_data
+    }
+
+    /// Whether the DIF 0x1F announced that more records follow in the next telegram
+    pub fn more_records_follow(&self) -> bool {
+        self.more_records_follow
+    }
+
+    /// Whether a record could not be added as the maximum capacity was reached,
+    /// e.g. one a manufacturer decoder tried to add
+    pub fn records_dropped(&self) -> bool {
+        self.records_dropped
+    }
 }
 
-impl Default for DataRecords {
+/// Serialized as the records, the manufacturer data as hex string, whether
+/// more records follow and whether records were dropped
+#[cfg(feature = "serde")]
+impl serde::Serialize for DataRecords<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("DataRecords", 4)?;
+        state.serialize_field("records", self.inner.as_slice())?;
+        state.serialize_field(
+            "manufacturer_data",
+            &self.manufacturer_data.map(crate::serde_helpers::HexBytes),
+        )?;
+        state.serialize_field("more_records_follow", &self.more_records_follow)?;
+        state.serialize_field("records_dropped", &self.records_dropped)?;
+        state.end()
+    }
+}
+
+impl Default for DataRecords<'_> {
     fn default() -> Self {
         DataRecords::new()
     }
@@ -61,6 +122,7 @@ impl Default for DataRecords {
 bitflags::bitflags! {
     #[repr(transparent)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct StatusField: u8 {
         const COUNTER_BINARY_SIGNED     = 0b00000001;
         const COUNTER_FIXED_DATE        = 0b00000010;
@@ -73,7 +135,44 @@ bitflags::bitflags! {
     }
 }
 
+/// The set flags in words separated by commas, `OK` if none is set
+impl fmt::Display for StatusField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("OK");
+        }
+        let names = [
+            (StatusField::COUNTER_BINARY_SIGNED, "counter binary signed"),
+            (StatusField::COUNTER_FIXED_DATE, "counter fixed date"),
+            (StatusField::POWER_LOW, "power low"),
+            (StatusField::PERMANENT_ERROR, "permanent error"),
+            (StatusField::TEMPORARY_ERROR, "temporary error"),
+            (
+                StatusField::MANUFACTURER_SPECIFIC_1,
+                "manufacturer specific 1",
+            ),
+            (
+                StatusField::MANUFACTURER_SPECIFIC_2,
+                "manufacturer specific 2",
+            ),
+            (
+                StatusField::MANUFACTURER_SPECIFIC_3,
+                "manufacturer specific 3",
+            ),
+        ];
+        let mut separator = "";
+        for (flag, name) in names {
+            if self.contains(flag) {
+                write!(f, "{}{}", separator, name)?;
+                separator = ", ";
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     SlaveToMaster,
     MasterToSlave,
@@ -110,6 +209,7 @@ impl From<ControlInformation> for Direction {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ControlInformation {
     SendData,
     SelectSlave,
@@ -136,7 +236,7 @@ pub enum ControlInformation {
 }
 
 impl ControlInformation {
-    fn from(byte: u8) -> Result<ControlInformation, ApplicationLayerError> {
+    pub(crate) fn from(byte: u8) -> Result<ControlInformation, ApplicationLayerError> {
         match byte {
             0x50 => Ok(ControlInformation::ResetAtApplicationLevel),
             0x51 => Ok(ControlInformation::SendData),
@@ -165,15 +265,62 @@ impl ControlInformation {
     }
 }
 
+impl fmt::Display for ControlInformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ControlInformation::SendData => "Send data",
+            ControlInformation::SelectSlave => "Select slave",
+            ControlInformation::ResetAtApplicationLevel => "Reset at application level",
+            ControlInformation::SynchronizeSlave => "Synchronize slave",
+            ControlInformation::SetBaudRate300 => "Set baud rate to 300",
+            ControlInformation::SetBaudRate600 => "Set baud rate to 600",
+            ControlInformation::SetBaudRate1200 => "Set baud rate to 1200",
+            ControlInformation::SetBaudRate2400 => "Set baud rate to 2400",
+            ControlInformation::SetBaudRate4800 => "Set baud rate to 4800",
+            ControlInformation::SetBaudRate9600 => "Set baud rate to 9600",
+            ControlInformation::SetBaudRate19200 => "Set baud rate to 19200",
+            ControlInformation::SetBaudRate38400 => "Set baud rate to 38400",
+            ControlInformation::OutputRAMContent => "Output RAM content",
+            ControlInformation::WriteRAMContent => "Write RAM content",
+            ControlInformation::StartCalibrationTestMode => "Start calibration test mode",
+            ControlInformation::ReadEEPROM => "Read EEPROM",
+            ControlInformation::StartSoftwareTest => "Start software test",
+            ControlInformation::HashProcedure(number) => {
+                return write!(f, "Hash procedure {}", number)
+            }
+            ControlInformation::SendErrorStatus => "Send error status",
+            ControlInformation::SendAlarmStatus => "Send alarm status",
+            ControlInformation::ResponseWithVariableDataStructure => {
+                "Response with variable data structure"
+            }
+            ControlInformation::ResponseWithFixedDataStructure => {
+                "Response with fixed data structure"
+            }
+        };
+        f.write_str(name)
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ApplicationLayerError {
     MissingControlInformation,
-    InvalidControlInformation { byte: u8 },
-    IdentificationNumberError { digits: [u8; 4], number: u32 },
-    InvalidManufacturerCode { code: u16 },
+    InvalidControlInformation {
+        byte: u8,
+    },
+    IdentificationNumberError {
+        digits: [u8; 4],
+        number: u32,
+    },
+    InvalidManufacturerCode {
+        code: u16,
+    },
+    InsufficientData,
+    Unimplemented {
+        control_information: ControlInformation,
+    },
 }
 
-#[cfg(feature = "std")]
 impl fmt::Display for ApplicationLayerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -183,12 +330,25 @@ impl fmt::Display for ApplicationLayerError {
             ApplicationLayerError::InvalidControlInformation { byte } => {
                 write!(f, "Invalid control information: {}", byte)
             }
-            ApplicationLayerError::IdentificationNumberError => {
-                write!(f, "Invalid identification number")
-            }
+            // the BCD digits as they are printed, most significant first
+            ApplicationLayerError::IdentificationNumberError { digits, .. } => write!(
+                f,
+                "Invalid identification number: {:02X}{:02X}{:02X}{:02X}",
+                digits[3], digits[2], digits[1], digits[0]
+            ),
             ApplicationLayerError::InvalidManufacturerCode { code } => {
                 write!(f, "Invalid manufacturer code: {}", code)
             }
+            ApplicationLayerError::InsufficientData => write!(f, "Insufficient data"),
+            ApplicationLayerError::Unimplemented {
+                control_information,
+            } => {
+                write!(
+                    f,
+                    "Unimplemented control information: {}",
+                    control_information
+                )
+            }
         }
     }
 }
@@ -197,6 +357,7 @@ impl fmt::Display for ApplicationLayerError {
 impl std::error::Error for ApplicationLayerError {}
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ApplicationResetSubcode {
     All(u8),
     UserData(u8),
@@ -240,6 +401,37 @@ impl ApplicationResetSubcode {
     }
 }
 
+/// The kind of reset with the whole subcode byte, e.g. `User data (0x01)`
+impl fmt::Display for ApplicationResetSubcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, value) = match self {
+            ApplicationResetSubcode::All(value) => ("All", value),
+            ApplicationResetSubcode::UserData(value) => ("User data", value),
+            ApplicationResetSubcode::SimpleBilling(value) => ("Simple billing", value),
+            ApplicationResetSubcode::EnhancedBilling(value) => ("Enhanced billing", value),
+            ApplicationResetSubcode::MultiTariffBilling(value) => ("Multi tariff billing", value),
+            ApplicationResetSubcode::InstantaneousValues(value) => ("Instantaneous values", value),
+            ApplicationResetSubcode::LoadManagementValues(value) => {
+                ("Load management values", value)
+            }
+            ApplicationResetSubcode::Reserved1(value)
+            | ApplicationResetSubcode::Reserved2(value) => ("Reserved", value),
+            ApplicationResetSubcode::InstallationStartup(value) => {
+                ("Installation and startup", value)
+            }
+            ApplicationResetSubcode::Testing(value) => ("Testing", value),
+            ApplicationResetSubcode::Calibration(value) => ("Calibration", value),
+            ApplicationResetSubcode::ConfigurationUpdates(value) => {
+                ("Configuration updates", value)
+            }
+            ApplicationResetSubcode::Manufacturing(value) => ("Manufacturing", value),
+            ApplicationResetSubcode::Development(value) => ("Development", value),
+            ApplicationResetSubcode::Selftest(value) => ("Selftest", value),
+        };
+        write!(f, "{} (0x{:02X})", name, value)
+    }
+}
+
 fn bcd_hex_digits_to_u32(digits: [u8; 4]) -> Result<u32, ApplicationLayerError> {
     let mut number = 0u32;
 
@@ -256,21 +448,54 @@ fn bcd_hex_digits_to_u32(digits: [u8; 4]) -> Result<u32, ApplicationLayerError>
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Counter {
     count: u32,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct IdentificationNumber {
     number: u32,
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for IdentificationNumber {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:08}", self.number))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for IdentificationNumber {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_helpers::deserialize_str(deserializer, "an 8 digit number", |value| {
+            if value.len() != 8 || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            let number = value.parse().ok()?;
+            Some(IdentificationNumber { number })
+        })
+    }
+}
+
 impl From<IdentificationNumber> for u32 {
     fn from(id: IdentificationNumber) -> Self {
         id.number
     }
 }
 
+impl From<Counter> for u32 {
+    fn from(counter: Counter) -> Self {
+        counter.count
+    }
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.count)
+    }
+}
+
 impl IdentificationNumber {
     pub fn from_bcd_hex_digits(digits: [u8; 4]) -> Result<Self, ApplicationLayerError> {
         let number = bcd_hex_digits_to_u32(digits)?;
@@ -286,6 +511,7 @@ impl Counter {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FixedDataHeder {
     identification_number: IdentificationNumber,
     manufacturer_code: ManufacturerCode,
@@ -298,6 +524,7 @@ pub struct FixedDataHeder {
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum UserDataBlock<'a> {
     ResetAtApplicationLevel {
         subcode: ApplicationResetSubcode,
@@ -312,11 +539,20 @@ pub enum UserDataBlock<'a> {
     },
     VariableDataStructure {
         fixed_data_header: FixedDataHeader,
+        #[cfg_attr(
+            feature = "serde",
+            serde(
+                rename = "data_records",
+                serialize_with = "crate::serde_helpers::records"
+            )
+        )]
         variable_data_block: &'a [u8],
     },
 }
 
-#[derive(Debug, PartialEq)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Medium {
     Other,
     Oil,
@@ -378,7 +614,37 @@ impl Medium {
     }
 }
 
+impl fmt::Display for Medium {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Medium::Other => "Other",
+            Medium::Oil => "Oil",
+            Medium::Electricity => "Electricity",
+            Medium::Gas => "Gas",
+            Medium::Heat => "Heat",
+            Medium::Steam => "Steam",
+            Medium::HotWater => "Hot water",
+            Medium::Water => "Water",
+            Medium::HeatCostAllocator => "Heat cost allocator",
+            Medium::Reserved => "Reserved",
+            Medium::GasMode2 => "Gas (mode 2)",
+            Medium::HeatMode2 => "Heat (mode 2)",
+            Medium::HotWaterMode2 => "Hot water (mode 2)",
+            Medium::WaterMode2 => "Water (mode 2)",
+            Medium::HeatCostAllocator2 => "Heat cost allocator (mode 2)",
+            Medium::ReservedMode2 => "Reserved (mode 2)",
+            Medium::Unknown => "Unknown",
+            Medium::ColdWater => "Cold water",
+            Medium::DualWater => "Dual water",
+            Medium::Pressure => "Pressure",
+            Medium::ADConverter => "A/D converter",
+        };
+        f.write_str(name)
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FixedDataHeader {
     pub identification_number: IdentificationNumber,
     pub manufacturer: ManufacturerCode,
@@ -389,7 +655,156 @@ pub struct FixedDataHeader {
     pub signature: u16,
 }
 
-#[derive(Debug, PartialEq)]
+/// e.g. `ELV 12345678, version 2, Heat, access number 85, status OK`
+impl fmt::Display for FixedDataHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:08}, version {}, {}, access number {}, status {}",
+            self.manufacturer,
+            u32::from(self.identification_number),
+            self.version,
+            self.medium,
+            self.access_number,
+            self.status
+        )
+    }
+}
+
+impl FixedDataHeader {
+    /// The product name from the table of rscada libmbus
+    /// (`mbus_data_product_name` in `mbus-protocol.c`), which tells the meters
+    /// apart by the manufacturer and the version, for some manufacturers also
+    /// by the medium or the identification number
+    pub fn product_name(&self) -> Option<&'static str> {
+        let version = self.version;
+        let electricity = self.medium == Medium::Electricity;
+        let name = match self.manufacturer.code {
+            ['A', 'B', 'B'] => match version {
+                0x02 => "ABB Delta-Meter",
+                0x20 => "ABB B21 113-100",
+                _ => "",
+            },
+            ['A', 'C', 'W'] => match version {
+                0x09 => "Itron CF Echo 2",
+                0x0A => "Itron CF 51",
+                0x0B => "Itron CF 55",
+                0x0E => "Itron BM +m",
+                0x0F => "Itron CF 800",
+                0x14 => "Itron CYBLE M-Bus 1.4",
+                _ => "",
+            },
+            ['A', 'M', 'T'] => match version {
+                0xC0.. => "Aquametro CALEC ST",
+                0x80.. => "Aquametro CALEC MB",
+                0x40.. => "Aquametro SAPHIR",
+                _ => "Aquametro AMTRON",
+            },
+            ['B', 'E', 'C'] if electricity => match version {
+                0x00 => "Berg DCMi",
+                0x07 => "Berg BLMi",
+                _ => "",
+            },
+            ['B', 'E', 'C'] if self.medium == Medium::Unknown && version == 0x71 => "Berg BMB-10S0",
+            ['E', 'F', 'E'] => match version {
+                0x00 if self.medium == Medium::HotWater => "Engelmann WaterStar",
+                0x00 => "Engelmann / Elster SensoStar 2",
+                0x01 => "Engelmann SensoStar 2C",
+                _ => "",
+            },
+            ['E', 'L', 'S'] => match version {
+                0x02 => "Elster TMP-A",
+                0x0A => "Elster Falcon",
+                0x2F => "Elster F96 Plus",
+                _ => "",
+            },
+            ['E', 'L', 'V'] => match version {
+                0x14..=0x1D => "Elvaco CMa10",
+                0x32..=0x3B => "Elvaco CMa11",
+                _ => "",
+            },
+            ['E', 'M', 'H'] if version == 0x00 => "EMH DIZ",
+            ['E', 'M', 'U'] if electricity && version == 0x10 => "EMU Professional 3/75 M-Bus",
+            ['G', 'A', 'V'] if electricity => match version {
+                0x2D..=0x30 => "Carlo Gavazzi EM24",
+                0x39 | 0x3A => "Carlo Gavazzi EM21",
+                0x40 => "Carlo Gavazzi EM33",
+                _ => "",
+            },
+            ['G', 'M', 'C'] if version == 0xE6 => "GMC-I A230 EMMOD 206",
+            ['H', 'Y', 'D'] => match version {
+                0x28 => "ABB F95 Typ US770",
+                0x2F => "Hydrometer Sharky 775",
+                _ => "",
+            },
+            ['J', 'A', 'N'] if electricity && version == 0x09 => "Janitza UMG 96S",
+            ['K', 'A', 'M'] => match version {
+                0x01 => "Kamstrup 382 (6850-005)",
+                0x08 => "Kamstrup Multical 601",
+                _ => "",
+            },
+            ['L', 'S', 'E'] if version == 0x99 => "Siemens WFH21",
+            ['L', 'U', 'G'] => match version {
+                0x02 => "Landis & Gyr Ultraheat 2WR5",
+                0x03 => "Landis & Gyr Ultraheat 2WR6",
+                0x04 => "Landis & Gyr Ultraheat UH50",
+                0x07 => "Landis & Gyr Ultraheat T230",
+                _ => "",
+            },
+            ['N', 'Z', 'R'] if version == 0x01 => "NZR DHZ 5/63",
+            ['R', 'A', 'M'] if version == 0x03 => "Rossweiner ETK/ETW Modularis",
+            ['R', 'E', 'L'] => match version {
+                0x08 => "Relay PadPuls M1",
+                0x12 => "Relay PadPuls M4",
+                0x20 => "Relay Padin 4",
+                0x30 => "Relay AnDi 4",
+                0x40 => "Relay PadPuls M2",
+                _ => "",
+            },
+            ['R', 'K', 'E'] if version == 0x69 => "Ista sensonic II mbus",
+            // Saia-Burgess tells its meters apart by the first digits of the
+            // identification number
+            ['S', 'B', 'C'] => match self.identification_number.number / 1_000_000 {
+                10 | 19 => "Saia-Burgess ALE3",
+                11 => "Saia-Burgess AWD3",
+                _ => "",
+            },
+            ['S', 'E', 'N'] => match version {
+                0x08 | 0x19 => "Sensus PolluCom E",
+                0x0B => "Sensus PolluTherm",
+                0x0E => "Sensus PolluStat E",
+                _ => "",
+            },
+            ['S', 'L', 'B'] => match version {
+                0x02 => "Allmess Megacontrol CF-50",
+                0x06 => "CF Compact / Integral MK MaXX",
+                _ => "",
+            },
+            ['S', 'O', 'N'] if version == 0x0D => "Sontex Supercal 531",
+            ['S', 'P', 'X'] if matches!(version, 0x31 | 0x34) => "Sensus PolluTherm",
+            ['S', 'V', 'M'] => match version {
+                0x08 => "Elster F2 / Deltamess F2",
+                0x09 => "Elster F4 / Kamstrup SVM F22",
+                _ => "",
+            },
+            ['T', 'C', 'H'] => match version {
+                0x26 => "Techem m-bus S",
+                0x40 => "Techem ultra S3",
+                _ => "",
+            },
+            ['W', 'Z', 'G'] if version == 0x03 => "Modularis ETW-EAX",
+            ['Z', 'R', 'M'] => match version {
+                0x81 => "Minol Minocal C2",
+                0x82 => "Minol Minocal WR3",
+                _ => "",
+            },
+            _ => "",
+        };
+        Some(name).filter(|name| !name.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ManufacturerCode {
     pub code: [char; 3],
 }
@@ -413,7 +828,33 @@ impl ManufacturerCode {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(feature = "serde")]
+impl serde::Serialize for ManufacturerCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let [first, second, third] = self.code;
+        serializer.collect_str(&format_args!("{}{}{}", first, second, third))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ManufacturerCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_helpers::deserialize_str(deserializer, "three uppercase letters", |value| {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                (Some(first), Some(second), Some(third), None)
+                    if [first, second, third].iter().all(char::is_ascii_uppercase) =>
+                {
+                    Some(ManufacturerCode {
+                        code: [first, second, third],
+                    })
+                }
+                _ => None,
+            }
+        })
+    }
+}
+
 impl fmt::Display for ManufacturerCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}{}{}", self.code[0], self.code[1], self.code[2])
@@ -421,6 +862,7 @@ impl fmt::Display for ManufacturerCode {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeasuredMedium {
     pub medium: Medium,
 }
@@ -444,29 +886,16 @@ impl<'a> TryFrom<&'a [u8]> for UserDataBlock<'a> {
         let control_information = ControlInformation::from(data[0])?;
 
         match control_information {
+            ControlInformation::ResetAtApplicationLevel if data.len() < 2 => {
+                Err(ApplicationLayerError::InsufficientData)
+            }
             ControlInformation::ResetAtApplicationLevel => {
                 let subcode = ApplicationResetSubcode::from(data[1]);
                 Ok(UserDataBlock::ResetAtApplicationLevel { subcode })
             }
-            ControlInformation::SendData => todo!(),
-            ControlInformation::SelectSlave => todo!(),
-            ControlInformation::SynchronizeSlave => todo!(),
-            ControlInformation::SetBaudRate300 => todo!(),
-            ControlInformation::SetBaudRate600 => todo!(),
-            ControlInformation::SetBaudRate1200 => todo!(),
-            ControlInformation::SetBaudRate2400 => todo!(),
-            ControlInformation::SetBaudRate4800 => todo!(),
-            ControlInformation::SetBaudRate9600 => todo!(),
-            ControlInformation::SetBaudRate19200 => todo!(),
-            ControlInformation::SetBaudRate38400 => todo!(),
-            ControlInformation::OutputRAMContent => todo!(),
-            ControlInformation::WriteRAMContent => todo!(),
-            ControlInformation::StartCalibrationTestMode => todo!(),
-            ControlInformation::ReadEEPROM => todo!(),
-            ControlInformation::StartSoftwareTest => todo!(),
-            ControlInformation::HashProcedure(_) => todo!(),
-            ControlInformation::SendErrorStatus => todo!(),
-            ControlInformation::SendAlarmStatus => todo!(),
+            ControlInformation::ResponseWithVariableDataStructure if data.len() < 13 => {
+                Err(ApplicationLayerError::InsufficientData)
+            }
             ControlInformation::ResponseWithVariableDataStructure => {
                 Ok(UserDataBlock::VariableDataStructure {
                     fixed_data_header: FixedDataHeader {
@@ -485,6 +914,9 @@ impl<'a> TryFrom<&'a [u8]> for UserDataBlock<'a> {
                     variable_data_block: &data[13..data.len()],
                 })
             }
+            ControlInformation::ResponseWithFixedDataStructure if data.len() < 17 => {
+                Err(ApplicationLayerError::InsufficientData)
+            }
             ControlInformation::ResponseWithFixedDataStructure => {
                 let identification_number = IdentificationNumber::from_bcd_hex_digits([
                     data[1], data[2], data[3], data[4],
@@ -505,15 +937,64 @@ impl<'a> TryFrom<&'a [u8]> for UserDataBlock<'a> {
                     counter2,
                 })
             }
+            control_information => Err(ApplicationLayerError::Unimplemented {
+                control_information,
+            }),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    extern crate std;
+    use std::string::ToString;
 
     use super::*;
 
+    #[test]
+    fn test_display() {
+        let error = bcd_hex_digits_to_u32([0x78, 0x56, 0x3A, 0x12]).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid identification number: 123A5678");
+        assert_eq!(
+            ApplicationLayerError::MissingControlInformation.to_string(),
+            "Missing control information"
+        );
+        assert_eq!(
+            ApplicationLayerError::Unimplemented {
+                control_information: ControlInformation::SetBaudRate2400
+            }
+            .to_string(),
+            "Unimplemented control information: Set baud rate to 2400"
+        );
+        assert_eq!(
+            ApplicationResetSubcode::from(0x01).to_string(),
+            "User data (0x01)"
+        );
+        assert_eq!(Medium::HotWaterMode2.to_string(), "Hot water (mode 2)");
+        assert_eq!(StatusField::empty().to_string(), "OK");
+        assert_eq!(
+            (StatusField::POWER_LOW | StatusField::TEMPORARY_ERROR).to_string(),
+            "power low, temporary error"
+        );
+
+        let header = FixedDataHeader {
+            identification_number: IdentificationNumber::from_bcd_hex_digits([
+                0x78, 0x56, 0x34, 0x12,
+            ])
+            .unwrap(),
+            manufacturer: ManufacturerCode::from_id(0x1596).unwrap(),
+            version: 2,
+            medium: Medium::Heat,
+            access_number: 85,
+            status: StatusField::empty(),
+            signature: 0,
+        };
+        assert_eq!(
+            header.to_string(),
+            "ELV 12345678, version 2, Heat, access number 85, status OK"
+        );
+    }
+
     #[test]
     fn test_control_information() {
         assert_eq!(
@@ -607,6 +1088,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_insufficient_data() {
+        for data in [&[0x50][..], &[0x72, 0x78, 0x56, 0x34, 0x12], &[0x73, 0x78]] {
+            assert_eq!(
+                UserDataBlock::try_from(data),
+                Err(ApplicationLayerError::InsufficientData)
+            );
+        }
+        assert_eq!(
+            UserDataBlock::try_from([0x51].as_slice()),
+            Err(ApplicationLayerError::Unimplemented {
+                control_information: ControlInformation::SendData
+            })
+        );
+    }
+
     #[test]
     fn test_identification_number() -> Result<(), ApplicationLayerError> {
         let data = [0x78, 0x56, 0x34, 0x12];
@@ -0,0 +1,204 @@
+//! Conversion of data records into the typed quantities of the `uom` crate.
+//!
+//! The record is scaled by its exponent and normalised to its SI base unit
+//! first, temperature differences become a
+//! [`TemperatureInterval`] rather than a [`ThermodynamicTemperature`].
+
+use ::uom::si::electric_current::ampere;
+use ::uom::si::electric_potential::volt;
+use ::uom::si::energy::joule;
+use ::uom::si::f64::{
+    ElectricCurrent, ElectricPotential, Energy, Frequency, Mass, MassRate, Power, Pressure,
+    TemperatureInterval, ThermodynamicTemperature, Time, Volume, VolumeRate,
+};
+use ::uom::si::frequency::hertz;
+use ::uom::si::mass::kilogram;
+use ::uom::si::mass_rate::kilogram_per_second;
+use ::uom::si::power::watt;
+use ::uom::si::pressure::pascal;
+use ::uom::si::temperature_interval;
+use ::uom::si::thermodynamic_temperature;
+use ::uom::si::time::second;
+use ::uom::si::volume::cubic_meter;
+use ::uom::si::volume_rate::cubic_meter_per_second;
+
+use super::unit_conversion::{BaseUnit, UnitConversionError};
+use super::value_information::Unit;
+use super::variable_user_data::{DataRecord, Quantity};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhysicalQuantity {
+    Energy(Energy),
+    Volume(Volume),
+    VolumeRate(VolumeRate),
+    Power(Power),
+    ThermodynamicTemperature(ThermodynamicTemperature),
+    TemperatureInterval(TemperatureInterval),
+    Pressure(Pressure),
+    Time(Time),
+    Mass(Mass),
+    MassRate(MassRate),
+    ElectricPotential(ElectricPotential),
+    ElectricCurrent(ElectricCurrent),
+    Frequency(Frequency),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhysicalQuantityError {
+    /// The quantity has no physical dimension, like an identification
+    /// number, plain text or error flags
+    NoDimension(Quantity),
+    /// The unit has no counterpart in `uom`, like the apparent power in VA
+    UnsupportedUnit(Unit),
+    NonNumericValue,
+    /// The record holds a quantity of another dimension than requested
+    OtherDimension,
+}
+
+impl TryFrom<&DataRecord<'_>> for PhysicalQuantity {
+    type Error = PhysicalQuantityError;
+
+    fn try_from(record: &DataRecord<'_>) -> Result<Self, PhysicalQuantityError> {
+        let (value, base_unit) = record.base_value().map_err(|error| match error {
+            UnitConversionError::NonNumericValue => PhysicalQuantityError::NonNumericValue,
+            _ => PhysicalQuantityError::NoDimension(record.quantity),
+        })?;
+        Ok(match base_unit {
+            BaseUnit::Joule => PhysicalQuantity::Energy(Energy::new::<joule>(value)),
+            BaseUnit::CubicMeter => PhysicalQuantity::Volume(Volume::new::<cubic_meter>(value)),
+            BaseUnit::CubicMeterPerSecond => {
+                PhysicalQuantity::VolumeRate(VolumeRate::new::<cubic_meter_per_second>(value))
+            }
+            BaseUnit::Kilogram => PhysicalQuantity::Mass(Mass::new::<kilogram>(value)),
+            BaseUnit::KilogramPerSecond => {
+                PhysicalQuantity::MassRate(MassRate::new::<kilogram_per_second>(value))
+            }
+            BaseUnit::Watt => PhysicalQuantity::Power(Power::new::<watt>(value)),
+            BaseUnit::Kelvin if record.quantity == Quantity::TemperatureDifference => {
+                PhysicalQuantity::TemperatureInterval(TemperatureInterval::new::<
+                    temperature_interval::kelvin,
+                >(value))
+            }
+            BaseUnit::Kelvin => {
+                PhysicalQuantity::ThermodynamicTemperature(ThermodynamicTemperature::new::<
+                    thermodynamic_temperature::kelvin,
+                >(value))
+            }
+            BaseUnit::Pascal => PhysicalQuantity::Pressure(Pressure::new::<pascal>(value)),
+            BaseUnit::Second => PhysicalQuantity::Time(Time::new::<second>(value)),
+            BaseUnit::Volt => {
+                PhysicalQuantity::ElectricPotential(ElectricPotential::new::<volt>(value))
+            }
+            BaseUnit::Ampere => {
+                PhysicalQuantity::ElectricCurrent(ElectricCurrent::new::<ampere>(value))
+            }
+            BaseUnit::Hertz => PhysicalQuantity::Frequency(Frequency::new::<hertz>(value)),
+            BaseUnit::VoltAmpere | BaseUnit::VoltAmpereReactive => {
+                return Err(PhysicalQuantityError::UnsupportedUnit(record.unit))
+            }
+        })
+    }
+}
+
+macro_rules! impl_try_from_record {
+    ($($quantity:ident),*) => {
+        $(
+            impl TryFrom<&DataRecord<'_>> for $quantity {
+                type Error = PhysicalQuantityError;
+
+                fn try_from(record: &DataRecord<'_>) -> Result<Self, PhysicalQuantityError> {
+                    match PhysicalQuantity::try_from(record)? {
+                        PhysicalQuantity::$quantity(quantity) => Ok(quantity),
+                        _ => Err(PhysicalQuantityError::OtherDimension),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_record!(
+    Energy,
+    Volume,
+    VolumeRate,
+    Power,
+    ThermodynamicTemperature,
+    TemperatureInterval,
+    Pressure,
+    Time,
+    Mass,
+    MassRate,
+    ElectricPotential,
+    ElectricCurrent,
+    Frequency
+);
+
+#[cfg(test)]
+mod tests {
+    use ::uom::si::energy::kilowatt_hour;
+    use ::uom::si::f64::{Energy, TemperatureInterval, ThermodynamicTemperature, VolumeRate};
+    use ::uom::si::temperature_interval;
+    use ::uom::si::thermodynamic_temperature::degree_celsius;
+    use ::uom::si::volume_rate::cubic_meter_per_hour;
+
+    use super::{PhysicalQuantity, PhysicalQuantityError};
+    use crate::user_data::variable_user_data::Quantity;
+    use crate::user_data::DataRecords;
+
+    #[test]
+    fn test_typed_quantities() {
+        /* 21837 * 10^2 Wh (8 digit BCD), volume flow 1500 l/h, flow
+        temperature 2000 * 10^-2 °C and temperature difference 1000 * 10^-2 K */
+        let data = &[
+            0x0C, 0x05, 0x37, 0x18, 0x02, 0x00, 0x02, 0x3B, 0xDC, 0x05, 0x02, 0x59, 0xD0, 0x07,
+            0x02, 0x61, 0xE8, 0x03,
+        ];
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+
+        let energy = Energy::try_from(result.get(0).unwrap()).unwrap();
+        assert!((energy.get::<kilowatt_hour>() - 2183.7).abs() < 1e-9);
+        let flow = VolumeRate::try_from(result.get(1).unwrap()).unwrap();
+        assert!((flow.get::<cubic_meter_per_hour>() - 1.5).abs() < 1e-9);
+        let temperature = ThermodynamicTemperature::try_from(result.get(2).unwrap()).unwrap();
+        assert!((temperature.get::<degree_celsius>() - 20.0).abs() < 1e-9);
+        let difference = TemperatureInterval::try_from(result.get(3).unwrap()).unwrap();
+        assert!((difference.get::<temperature_interval::kelvin>() - 10.0).abs() < 1e-9);
+
+        assert_eq!(
+            ThermodynamicTemperature::try_from(result.get(3).unwrap()),
+            Err(PhysicalQuantityError::OtherDimension)
+        );
+        assert_eq!(
+            Energy::try_from(result.get(1).unwrap()),
+            Err(PhysicalQuantityError::OtherDimension)
+        );
+    }
+
+    #[test]
+    fn test_dimensionless_quantities() {
+        /* identification number, error flags and a value with the plain text
+        unit "BA" */
+        let data = &[
+            0x0C, 0x78, 0x78, 0x56, 0x34, 0x12, 0x01, 0xFD, 0x17, 0x00, 0x01, 0x7C, 0x02, b'A',
+            b'B', 0x05,
+        ];
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+
+        assert_eq!(
+            PhysicalQuantity::try_from(result.get(0).unwrap()),
+            Err(PhysicalQuantityError::NoDimension(
+                Quantity::IdentificationNumber
+            ))
+        );
+        assert_eq!(
+            PhysicalQuantity::try_from(result.get(1).unwrap()),
+            Err(PhysicalQuantityError::NoDimension(Quantity::ErrorFlags))
+        );
+        assert_eq!(
+            PhysicalQuantity::try_from(result.get(2).unwrap()),
+            Err(PhysicalQuantityError::NoDimension(Quantity::PlainText))
+        );
+    }
+}
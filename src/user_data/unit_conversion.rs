@@ -0,0 +1,282 @@
+//! Normalisation of the units of the standard to SI base units and
+//! conversion between compatible units.
+//!
+//! Every convertible unit is an affine transformation of its base unit,
+//! `base = value * factor + offset`, where only the temperatures have an
+//! offset. Temperature differences have to be converted without it, see
+//! [`Unit::convert_difference`].
+
+use super::value_information::Unit;
+
+/// The SI (derived) unit a [`Unit`] is normalised to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BaseUnit {
+    Joule,
+    CubicMeter,
+    Kilogram,
+    Watt,
+    CubicMeterPerSecond,
+    KilogramPerSecond,
+    Kelvin,
+    Pascal,
+    Second,
+    Volt,
+    Ampere,
+    Hertz,
+    VoltAmpere,
+    VoltAmpereReactive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnitConversionError {
+    /// The unit has no fixed relation to an SI unit, like months, currencies
+    /// or identifiers
+    NoBaseUnit(Unit),
+    /// The units measure different physical quantities
+    IncompatibleUnits { from: Unit, to: Unit },
+    /// The value of the record is not a number
+    NonNumericValue,
+}
+
+// Cubic meters in a cubic foot and in an American gallon
+const CUBIC_FOOT: f64 = 0.028_316_846_592;
+const AMERICAN_GALLON: f64 = 0.003_785_411_784;
+// Joules in an international table mega calorie
+const MEGA_CALORIE: f64 = 4.1868e6;
+
+impl Unit {
+    /// The base unit with the factor and offset to convert a value to it
+    fn to_base_unit(self) -> Option<(BaseUnit, f64, f64)> {
+        let (base_unit, factor, offset) = match self {
+            Unit::WattHour => (BaseUnit::Joule, 3.6e3, 0.0),
+            Unit::KiloWattHour => (BaseUnit::Joule, 3.6e6, 0.0),
+            Unit::MegaWattHour => (BaseUnit::Joule, 3.6e9, 0.0),
+            Unit::Joul => (BaseUnit::Joule, 1.0, 0.0),
+            Unit::KiloJoul => (BaseUnit::Joule, 1e3, 0.0),
+            Unit::MegaJoul => (BaseUnit::Joule, 1e6, 0.0),
+            Unit::GigaJoul => (BaseUnit::Joule, 1e9, 0.0),
+            Unit::MegaCalorie => (BaseUnit::Joule, MEGA_CALORIE, 0.0),
+            Unit::Liter => (BaseUnit::CubicMeter, 1e-3, 0.0),
+            Unit::MegaLiter => (BaseUnit::CubicMeter, 1e3, 0.0),
+            Unit::CubicMeter => (BaseUnit::CubicMeter, 1.0, 0.0),
+            Unit::CubicFeet => (BaseUnit::CubicMeter, CUBIC_FOOT, 0.0),
+            Unit::AmericanGallon => (BaseUnit::CubicMeter, AMERICAN_GALLON, 0.0),
+            Unit::Kilogram => (BaseUnit::Kilogram, 1.0, 0.0),
+            Unit::Tonne => (BaseUnit::Kilogram, 1e3, 0.0),
+            Unit::Watt => (BaseUnit::Watt, 1.0, 0.0),
+            Unit::KiloWatt => (BaseUnit::Watt, 1e3, 0.0),
+            Unit::MegaWat | Unit::MegaWatt => (BaseUnit::Watt, 1e6, 0.0),
+            Unit::JoulPerHour => (BaseUnit::Watt, 1.0 / 3600.0, 0.0),
+            Unit::KiloJoulHour => (BaseUnit::Watt, 1e3 / 3600.0, 0.0),
+            Unit::MegaJoulHour => (BaseUnit::Watt, 1e6 / 3600.0, 0.0),
+            Unit::GigaJoulHour => (BaseUnit::Watt, 1e9 / 3600.0, 0.0),
+            Unit::LiterHour => (BaseUnit::CubicMeterPerSecond, 1e-3 / 3600.0, 0.0),
+            Unit::MegaLiterHour => (BaseUnit::CubicMeterPerSecond, 1e3 / 3600.0, 0.0),
+            Unit::CubicMeterPerHour => (BaseUnit::CubicMeterPerSecond, 1.0 / 3600.0, 0.0),
+            Unit::CubicMeterPerMinute => (BaseUnit::CubicMeterPerSecond, 1.0 / 60.0, 0.0),
+            Unit::CubicMeterPerSecond => (BaseUnit::CubicMeterPerSecond, 1.0, 0.0),
+            Unit::AmericanGallonPerMinute => {
+                (BaseUnit::CubicMeterPerSecond, AMERICAN_GALLON / 60.0, 0.0)
+            }
+            Unit::AmericanGallonPerHour => {
+                (BaseUnit::CubicMeterPerSecond, AMERICAN_GALLON / 3600.0, 0.0)
+            }
+            Unit::KilogramPerHour => (BaseUnit::KilogramPerSecond, 1.0 / 3600.0, 0.0),
+            Unit::Kelvin => (BaseUnit::Kelvin, 1.0, 0.0),
+            Unit::Celsius => (BaseUnit::Kelvin, 1.0, 273.15),
+            Unit::Fahrenheit => (BaseUnit::Kelvin, 5.0 / 9.0, 459.67 * 5.0 / 9.0),
+            Unit::Bar => (BaseUnit::Pascal, 1e5, 0.0),
+            Unit::Seconds => (BaseUnit::Second, 1.0, 0.0),
+            Unit::Minutes => (BaseUnit::Second, 60.0, 0.0),
+            Unit::Hours => (BaseUnit::Second, 3600.0, 0.0),
+            Unit::Days => (BaseUnit::Second, 86400.0, 0.0),
+            Unit::Volt => (BaseUnit::Volt, 1.0, 0.0),
+            Unit::Ampere => (BaseUnit::Ampere, 1.0, 0.0),
+            Unit::Hertz => (BaseUnit::Hertz, 1.0, 0.0),
+            Unit::KiloVoltAmpere => (BaseUnit::VoltAmpere, 1e3, 0.0),
+            Unit::KiloVoltAmpereReactive => (BaseUnit::VoltAmpereReactive, 1e3, 0.0),
+            _ => return None,
+        };
+        Some((base_unit, factor, offset))
+    }
+
+    /// The SI unit this unit is normalised to, if it has a fixed relation to
+    /// one
+    pub fn base_unit(self) -> Option<BaseUnit> {
+        self.to_base_unit().map(|(base_unit, _, _)| base_unit)
+    }
+
+    /// Converts a value of this unit to its base unit
+    pub fn to_base(self, value: f64) -> Result<(f64, BaseUnit), UnitConversionError> {
+        let (base_unit, factor, offset) = self
+            .to_base_unit()
+            .ok_or(UnitConversionError::NoBaseUnit(self))?;
+        Ok((value * factor + offset, base_unit))
+    }
+
+    /// Converts a value of this unit to `to`, e.g. 1 kWh to 3.6 MJ or
+    /// 20 °C to 68 °F
+    pub fn convert(self, value: f64, to: Unit) -> Result<f64, UnitConversionError> {
+        self.convert_with(value, to, true)
+    }
+
+    /// Converts a difference of two values, which only differs from
+    /// [`Unit::convert`] for temperatures: a difference of 1 K is a
+    /// difference of 1 °C and 1.8 °F
+    pub fn convert_difference(self, value: f64, to: Unit) -> Result<f64, UnitConversionError> {
+        self.convert_with(value, to, false)
+    }
+
+    fn convert_with(
+        self,
+        value: f64,
+        to: Unit,
+        absolute: bool,
+    ) -> Result<f64, UnitConversionError> {
+        let (from_base, from_factor, from_offset) = self
+            .to_base_unit()
+            .ok_or(UnitConversionError::NoBaseUnit(self))?;
+        let (to_base, to_factor, to_offset) = to
+            .to_base_unit()
+            .ok_or(UnitConversionError::NoBaseUnit(to))?;
+        if from_base != to_base {
+            return Err(UnitConversionError::IncompatibleUnits { from: self, to });
+        }
+        if absolute {
+            Ok((value * from_factor + from_offset - to_offset) / to_factor)
+        } else {
+            Ok(value * from_factor / to_factor)
+        }
+    }
+}
+
+/// Multiplies the value by 10^exponent, dividing for negative exponents so
+/// values like 21837 * 10^-2 come out as the closest float to 218.37
+pub(crate) fn scale(value: f64, exponent: isize) -> f64 {
+    let mut power = 1.0;
+    for _ in 0..exponent.unsigned_abs() {
+        power *= 10.0;
+    }
+    if exponent < 0 {
+        value / power
+    } else {
+        value * power
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scale, BaseUnit, UnitConversionError};
+    use crate::user_data::value_information::Unit;
+
+    fn assert_close(actual: f64, expected: f64) {
+        let tolerance = 1e-9 * expected.abs().max(1.0);
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_energy_conversion() {
+        assert_close(Unit::KiloWattHour.convert(1.0, Unit::Joul).unwrap(), 3.6e6);
+        assert_close(Unit::WattHour.convert(1e6, Unit::GigaJoul).unwrap(), 3.6);
+        assert_close(
+            Unit::GigaJoul.convert(3.6, Unit::MegaWattHour).unwrap(),
+            1.0,
+        );
+        assert_close(
+            Unit::MegaCalorie.convert(1.0, Unit::KiloJoul).unwrap(),
+            4186.8,
+        );
+        assert_eq!(
+            Unit::MegaJoul.to_base(2.5).unwrap(),
+            (2.5e6, BaseUnit::Joule)
+        );
+    }
+
+    #[test]
+    fn test_temperature_conversion() {
+        assert_close(Unit::Celsius.convert(20.0, Unit::Kelvin).unwrap(), 293.15);
+        assert_close(
+            Unit::Celsius.convert(100.0, Unit::Fahrenheit).unwrap(),
+            212.0,
+        );
+        assert_close(Unit::Fahrenheit.convert(32.0, Unit::Celsius).unwrap(), 0.0);
+        assert_close(
+            Unit::Kelvin.convert(0.0, Unit::Fahrenheit).unwrap(),
+            -459.67,
+        );
+        assert_close(
+            Unit::Kelvin
+                .convert_difference(10.0, Unit::Fahrenheit)
+                .unwrap(),
+            18.0,
+        );
+        assert_close(
+            Unit::Celsius.convert_difference(5.0, Unit::Kelvin).unwrap(),
+            5.0,
+        );
+    }
+
+    #[test]
+    fn test_pressure_flow_and_volume_conversion() {
+        assert_close(Unit::Bar.convert(1.5, Unit::Bar).unwrap(), 1.5);
+        assert_eq!(Unit::Bar.to_base(1.5).unwrap(), (1.5e5, BaseUnit::Pascal));
+        assert_close(
+            Unit::LiterHour
+                .convert(1500.0, Unit::CubicMeterPerHour)
+                .unwrap(),
+            1.5,
+        );
+        assert_close(
+            Unit::CubicMeterPerHour
+                .convert(3.6, Unit::CubicMeterPerSecond)
+                .unwrap(),
+            1e-3,
+        );
+        assert_close(
+            Unit::AmericanGallon.convert(1.0, Unit::Liter).unwrap(),
+            3.785411784,
+        );
+        assert_close(Unit::Hours.convert(1.5, Unit::Minutes).unwrap(), 90.0);
+    }
+
+    #[test]
+    fn test_incompatible_conversion() {
+        assert_eq!(
+            Unit::KiloWattHour.convert(1.0, Unit::KiloWatt),
+            Err(UnitConversionError::IncompatibleUnits {
+                from: Unit::KiloWattHour,
+                to: Unit::KiloWatt
+            })
+        );
+        assert_eq!(
+            Unit::Celsius.convert(1.0, Unit::Bar),
+            Err(UnitConversionError::IncompatibleUnits {
+                from: Unit::Celsius,
+                to: Unit::Bar
+            })
+        );
+        assert_eq!(
+            Unit::Months.convert(1.0, Unit::Days),
+            Err(UnitConversionError::NoBaseUnit(Unit::Months))
+        );
+        assert_eq!(
+            Unit::Liter.convert(1.0, Unit::HCA),
+            Err(UnitConversionError::NoBaseUnit(Unit::HCA))
+        );
+        assert_eq!(Unit::WithoutUnits.base_unit(), None);
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(21837.0, -2), 218.37);
+        assert_eq!(scale(12.0, 3), 12000.0);
+        assert_eq!(scale(7.0, 0), 7.0);
+    }
+}
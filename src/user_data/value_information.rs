@@ -1,29 +1,47 @@
 use arrayvec::ArrayVec;
+use core::fmt;
 
-const MAX_PLAIN_TEXT_VIF_SIZE: usize = 10;
-/* TODO add suppor for 2 - 10 VIFE */
+use super::data_information::AsciiText;
 
 const MAX_VIFE_RECORDS: usize = 10;
-#[derive(Debug)]
-struct ValueInformationBlock {
-    _value_information: ValueInformation,
-    _value_information_extension: Option<ArrayVec<u8, MAX_VIFE_RECORDS>>,
+
+/// Where the VIFE of a plain text VIF (0xFC) is placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlainTextLayout {
+    /// VIF, VIFE, length and text as required by EN 13757-3
+    Strict,
+    /// VIF, length, text and VIFE, which is what most meters actually send
+    Common,
+    /// Try the strict layout first and fall back to the common one if the
+    /// text does not fit into the data or contains non printable characters
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValueInformationBlock<'a> {
+    pub value_information: ValueInformation<'a>,
+    pub value_information_extension: Option<ArrayVec<u8, MAX_VIFE_RECORDS>>,
+    size: usize,
 }
 
 #[derive(Debug, PartialEq)]
-pub enum ValueInformation {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ValueInformation<'a> {
     Primary(u8),
-    PlainText(ArrayVec<u8, MAX_PLAIN_TEXT_VIF_SIZE>),
+    PlainText(AsciiText<'a>),
     Extended(VIFExtension),
     Any,
     ManufacturerSpecific,
 }
 
-impl ValueInformation {
+impl ValueInformation<'_> {
     pub fn get_size(&self) -> usize {
         match self {
             ValueInformation::Primary(_) => 1,
-            ValueInformation::PlainText(x) => x.len() + 2,
+            ValueInformation::PlainText(x) => x.bytes.len() + 2,
             ValueInformation::Extended(_) => 2,
             ValueInformation::Any => 1,
             ValueInformation::ManufacturerSpecific => 1,
@@ -32,107 +50,259 @@ impl ValueInformation {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueInformationError {
     InvalidValueInformation,
+    DataTooShort,
+    TooManyExtensions,
 }
 
-impl TryFrom<&[u8]> for ValueInformation {
-    type Error = ValueInformationError;
+impl fmt::Display for ValueInformationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueInformationError::InvalidValueInformation => {
+                write!(f, "Invalid value information")
+            }
+            ValueInformationError::DataTooShort => write!(f, "Data too short"),
+            ValueInformationError::TooManyExtensions => write!(f, "Too many extensions"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValueInformationError {}
 
-    fn try_from(data: &[u8]) -> Result<Self, ValueInformationError> {
-        Ok(match data[0] {
-            0x00..=0x7B | 0x80..=0xFA => ValueInformation::Primary(data[0]),
-            0x7C | 0xFC => {
-                let mut vif = ArrayVec::new();
-                let len = data[1] as usize;
-                for i in 0..len {
-                    vif.push(data[i + 2]);
+fn plain_text(data: &[u8]) -> Result<AsciiText<'_>, ValueInformationError> {
+    let length = *data.first().ok_or(ValueInformationError::DataTooShort)? as usize;
+    let bytes = data
+        .get(1..1 + length)
+        .ok_or(ValueInformationError::DataTooShort)?;
+    Ok(AsciiText { bytes })
+}
+
+// Every VIFE has the extension bit set, except for the last one
+fn extensions(data: &[u8]) -> Result<ArrayVec<u8, MAX_VIFE_RECORDS>, ValueInformationError> {
+    let mut extensions = ArrayVec::new();
+    for &byte in data {
+        extensions
+            .try_push(byte)
+            .map_err(|_| ValueInformationError::TooManyExtensions)?;
+        if byte & 0x80 == 0 {
+            return Ok(extensions);
+        }
+    }
+    Err(ValueInformationError::DataTooShort)
+}
+
+impl<'a> ValueInformationBlock<'a> {
+    pub fn parse(data: &'a [u8], layout: PlainTextLayout) -> Result<Self, ValueInformationError> {
+        let vif = *data.first().ok_or(ValueInformationError::DataTooShort)?;
+        if vif == 0xFC {
+            return match layout {
+                PlainTextLayout::Strict => Self::plain_text_strict(data),
+                PlainTextLayout::Common => Self::plain_text_common(data),
+                PlainTextLayout::Auto => {
+                    let strict = Self::plain_text_strict(data);
+                    if strict.as_ref().is_ok_and(Self::is_printable) {
+                        return strict;
+                    }
+                    let common = Self::plain_text_common(data);
+                    if common.as_ref().is_ok_and(Self::is_printable) || strict.is_err() {
+                        common
+                    } else {
+                        strict
+                    }
                 }
-                vif.reverse();
-                ValueInformation::PlainText(vif)
+            };
+        }
+
+        let value_information = ValueInformation::try_from(data)?;
+        let mut size = value_information.get_size();
+        let extension_bit = match vif {
+            0x7C => false,
+            _ => data[size - 1] & 0x80 != 0,
+        };
+        let value_information_extension = if extension_bit {
+            let extensions = extensions(&data[size..])?;
+            size += extensions.len();
+            Some(extensions)
+        } else {
+            None
+        };
+        Ok(ValueInformationBlock {
+            value_information,
+            value_information_extension,
+            size,
+        })
+    }
+
+    fn plain_text_strict(data: &'a [u8]) -> Result<Self, ValueInformationError> {
+        let extensions = extensions(&data[1..])?;
+        let text = plain_text(&data[1 + extensions.len()..])?;
+        Ok(ValueInformationBlock {
+            size: 2 + extensions.len() + text.bytes.len(),
+            value_information: ValueInformation::PlainText(text),
+            value_information_extension: Some(extensions),
+        })
+    }
+
+    fn plain_text_common(data: &'a [u8]) -> Result<Self, ValueInformationError> {
+        let text = plain_text(&data[1..])?;
+        let extensions = extensions(&data[2 + text.bytes.len()..])?;
+        Ok(ValueInformationBlock {
+            size: 2 + extensions.len() + text.bytes.len(),
+            value_information: ValueInformation::PlainText(text),
+            value_information_extension: Some(extensions),
+        })
+    }
+
+    fn is_printable(&self) -> bool {
+        match &self.value_information {
+            ValueInformation::PlainText(text) => {
+                text.bytes.iter().all(|byte| (0x20..0x7F).contains(byte))
+            }
+            _ => true,
+        }
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ValueInformationBlock<'a> {
+    type Error = ValueInformationError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, ValueInformationError> {
+        ValueInformationBlock::parse(data, PlainTextLayout::default())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ValueInformation<'a> {
+    type Error = ValueInformationError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, ValueInformationError> {
+        let vif = *data.first().ok_or(ValueInformationError::DataTooShort)?;
+        let extended = || {
+            data.get(1)
+                .map(|byte| byte & 0x7F)
+                .ok_or(ValueInformationError::DataTooShort)
+        };
+        Ok(match vif {
+            0x00..=0x7B | 0x80..=0xFA => ValueInformation::Primary(vif),
+            0x7C | 0xFC => ValueInformation::PlainText(plain_text(&data[1..])?),
+            0xFD => {
+                let code = extended()?;
+                ValueInformation::Extended(match code {
+                    0x00..=0x03 => VIFExtension::CreditOfCurrencyUnits(0b11 & code),
+                    0x04..=0x07 => VIFExtension::DebitOfCurrencyUnits(0b11 & code),
+                    0x08 => VIFExtension::AccessNumber,
+                    0x09 => VIFExtension::Medium,
+                    0x0A => VIFExtension::Manufacturer,
+                    0x0B => VIFExtension::ParameterSetIdentification,
+                    0x0C => VIFExtension::ModelVersion,
+                    0x0D => VIFExtension::HardwareVersion,
+                    0x0E => VIFExtension::FirmwareVersion,
+                    0x0F => VIFExtension::SoftwareVersion,
+                    0x10 => VIFExtension::CustomerLocation,
+                    0x11 => VIFExtension::Customer,
+                    0x12 => VIFExtension::AccessCodeUser,
+                    0x13 => VIFExtension::AccessCodeOperator,
+                    0x14 => VIFExtension::AccessCodeSystemOperator,
+                    0x15 => VIFExtension::AccessCodeDeveloper,
+                    0x16 => VIFExtension::Password,
+                    0x17 => VIFExtension::ErrorFlags,
+                    0x18 => VIFExtension::ErrorMask,
+                    0x19 => VIFExtension::SecurityKey,
+                    0x1A => VIFExtension::DigitalOutput,
+                    0x1B => VIFExtension::DigitalInput,
+                    0x1C => VIFExtension::BaudRate,
+                    0x1D => VIFExtension::ResponseDelayTime,
+                    0x1E => VIFExtension::Retry,
+                    0x1F => VIFExtension::RemoteControl,
+                    0x20 => VIFExtension::FirstStorage,
+                    0x21 => VIFExtension::LastStorage,
+                    0x22 => VIFExtension::SizeOfStorageBlock,
+                    0x24..=0x27 => VIFExtension::StorageIntervalSecondsToDays(0b11 & code),
+                    0x28 => VIFExtension::StorageIntervalMonths,
+                    0x29 => VIFExtension::StorageIntervalYears,
+                    0x2A => VIFExtension::OperatorSpecificData,
+                    0x2B => VIFExtension::TimePointSecond,
+                    0x2C..=0x2F => VIFExtension::DurationSinceLastReadout(0b11 & code),
+                    0x30 => VIFExtension::StartOfTariff,
+                    0x31..=0x33 => VIFExtension::DurationOfTariff(0b11 & code),
+                    0x34..=0x37 => VIFExtension::PeriodOfTariff(0b11 & code),
+                    0x38 => VIFExtension::PeriodOfTarrifMonths,
+                    0x39 => VIFExtension::PeriodOfTTariffYears,
+                    0x3A => VIFExtension::Dimensionless,
+                    0x3B => VIFExtension::DataContainerWirelessMBus,
+                    0x3C..=0x3F => VIFExtension::PeriodOfNominalDataTransmissions(0b11 & code),
+                    0x40..=0x4F => VIFExtension::Volts(0b1111 & code),
+                    0x50..=0x5F => VIFExtension::Ampere(0b1111 & code),
+                    0x60 => VIFExtension::ResetCounter,
+                    0x61 => VIFExtension::CumulationCounter,
+                    0x62 => VIFExtension::ControlSignal,
+                    0x63 => VIFExtension::DayOfWeek,
+                    0x64 => VIFExtension::WeekNumber,
+                    0x65 => VIFExtension::TimePointOfDay,
+                    0x66 => VIFExtension::StateOfParameterActivation,
+                    0x67 => VIFExtension::SpecialSupervision,
+                    0x68..=0x6B => VIFExtension::DurationSinceLastCumulation(0b11 & code),
+                    0x6C..=0x6F => VIFExtension::OperatingTimeBattery(0b11 & code),
+                    0x70 => VIFExtension::DateAndTimeOfBatteryChange,
+                    0x71 => VIFExtension::RFLevel,
+                    0x72 => VIFExtension::DaylightSaving,
+                    0x73 => VIFExtension::ListeningWindowManagement,
+                    0x74 => VIFExtension::RemainingBatteryLifeTime,
+                    0x75 => VIFExtension::NumberOfTimesMeterStopped,
+                    0x76 => VIFExtension::DataContainerManufacturerSpecific,
+                    _ => VIFExtension::Reserved,
+                })
+            }
+            0xFB => {
+                let code = extended()?;
+                ValueInformation::Extended(match code {
+                    0x00 | 0x01 => VIFExtension::EnergyMWh(0b1 & code),
+                    0x02 | 0x03 => VIFExtension::ReactiveEnergy(0b1 & code),
+                    0x04 | 0x05 => VIFExtension::ApparentEnergy(0b1 & code),
+                    0x08 | 0x09 => VIFExtension::EnergyGJ(0b1 & code),
+                    0x0C..=0x0F => VIFExtension::EnergyMCal(0b11 & code),
+                    0x10 | 0x11 => VIFExtension::VolumeM3(0b1 & code),
+                    0x14..=0x17 => VIFExtension::ReactivePower(0b11 & code),
+                    0x18 | 0x19 => VIFExtension::MassTons(0b1 & code),
+                    0x1A | 0x1B => VIFExtension::RelativeHumidity(0b1 & code),
+                    0x20 => VIFExtension::VolumeFeet3,
+                    0x21 => VIFExtension::VolumeFeet3Tenth,
+                    0x22 => VIFExtension::VolumeAmericanGallonTenth,
+                    0x23 => VIFExtension::VolumeAmericanGallon,
+                    0x24 => VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth,
+                    0x25 => VIFExtension::VolumeFlowAmericanGallonPerMinute,
+                    0x26 => VIFExtension::VolumeFlowAmericanGallonPerHour,
+                    0x28 | 0x29 => VIFExtension::PowerMW(0b1 & code),
+                    0x2A => VIFExtension::PhaseVoltageToVoltage,
+                    0x2B => VIFExtension::PhaseVoltageToCurrent,
+                    0x2C..=0x2F => VIFExtension::Frequency(0b11 & code),
+                    0x30 | 0x31 => VIFExtension::PowerGJH(0b1 & code),
+                    0x34..=0x37 => VIFExtension::ApparentPower(0b11 & code),
+                    0x58..=0x5B => VIFExtension::FlowTemperature(0b11 & code),
+                    0x5C..=0x5F => VIFExtension::ReturnTemperature(0b11 & code),
+                    0x60..=0x63 => VIFExtension::TemperatureDifference(0b11 & code),
+                    0x64..=0x67 => VIFExtension::ExternalTemperature(0b11 & code),
+                    0x70..=0x73 => VIFExtension::ColdWarmTemperatureLimitFarenheit(0b11 & code),
+                    0x74..=0x77 => VIFExtension::ColdWarmTemperatureLimitCelsius(0b11 & code),
+                    0x78..=0x7F => VIFExtension::CumulativeCountMaxPower(0b111 & code),
+                    _ => VIFExtension::Reserved,
+                })
             }
-            0xFD => ValueInformation::Extended(match data[1] {
-                0x00..=0x03 => VIFExtension::CreditOfCurrencyUnits(0b11 & data[1]),
-                0x04..=0x07 => VIFExtension::DebitOfCurrencyUnits(0b11 & data[1]),
-                0x08 => VIFExtension::AccessNumber,
-                0x09 => VIFExtension::Medium,
-                0x0A => VIFExtension::Manufacturer,
-                0x0B => VIFExtension::ParameterSetIdentification,
-                0x0C => VIFExtension::ModelVersion,
-                0x0D => VIFExtension::HardwareVersion,
-                0x0E => VIFExtension::FirmwareVersion,
-                0x0F => VIFExtension::SoftwareVersion,
-                0x10 => VIFExtension::CustomerLocation,
-                0x11 => VIFExtension::Customer,
-                0x12 => VIFExtension::AccessCodeUser,
-                0x13 => VIFExtension::AccessCodeOperator,
-                0x14 => VIFExtension::AccessCodeSystemOperator,
-                0x15 => VIFExtension::AccessCodeDeveloper,
-                0x16 => VIFExtension::Password,
-                0x17 => VIFExtension::ErrorFlags,
-                0x18 => VIFExtension::ErrorMask,
-                0x1A => VIFExtension::DigitalOutput,
-                0x1B => VIFExtension::DigitalInput,
-                0x1C => VIFExtension::BaudRate,
-                0x1D => VIFExtension::ResponseDelayTime,
-                0x1E => VIFExtension::Retry,
-                0x20 => VIFExtension::FirstStorage,
-                0x21 => VIFExtension::LastStorage,
-                0x22 => VIFExtension::SizeOfStorageBlock,
-                0x23..=0x26 => VIFExtension::StorageIntervalSecondsToDays(0b11 & data[1]),
-                0x28 => VIFExtension::StorageIntervalMonths,
-                0x29 => VIFExtension::StorageIntervalYears,
-                0x2C..=0x2F => VIFExtension::DurationSinceLastReadout(0b11 & data[1]),
-                0x30 => VIFExtension::StartOfTariff,
-                0x31..=0x33 => VIFExtension::DurationOfTariff(0b11 & data[1]),
-                0x34..=0x37 => VIFExtension::PeriodOfTariff(0b11 & data[1]),
-                0x38 => VIFExtension::PeriodOfTarrifMonths,
-                0x39 => VIFExtension::PeriodOfTTariffYears,
-                0x3A => VIFExtension::Dimensionless,
-                0x40..=0x47 => VIFExtension::Volts(0b1111 & data[1]),
-                0x48..=0x4F => VIFExtension::Ampere(0b1111 & data[1]),
-                0x60 => VIFExtension::ResetCounter,
-                0x61 => VIFExtension::CumulationCounter,
-                0x62 => VIFExtension::ControlSignal,
-                0x63 => VIFExtension::DayOfWeek,
-                0x64 => VIFExtension::WeekNumber,
-                0x65 => VIFExtension::TimePointOfDay,
-                0x66 => VIFExtension::StateOfParameterActivation,
-                0x67 => VIFExtension::SpecialSupervision,
-                0x68..=0x6B => VIFExtension::DurationSinceLastCumulation(0b11 & data[1]),
-                0x6C..=0x6F => VIFExtension::OperatingTimeBattery(0b11 & data[1]),
-                0x70 => VIFExtension::DateAndTimeOfBatteryChange,
-                _ => VIFExtension::Reserved,
-            }),
-            0xFB => ValueInformation::Extended(match data[1] {
-                0x00 | 0x01 => VIFExtension::EnergyMWh(0b1 & data[1]),
-                0x08 | 0x09 => VIFExtension::EnergyGJ(0b1 & data[1]),
-                0x10 | 0x11 => VIFExtension::VolumeM3(0b1 & data[1]),
-                0x18 | 0x19 => VIFExtension::MassTons(0b1 & data[1]),
-                0x21 => VIFExtension::VolumeFeet3Tenth,
-                0x22 => VIFExtension::VolumeAmericanGallon,
-                0x23 => VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth,
-                0x24 => VIFExtension::VolumeFlowAmericanGallonPerMinute,
-                0x25 => VIFExtension::VolumeFlowAmericanGallonPerHour,
-                0x28 | 0x29 => VIFExtension::PowerMW(0b1 & data[1]),
-                0x30 | 0x31 => VIFExtension::PowerGJH(0b1 & data[1]),
-                0x50..=0x53 => VIFExtension::FlowTemperature(0b11 & data[1]),
-                0x54..=0x57 => VIFExtension::ReturnTemperature(0b11 & data[1]),
-                0x60..=0x63 => VIFExtension::TemperatureDifference(0b11 & data[1]),
-                0x64..=0x67 => VIFExtension::ExternalTemperature(0b11 & data[1]),
-                0x70..=0x73 => VIFExtension::ColdWarmTemperatureLimitFarenheit(0b11 & data[1]),
-                0x74..=0x77 => VIFExtension::ColdWarmTemperatureLimitCelsius(0b11 & data[1]),
-                0x78..=0x7F => VIFExtension::CumulativeCountMaxPower(0b111 & data[1]),
-                _ => VIFExtension::Reserved,
-            }),
             0x7E | 0xFE => ValueInformation::Any,
             0x7F | 0xFF => ValueInformation::ManufacturerSpecific,
-            _ => unreachable!("Invalid value information: {:X}", data[0]),
+            _ => return Err(ValueInformationError::InvalidValueInformation),
         })
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VIFExtension {
     CreditOfCurrencyUnits(u8),
     DebitOfCurrencyUnits(u8),
@@ -153,18 +323,22 @@ pub enum VIFExtension {
     Password,
     ErrorFlags,
     ErrorMask,
+    SecurityKey,
     Reserved,
     DigitalOutput,
     DigitalInput,
     BaudRate,
     ResponseDelayTime,
     Retry,
+    RemoteControl,
     FirstStorage,
     LastStorage,
     SizeOfStorageBlock,
     StorageIntervalSecondsToDays(u8),
     StorageIntervalMonths,
     StorageIntervalYears,
+    OperatorSpecificData,
+    TimePointSecond,
     DurationSinceLastReadout(u8),
     StartOfTariff,
     DurationOfTariff(u8),
@@ -172,6 +346,8 @@ pub enum VIFExtension {
     PeriodOfTarrifMonths,
     PeriodOfTTariffYears,
     Dimensionless,
+    DataContainerWirelessMBus,
+    PeriodOfNominalDataTransmissions(u8),
     Volts(u8),
     Ampere(u8),
     ResetCounter,
@@ -185,10 +361,22 @@ pub enum VIFExtension {
     DurationSinceLastCumulation(u8),
     OperatingTimeBattery(u8),
     DateAndTimeOfBatteryChange,
+    RFLevel,
+    DaylightSaving,
+    ListeningWindowManagement,
+    RemainingBatteryLifeTime,
+    NumberOfTimesMeterStopped,
+    DataContainerManufacturerSpecific,
     EnergyMWh(u8),
+    ReactiveEnergy(u8),
+    ApparentEnergy(u8),
     EnergyGJ(u8),
+    EnergyMCal(u8),
     VolumeM3(u8),
+    ReactivePower(u8),
     MassTons(u8),
+    RelativeHumidity(u8),
+    VolumeFeet3,
     VolumeFeet3Tenth,
     VolumeAmericanGallonTenth,
     VolumeAmericanGallon,
@@ -196,7 +384,11 @@ pub enum VIFExtension {
     VolumeFlowAmericanGallonPerMinute,
     VolumeFlowAmericanGallonPerHour,
     PowerMW(u8),
+    PhaseVoltageToVoltage,
+    PhaseVoltageToCurrent,
+    Frequency(u8),
     PowerGJH(u8),
+    ApparentPower(u8),
     FlowTemperature(u8),
     ReturnTemperature(u8),
     TemperatureDifference(u8),
@@ -206,7 +398,9 @@ pub enum VIFExtension {
     CumulativeCountMaxPower(u8),
 }
 
+#[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Unit {
     HourMinuteSecond,
     DayMonthYear,
@@ -244,14 +438,130 @@ pub enum Unit {
     Hours,
     Days,
     JoulPerHour,
-    ActualityDuration,
     TimePoint,
     FabricationNumber,
     MegaWatt,
     PlainText,
+    LocalCurrency,
+    Volt,
+    Ampere,
+    Baud,
+    BitTimes,
+    Months,
+    Years,
+    DecibelMilliwatt,
+    KiloVoltAmpereReactiveHour,
+    KiloVoltAmpereHour,
+    MegaCalorie,
+    KiloVoltAmpereReactive,
+    KiloVoltAmpere,
+    Tonne,
+    Percent,
+    CubicFeet,
+    AmericanGallon,
+    AmericanGallonPerMinute,
+    AmericanGallonPerHour,
+    Degree,
+    Hertz,
+    Fahrenheit,
+}
+
+impl Unit {
+    /// The symbol of the unit, empty for values without a physical unit like
+    /// identifiers, points in time or plain text units
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::HourMinuteSecond
+            | Unit::DayMonthYear
+            | Unit::TimePoint
+            | Unit::FabricationNumber
+            | Unit::PlainText
+            | Unit::WithoutUnits
+            | Unit::Reserved => "",
+            Unit::WattHour => "Wh",
+            Unit::KiloWattHour => "kWh",
+            Unit::MegaWattHour => "MWh",
+            Unit::Joul => "J",
+            Unit::KiloJoul => "kJ",
+            Unit::MegaJoul => "MJ",
+            Unit::GigaJoul => "GJ",
+            Unit::Kilogram => "kg",
+            Unit::Tonne => "t",
+            Unit::Watt => "W",
+            Unit::KiloWatt => "kW",
+            Unit::MegaWat | Unit::MegaWatt => "MW",
+            Unit::JoulPerHour => "J/h",
+            Unit::KiloJoulHour => "kJ/h",
+            Unit::MegaJoulHour => "MJ/h",
+            Unit::GigaJoulHour => "GJ/h",
+            Unit::Liter => "l",
+            Unit::MegaLiter => "Ml",
+            Unit::CubicMeter => "m³",
+            Unit::LiterHour => "l/h",
+            Unit::MegaLiterHour => "Ml/h",
+            Unit::CubicMeterPerHour => "m³/h",
+            Unit::CubicMeterPerMinute => "m³/min",
+            Unit::CubicMeterPerSecond => "m³/s",
+            Unit::KilogramPerHour => "kg/h",
+            Unit::Celsius => "°C",
+            Unit::Kelvin => "K",
+            Unit::Fahrenheit => "°F",
+            Unit::Bar => "bar",
+            Unit::HCA => "HCA",
+            Unit::Seconds => "s",
+            Unit::Minutes => "min",
+            Unit::Hours => "h",
+            Unit::Days => "d",
+            Unit::Months => "months",
+            Unit::Years => "years",
+            Unit::LocalCurrency => "currency units",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Baud => "Bd",
+            Unit::BitTimes => "bit times",
+            Unit::DecibelMilliwatt => "dBm",
+            Unit::KiloVoltAmpereReactiveHour => "kvarh",
+            Unit::KiloVoltAmpereHour => "kVAh",
+            Unit::KiloVoltAmpereReactive => "kvar",
+            Unit::KiloVoltAmpere => "kVA",
+            Unit::MegaCalorie => "Mcal",
+            Unit::Percent => "%",
+            Unit::CubicFeet => "ft³",
+            Unit::AmericanGallon => "gal",
+            Unit::AmericanGallonPerMinute => "gal/min",
+            Unit::AmericanGallonPerHour => "gal/h",
+            Unit::Degree => "°",
+            Unit::Hertz => "Hz",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
 }
 
-impl TryFrom<&ValueInformation> for Unit {
+// The two lowest bits of a duration VIF(E) select its unit
+fn duration_unit(nn: u8) -> Unit {
+    match nn & 0b11 {
+        0b00 => Unit::Seconds,
+        0b01 => Unit::Minutes,
+        0b10 => Unit::Hours,
+        _ => Unit::Days,
+    }
+}
+
+fn long_duration_unit(nn: u8) -> Unit {
+    match nn & 0b11 {
+        0b00 => Unit::Hours,
+        0b01 => Unit::Days,
+        0b10 => Unit::Months,
+        _ => Unit::Years,
+    }
+}
+
+impl TryFrom<&ValueInformation<'_>> for Unit {
     type Error = ValueInformationError;
 
     fn try_from(value_information: &ValueInformation) -> Result<Self, ValueInformationError> {
@@ -274,36 +584,140 @@ impl TryFrom<&ValueInformation> for Unit {
                 0x58..=0x5F | 0x64..=0x67 => Ok(Unit::Celsius),
                 0x60..=0x63 => Ok(Unit::Kelvin),
                 0x68..=0x6B => Ok(Unit::Bar),
-                0x6C..=0x6D => Ok(Unit::TimePoint),
-                0x74..=0x77 => Ok(Unit::ActualityDuration),
+                0x6C => Ok(Unit::DayMonthYear),
+                0x6D => Ok(Unit::TimePoint),
+                0x6E => Ok(Unit::HCA),
+                0x70 | 0x74 => Ok(Unit::Seconds),
+                0x71 | 0x75 => Ok(Unit::Minutes),
+                0x72 | 0x76 => Ok(Unit::Hours),
+                0x73 | 0x77 => Ok(Unit::Days),
                 0x78 => Ok(Unit::FabricationNumber),
-                _ => todo!("Implement the rest of the units: {:?}", x),
+                0x79..=0x7A => Ok(Unit::WithoutUnits),
+                0x6F | 0x7B..=0xFF => Ok(Unit::Reserved),
             },
             ValueInformation::PlainText(_) => Ok(Unit::PlainText),
             ValueInformation::Extended(x) => match x {
+                VIFExtension::CreditOfCurrencyUnits(_) | VIFExtension::DebitOfCurrencyUnits(_) => {
+                    Ok(Unit::LocalCurrency)
+                }
+                VIFExtension::AccessNumber
+                | VIFExtension::Medium
+                | VIFExtension::Manufacturer
+                | VIFExtension::ParameterSetIdentification
+                | VIFExtension::ModelVersion
+                | VIFExtension::HardwareVersion
+                | VIFExtension::FirmwareVersion
+                | VIFExtension::SoftwareVersion
+                | VIFExtension::CustomerLocation
+                | VIFExtension::Customer
+                | VIFExtension::AccessCodeUser
+                | VIFExtension::AccessCodeOperator
+                | VIFExtension::AccessCodeSystemOperator
+                | VIFExtension::AccessCodeDeveloper
+                | VIFExtension::Password
+                | VIFExtension::ErrorFlags
+                | VIFExtension::ErrorMask
+                | VIFExtension::SecurityKey
+                | VIFExtension::DigitalOutput
+                | VIFExtension::DigitalInput
+                | VIFExtension::Retry
+                | VIFExtension::RemoteControl
+                | VIFExtension::FirstStorage
+                | VIFExtension::LastStorage
+                | VIFExtension::SizeOfStorageBlock
+                | VIFExtension::OperatorSpecificData
+                | VIFExtension::Dimensionless
+                | VIFExtension::DataContainerWirelessMBus
+                | VIFExtension::ResetCounter
+                | VIFExtension::CumulationCounter
+                | VIFExtension::ControlSignal
+                | VIFExtension::DayOfWeek
+                | VIFExtension::WeekNumber
+                | VIFExtension::StateOfParameterActivation
+                | VIFExtension::SpecialSupervision
+                | VIFExtension::DaylightSaving
+                | VIFExtension::ListeningWindowManagement
+                | VIFExtension::NumberOfTimesMeterStopped
+                | VIFExtension::DataContainerManufacturerSpecific
+                | VIFExtension::Reserved => Ok(Unit::WithoutUnits),
+                VIFExtension::BaudRate => Ok(Unit::Baud),
+                VIFExtension::ResponseDelayTime => Ok(Unit::BitTimes),
+                VIFExtension::StorageIntervalSecondsToDays(nn)
+                | VIFExtension::DurationSinceLastReadout(nn)
+                | VIFExtension::DurationOfTariff(nn)
+                | VIFExtension::PeriodOfTariff(nn)
+                | VIFExtension::PeriodOfNominalDataTransmissions(nn) => Ok(duration_unit(*nn)),
+                VIFExtension::DurationSinceLastCumulation(nn)
+                | VIFExtension::OperatingTimeBattery(nn) => Ok(long_duration_unit(*nn)),
+                VIFExtension::StorageIntervalMonths | VIFExtension::PeriodOfTarrifMonths => {
+                    Ok(Unit::Months)
+                }
+                VIFExtension::StorageIntervalYears | VIFExtension::PeriodOfTTariffYears => {
+                    Ok(Unit::Years)
+                }
+                VIFExtension::TimePointSecond => Ok(Unit::Seconds),
+                VIFExtension::StartOfTariff
+                | VIFExtension::TimePointOfDay
+                | VIFExtension::DateAndTimeOfBatteryChange => Ok(Unit::TimePoint),
+                VIFExtension::Volts(_) => Ok(Unit::Volt),
+                VIFExtension::Ampere(_) => Ok(Unit::Ampere),
+                VIFExtension::RFLevel => Ok(Unit::DecibelMilliwatt),
+                VIFExtension::RemainingBatteryLifeTime => Ok(Unit::Days),
                 VIFExtension::EnergyMWh(_) => Ok(Unit::MegaWattHour),
+                VIFExtension::ReactiveEnergy(_) => Ok(Unit::KiloVoltAmpereReactiveHour),
+                VIFExtension::ApparentEnergy(_) => Ok(Unit::KiloVoltAmpereHour),
                 VIFExtension::EnergyGJ(_) => Ok(Unit::GigaJoul),
+                VIFExtension::EnergyMCal(_) => Ok(Unit::MegaCalorie),
                 VIFExtension::VolumeM3(_) => Ok(Unit::CubicMeter),
+                VIFExtension::ReactivePower(_) => Ok(Unit::KiloVoltAmpereReactive),
+                VIFExtension::MassTons(_) => Ok(Unit::Tonne),
+                VIFExtension::RelativeHumidity(_) => Ok(Unit::Percent),
+                VIFExtension::VolumeFeet3 | VIFExtension::VolumeFeet3Tenth => Ok(Unit::CubicFeet),
+                VIFExtension::VolumeAmericanGallonTenth | VIFExtension::VolumeAmericanGallon => {
+                    Ok(Unit::AmericanGallon)
+                }
+                VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth
+                | VIFExtension::VolumeFlowAmericanGallonPerMinute => {
+                    Ok(Unit::AmericanGallonPerMinute)
+                }
+                VIFExtension::VolumeFlowAmericanGallonPerHour => Ok(Unit::AmericanGallonPerHour),
                 VIFExtension::PowerMW(_) => Ok(Unit::MegaWatt),
+                VIFExtension::PhaseVoltageToVoltage | VIFExtension::PhaseVoltageToCurrent => {
+                    Ok(Unit::Degree)
+                }
+                VIFExtension::Frequency(_) => Ok(Unit::Hertz),
                 VIFExtension::PowerGJH(_) => Ok(Unit::GigaJoulHour),
-                VIFExtension::FlowTemperature(_) => Ok(Unit::Celsius),
-                VIFExtension::ReturnTemperature(_) => Ok(Unit::Celsius),
-                VIFExtension::TemperatureDifference(_) => Ok(Unit::Celsius),
-                VIFExtension::ExternalTemperature(_) => Ok(Unit::Celsius),
-                VIFExtension::ColdWarmTemperatureLimitFarenheit(_) => Ok(Unit::Celsius),
+                VIFExtension::ApparentPower(_) => Ok(Unit::KiloVoltAmpere),
+                VIFExtension::FlowTemperature(_)
+                | VIFExtension::ReturnTemperature(_)
+                | VIFExtension::TemperatureDifference(_)
+                | VIFExtension::ExternalTemperature(_)
+                | VIFExtension::ColdWarmTemperatureLimitFarenheit(_) => Ok(Unit::Fahrenheit),
                 VIFExtension::ColdWarmTemperatureLimitCelsius(_) => Ok(Unit::Celsius),
                 VIFExtension::CumulativeCountMaxPower(_) => Ok(Unit::Watt),
-                VIFExtension::DigitalInput => Ok(Unit::WithoutUnits),
-                _ => todo!("Implement the rest of the units: {:?}", x),
             },
-            ValueInformation::Any => todo!(),
-            ValueInformation::ManufacturerSpecific => todo!(),
+            ValueInformation::Any | ValueInformation::ManufacturerSpecific => {
+                Ok(Unit::WithoutUnits)
+            }
         }
     }
 }
 
 mod tests {
 
+    #[test]
+    fn test_unit_display() {
+        extern crate std;
+        use std::string::ToString;
+
+        use crate::user_data::value_information::Unit;
+
+        assert_eq!(Unit::KiloWattHour.to_string(), "kWh");
+        assert_eq!(Unit::CubicMeterPerHour.to_string(), "m³/h");
+        assert_eq!(Unit::Celsius.to_string(), "°C");
+        assert_eq!(Unit::FabricationNumber.to_string(), "");
+    }
+
     #[test]
     fn test_value_information_new() {
         use crate::user_data::value_information::Unit;
@@ -338,14 +752,15 @@ mod tests {
         assert_eq!(result.get_size(), 1);
     }
 
-    //
-    // To solve this issue the parser needs to be configurable
-    // it should try to parse according to mbus and if it fails it should try to parse
-    // with the wrong, but common, method
+    // Meters disagree on where the VIFE of a plain text VIF goes, which is why
+    // the layout is configurable. The default tries the norm conform layout
+    // first and falls back to the wrong, but common, one.
     #[test]
     fn test_plain_text_vif_common_none_norm_conform() {
-        use crate::user_data::value_information::ValueInformation;
-        use arrayvec::ArrayVec;
+        use crate::user_data::data_information::AsciiText;
+        use crate::user_data::value_information::{
+            PlainTextLayout, ValueInformation, ValueInformationBlock, ValueInformationError,
+        };
         // This is how the VIF is encoded in the test vectors
         // It is however none norm conform, see the next example which follows
         // the MBUS Norm which explicitly states that the VIIFE should be after the VIF
@@ -355,18 +770,31 @@ mod tests {
         // %RH
         // VIFE = 0x74 => E111 0nnn Multiplicative correction factor for value (not unit): 10nnn–6 => 10^-2
         let data = [0xFC, 0x03, 0x48, 0x52, 0x25, 0x74];
-        let mut a = ArrayVec::<u8, 10>::new();
-        a.try_extend_from_slice(&data[2..5]).unwrap();
-        a.reverse();
-        let result = ValueInformation::try_from(data.as_slice()).unwrap();
-        assert_eq!(result, ValueInformation::PlainText(a));
+        let result = ValueInformationBlock::try_from(data.as_slice()).unwrap();
+        assert_eq!(
+            result.value_information,
+            ValueInformation::PlainText(AsciiText { bytes: &data[2..5] })
+        );
+        assert_eq!(
+            result.value_information_extension.as_deref(),
+            Some([0x74].as_slice())
+        );
+        assert_eq!(result.get_size(), 6);
+
+        let result = ValueInformationBlock::parse(&data, PlainTextLayout::Common).unwrap();
         assert_eq!(result.get_size(), 6);
+        assert_eq!(
+            ValueInformationBlock::parse(&data, PlainTextLayout::Strict),
+            Err(ValueInformationError::DataTooShort)
+        );
     }
 
     #[test]
     fn test_plain_text_vif_norm_conform() {
-        use crate::user_data::value_information::ValueInformation;
-        use arrayvec::ArrayVec;
+        use crate::user_data::data_information::AsciiText;
+        use crate::user_data::value_information::{
+            PlainTextLayout, ValueInformation, ValueInformationBlock,
+        };
         // This is the ascii conform method of encoding the VIF
         // VIF  VIFE  LEN(3) 'R'   'H'  '%'
         // 0xFC, 0x74, 0x03, 0x48, 0x52, 0x25,
@@ -375,11 +803,33 @@ mod tests {
         // VIFE = 0x74 => E111 0nnn Multiplicative correction factor for value (not unit): 10nnn–6 => 10^-2
         //
         let data = [0xFC, 0x74, 0x03, 0x48, 0x52, 0x25];
-        let mut a = ArrayVec::<u8, 10>::new();
-        a.try_extend_from_slice(&data[2..5]).unwrap();
-        a.reverse();
-        let result = ValueInformation::try_from(data.as_slice()).unwrap();
-        assert_eq!(result, ValueInformation::PlainText(a));
+        let result = ValueInformationBlock::try_from(data.as_slice()).unwrap();
+        assert_eq!(
+            result.value_information,
+            ValueInformation::PlainText(AsciiText { bytes: &data[3..6] })
+        );
+        assert_eq!(
+            result.value_information_extension.as_deref(),
+            Some([0x74].as_slice())
+        );
         assert_eq!(result.get_size(), 6);
+
+        let result = ValueInformationBlock::parse(&data, PlainTextLayout::Strict).unwrap();
+        assert_eq!(result.get_size(), 6);
+        assert!(ValueInformationBlock::parse(&data, PlainTextLayout::Common).is_err());
+    }
+
+    #[test]
+    fn test_primary_vif_with_extensions() {
+        use crate::user_data::value_information::{ValueInformation, ValueInformationBlock};
+        // VIF 0x93 (volume 10^-3 m^3) followed by the VIFEs 0xBC and 0x3C
+        let data = [0x93, 0xBC, 0x3C, 0x00];
+        let result = ValueInformationBlock::try_from(data.as_slice()).unwrap();
+        assert_eq!(result.value_information, ValueInformation::Primary(0x93));
+        assert_eq!(
+            result.value_information_extension.as_deref(),
+            Some([0xBC, 0x3C].as_slice())
+        );
+        assert_eq!(result.get_size(), 3);
     }
 }
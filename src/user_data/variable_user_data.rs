@@ -1,20 +1,34 @@
-use super::data_information::FunctionField;
-use super::data_information::{self, DataInformationField};
-use super::value_information::{self, Unit, ValueInformation};
-use super::DataRecords;
+use core::fmt;
+
+use super::data_information::{self, DataInformationError, DataInformationField};
+use super::data_information::{AsciiText, DataFieldCoding, DataValue, FunctionField, Value};
+use super::date_time::{Date, DateTime, DaylightSaving, ListeningWindow, Time};
+use super::decimal::{Decimal, DecimalError};
+use super::manufacturer_specific::{find_decoder, ManufacturerDecoder};
+use super::unit_conversion::{scale, BaseUnit, UnitConversionError};
+use super::value_information::{self, Unit, VIFExtension, ValueInformation, ValueInformationBlock};
+use super::{DataRecords, FixedDataHeader, ManufacturerCode, Medium, ParserOptions};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct DataRecord {
+pub struct DataRecord<'a> {
     pub function: FunctionField,
     pub storage_number: u64,
+    pub tariff: u64,
+    pub sub_unit: u32,
     pub unit: Unit,
+    pub plain_text_unit: Option<AsciiText<'a>>,
+    pub raw_data_information: &'a [u8],
+    pub raw_value_information: &'a [u8],
+    pub raw_data: &'a [u8],
     pub exponent: Exponent,
     pub quantity: Quantity,
-    pub value: f64,
+    pub value: DataValue<'a>,
     pub size: usize,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Exponent {
     pub inner: Option<isize>,
 }
@@ -24,36 +38,139 @@ impl From<isize> for Exponent {
     }
 }
 
-impl From<&ValueInformation> for Quantity {
+impl From<&ValueInformation<'_>> for Quantity {
     fn from(value_information: &ValueInformation) -> Quantity {
         match value_information {
-            ValueInformation::Primary(x) => match x {
+            ValueInformation::Primary(x) => match x & 0x7F {
                 0x00..=0x0F => Quantity::Energy,
                 0x10..=0x17 => Quantity::Volume,
                 0x18..=0x1F => Quantity::Mass,
-                0x20..=0x27 => Quantity::Duration,
+                0x20..=0x23 => Quantity::OnTime,
+                0x24..=0x27 => Quantity::OperatingTime,
                 0x28..=0x37 => Quantity::Power,
                 0x38..=0x4F => Quantity::VolumeFlow,
                 0x50..=0x57 => Quantity::MassFlow,
-                0x58..=0x67 => Quantity::Temperature,
+                0x58..=0x5B => Quantity::FlowTemperature,
+                0x5C..=0x5F => Quantity::ReturnTemperature,
+                0x60..=0x63 => Quantity::TemperatureDifference,
+                0x64..=0x67 => Quantity::ExternalTemperature,
                 0x68..=0x6B => Quantity::Pressure,
                 0x6C..=0x6D => Quantity::TimePoint,
-                0x74..=0x77 => Quantity::Duration,
+                0x6E => Quantity::HCA,
+                0x70..=0x73 => Quantity::AveragingDuration,
+                0x74..=0x77 => Quantity::ActualityDuration,
                 0x78 => Quantity::IdentificationNumber,
-                _ => todo!("Implement the rest of the units: {:?}", x),
+                0x79 => Quantity::EnhancedIdentification,
+                0x7A => Quantity::BusAddress,
+                0x6F | 0x7B..=0xFF => Quantity::Reserved,
             },
             ValueInformation::PlainText(_) => Quantity::PlainText,
             ValueInformation::Extended(x) => match x {
-                value_information::VIFExtension::DigitalInput => Quantity::BinaryDigitalInput,
-                _ => todo!("Implement the rest of the units: {:?}", x),
+                VIFExtension::CreditOfCurrencyUnits(_) => Quantity::Credit,
+                VIFExtension::DebitOfCurrencyUnits(_) => Quantity::Debit,
+                VIFExtension::AccessNumber => Quantity::AccessNumber,
+                VIFExtension::Medium => Quantity::Medium,
+                VIFExtension::Manufacturer => Quantity::Manufacturer,
+                VIFExtension::ParameterSetIdentification => Quantity::ParameterSetIdentification,
+                VIFExtension::ModelVersion => Quantity::ModelVersion,
+                VIFExtension::HardwareVersion => Quantity::HardwareVersion,
+                VIFExtension::FirmwareVersion => Quantity::FirmwareVersion,
+                VIFExtension::SoftwareVersion => Quantity::SoftwareVersion,
+                VIFExtension::CustomerLocation => Quantity::CustomerLocation,
+                VIFExtension::Customer => Quantity::Customer,
+                VIFExtension::AccessCodeUser
+                | VIFExtension::AccessCodeOperator
+                | VIFExtension::AccessCodeSystemOperator
+                | VIFExtension::AccessCodeDeveloper => Quantity::AccessCode,
+                VIFExtension::Password => Quantity::Password,
+                VIFExtension::ErrorFlags => Quantity::ErrorFlags,
+                VIFExtension::ErrorMask => Quantity::ErrorMask,
+                VIFExtension::SecurityKey => Quantity::SecurityKey,
+                VIFExtension::DigitalOutput => Quantity::BinaryDigitalOutput,
+                VIFExtension::DigitalInput => Quantity::BinaryDigitalInput,
+                VIFExtension::BaudRate => Quantity::BaudRate,
+                VIFExtension::ResponseDelayTime => Quantity::ResponseDelayTime,
+                VIFExtension::Retry => Quantity::Retry,
+                VIFExtension::RemoteControl => Quantity::RemoteControl,
+                VIFExtension::FirstStorage => Quantity::FirstStorageNumber,
+                VIFExtension::LastStorage => Quantity::LastStorageNumber,
+                VIFExtension::SizeOfStorageBlock => Quantity::SizeOfStorageBlock,
+                VIFExtension::StorageIntervalSecondsToDays(_)
+                | VIFExtension::StorageIntervalMonths
+                | VIFExtension::StorageIntervalYears => Quantity::StorageInterval,
+                VIFExtension::OperatorSpecificData => Quantity::OperatorSpecificData,
+                VIFExtension::TimePointSecond
+                | VIFExtension::StartOfTariff
+                | VIFExtension::TimePointOfDay
+                | VIFExtension::DateAndTimeOfBatteryChange => Quantity::TimePoint,
+                VIFExtension::DurationSinceLastReadout(_) => Quantity::DurationSinceLastReadout,
+                VIFExtension::DurationOfTariff(_) => Quantity::DurationOfTariff,
+                VIFExtension::PeriodOfTariff(_)
+                | VIFExtension::PeriodOfTarrifMonths
+                | VIFExtension::PeriodOfTTariffYears => Quantity::PeriodOfTariff,
+                VIFExtension::Dimensionless => Quantity::Dimensionless,
+                VIFExtension::DataContainerWirelessMBus
+                | VIFExtension::DataContainerManufacturerSpecific => Quantity::DataContainer,
+                VIFExtension::PeriodOfNominalDataTransmissions(_) => {
+                    Quantity::PeriodOfNominalDataTransmissions
+                }
+                VIFExtension::Volts(_) => Quantity::Voltage,
+                VIFExtension::Ampere(_) => Quantity::Current,
+                VIFExtension::ResetCounter => Quantity::ResetCounter,
+                VIFExtension::CumulationCounter => Quantity::CumulationCounter,
+                VIFExtension::ControlSignal => Quantity::ControlSignal,
+                VIFExtension::DayOfWeek => Quantity::DayOfWeek,
+                VIFExtension::WeekNumber => Quantity::WeekNumber,
+                VIFExtension::StateOfParameterActivation => Quantity::StateOfParameterActivation,
+                VIFExtension::SpecialSupervision => Quantity::SpecialSupplierInformation,
+                VIFExtension::DurationSinceLastCumulation(_) => {
+                    Quantity::DurationSinceLastCumulation
+                }
+                VIFExtension::OperatingTimeBattery(_) => Quantity::OperatingTimeBattery,
+                VIFExtension::RFLevel => Quantity::RFLevel,
+                VIFExtension::DaylightSaving => Quantity::DaylightSaving,
+                VIFExtension::ListeningWindowManagement => Quantity::ListeningWindowManagement,
+                VIFExtension::RemainingBatteryLifeTime => Quantity::RemainingBatteryLifeTime,
+                VIFExtension::NumberOfTimesMeterStopped => Quantity::NumberOfTimesMeterStopped,
+                VIFExtension::Reserved => Quantity::Reserved,
+                VIFExtension::EnergyMWh(_)
+                | VIFExtension::EnergyGJ(_)
+                | VIFExtension::EnergyMCal(_) => Quantity::Energy,
+                VIFExtension::ReactiveEnergy(_) => Quantity::ReactiveEnergy,
+                VIFExtension::ApparentEnergy(_) => Quantity::ApparentEnergy,
+                VIFExtension::VolumeM3(_)
+                | VIFExtension::VolumeFeet3
+                | VIFExtension::VolumeFeet3Tenth
+                | VIFExtension::VolumeAmericanGallonTenth
+                | VIFExtension::VolumeAmericanGallon => Quantity::Volume,
+                VIFExtension::ReactivePower(_) => Quantity::ReactivePower,
+                VIFExtension::MassTons(_) => Quantity::Mass,
+                VIFExtension::RelativeHumidity(_) => Quantity::RelativeHumidity,
+                VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth
+                | VIFExtension::VolumeFlowAmericanGallonPerMinute
+                | VIFExtension::VolumeFlowAmericanGallonPerHour => Quantity::VolumeFlow,
+                VIFExtension::PowerMW(_) | VIFExtension::PowerGJH(_) => Quantity::Power,
+                VIFExtension::PhaseVoltageToVoltage => Quantity::PhaseVoltageToVoltage,
+                VIFExtension::PhaseVoltageToCurrent => Quantity::PhaseVoltageToCurrent,
+                VIFExtension::Frequency(_) => Quantity::Frequency,
+                VIFExtension::ApparentPower(_) => Quantity::ApparentPower,
+                VIFExtension::FlowTemperature(_) => Quantity::FlowTemperature,
+                VIFExtension::ReturnTemperature(_) => Quantity::ReturnTemperature,
+                VIFExtension::TemperatureDifference(_) => Quantity::TemperatureDifference,
+                VIFExtension::ExternalTemperature(_) => Quantity::ExternalTemperature,
+                VIFExtension::ColdWarmTemperatureLimitFarenheit(_)
+                | VIFExtension::ColdWarmTemperatureLimitCelsius(_) => {
+                    Quantity::ColdWarmTemperatureLimit
+                }
+                VIFExtension::CumulativeCountMaxPower(_) => Quantity::CumulativeCountMaxPower,
             },
-            ValueInformation::Any => todo!(),
-            ValueInformation::ManufacturerSpecific => todo!(),
+            ValueInformation::Any => Quantity::Any,
+            ValueInformation::ManufacturerSpecific => Quantity::ManufacturerSpecific,
         }
     }
 }
 
-impl From<&ValueInformation> for Exponent {
+impl From<&ValueInformation<'_>> for Exponent {
     fn from(value_information: &ValueInformation) -> Exponent {
         match value_information {
             ValueInformation::Primary(x) => match x & 0x7F {
@@ -61,30 +178,151 @@ impl From<&ValueInformation> for Exponent {
                     Exponent::from((x & 0b111) as isize - 3)
                 }
                 8..=15 | 0x30..=0x37 => Exponent::from((x & 0b111) as isize),
-                0x10..=0x17 | 0x38..=0x3F | 0x6C..=0x6D => Exponent::from((x & 0b111) as isize - 6),
-                0x20..=0x27 | 0x74..=0x77 => Exponent::from(1),
+                0x10..=0x17 | 0x38..=0x3F => Exponent::from((x & 0b111) as isize - 6),
+                0x20..=0x27 | 0x70..=0x77 => Exponent::from(0),
                 0x40..=0x47 => Exponent::from((x & 0b111) as isize - 7),
                 0x48..=0x4F => Exponent::from((x & 0b111) as isize - 9),
                 0x58..=0x6B => Exponent::from((x & 0b11) as isize - 3),
-                0x6E..=0x6F | 0x78 => Exponent { inner: None },
-                _ => todo!("Implement the rest of the units: {:?}", x),
+                0x6C..=0x6F | 0x78..=0xFF => Exponent { inner: None },
             },
             ValueInformation::PlainText(_) => Exponent { inner: None },
             ValueInformation::Extended(x) => match x {
-                value_information::VIFExtension::DigitalInput => Exponent { inner: None },
-                _ => todo!("Implement the rest of the units: {:?}", x),
+                VIFExtension::CreditOfCurrencyUnits(nn)
+                | VIFExtension::DebitOfCurrencyUnits(nn) => Exponent::from(*nn as isize - 3),
+                VIFExtension::Volts(n) => Exponent::from(*n as isize - 9),
+                VIFExtension::Ampere(n) => Exponent::from(*n as isize - 12),
+                VIFExtension::BaudRate
+                | VIFExtension::ResponseDelayTime
+                | VIFExtension::StorageIntervalSecondsToDays(_)
+                | VIFExtension::StorageIntervalMonths
+                | VIFExtension::StorageIntervalYears
+                | VIFExtension::TimePointSecond
+                | VIFExtension::DurationSinceLastReadout(_)
+                | VIFExtension::DurationOfTariff(_)
+                | VIFExtension::PeriodOfTariff(_)
+                | VIFExtension::PeriodOfTarrifMonths
+                | VIFExtension::PeriodOfTTariffYears
+                | VIFExtension::Dimensionless
+                | VIFExtension::PeriodOfNominalDataTransmissions(_)
+                | VIFExtension::DurationSinceLastCumulation(_)
+                | VIFExtension::OperatingTimeBattery(_)
+                | VIFExtension::RFLevel
+                | VIFExtension::RemainingBatteryLifeTime => Exponent::from(0),
+                VIFExtension::AccessNumber
+                | VIFExtension::Medium
+                | VIFExtension::Manufacturer
+                | VIFExtension::ParameterSetIdentification
+                | VIFExtension::ModelVersion
+                | VIFExtension::HardwareVersion
+                | VIFExtension::FirmwareVersion
+                | VIFExtension::SoftwareVersion
+                | VIFExtension::CustomerLocation
+                | VIFExtension::Customer
+                | VIFExtension::AccessCodeUser
+                | VIFExtension::AccessCodeOperator
+                | VIFExtension::AccessCodeSystemOperator
+                | VIFExtension::AccessCodeDeveloper
+                | VIFExtension::Password
+                | VIFExtension::ErrorFlags
+                | VIFExtension::ErrorMask
+                | VIFExtension::SecurityKey
+                | VIFExtension::DigitalOutput
+                | VIFExtension::DigitalInput
+                | VIFExtension::Retry
+                | VIFExtension::RemoteControl
+                | VIFExtension::FirstStorage
+                | VIFExtension::LastStorage
+                | VIFExtension::SizeOfStorageBlock
+                | VIFExtension::OperatorSpecificData
+                | VIFExtension::StartOfTariff
+                | VIFExtension::DataContainerWirelessMBus
+                | VIFExtension::ResetCounter
+                | VIFExtension::CumulationCounter
+                | VIFExtension::ControlSignal
+                | VIFExtension::DayOfWeek
+                | VIFExtension::WeekNumber
+                | VIFExtension::TimePointOfDay
+                | VIFExtension::StateOfParameterActivation
+                | VIFExtension::SpecialSupervision
+                | VIFExtension::DateAndTimeOfBatteryChange
+                | VIFExtension::DaylightSaving
+                | VIFExtension::ListeningWindowManagement
+                | VIFExtension::NumberOfTimesMeterStopped
+                | VIFExtension::DataContainerManufacturerSpecific
+                | VIFExtension::Reserved => Exponent { inner: None },
+                VIFExtension::EnergyMWh(n)
+                | VIFExtension::EnergyGJ(n)
+                | VIFExtension::EnergyMCal(n)
+                | VIFExtension::RelativeHumidity(n)
+                | VIFExtension::PowerMW(n)
+                | VIFExtension::PowerGJH(n) => Exponent::from(*n as isize - 1),
+                VIFExtension::ReactiveEnergy(n) | VIFExtension::ApparentEnergy(n) => {
+                    Exponent::from(*n as isize)
+                }
+                VIFExtension::VolumeM3(n) | VIFExtension::MassTons(n) => {
+                    Exponent::from(*n as isize + 2)
+                }
+                VIFExtension::ReactivePower(nn)
+                | VIFExtension::Frequency(nn)
+                | VIFExtension::ApparentPower(nn)
+                | VIFExtension::FlowTemperature(nn)
+                | VIFExtension::ReturnTemperature(nn)
+                | VIFExtension::TemperatureDifference(nn)
+                | VIFExtension::ExternalTemperature(nn)
+                | VIFExtension::ColdWarmTemperatureLimitFarenheit(nn)
+                | VIFExtension::ColdWarmTemperatureLimitCelsius(nn)
+                | VIFExtension::CumulativeCountMaxPower(nn) => Exponent::from(*nn as isize - 3),
+                VIFExtension::VolumeFeet3
+                | VIFExtension::VolumeAmericanGallon
+                | VIFExtension::VolumeFlowAmericanGallonPerMinute
+                | VIFExtension::VolumeFlowAmericanGallonPerHour => Exponent::from(0),
+                VIFExtension::VolumeFeet3Tenth
+                | VIFExtension::VolumeAmericanGallonTenth
+                | VIFExtension::PhaseVoltageToVoltage
+                | VIFExtension::PhaseVoltageToCurrent => Exponent::from(-1),
+                VIFExtension::VolumeFlowAmericanGallonPerMinuteThousandth => Exponent::from(-3),
             },
-            ValueInformation::Any => todo!(),
-            ValueInformation::ManufacturerSpecific => todo!(),
+            ValueInformation::Any | ValueInformation::ManufacturerSpecific => {
+                Exponent { inner: None }
+            }
+        }
+    }
+}
+
+// The combinable VIFEs 0x70-0x77 and 0x7D correct the exponent given by the VIF,
+// the VIFEs following a manufacturer specific VIF are manufacturer specific too
+impl From<&ValueInformationBlock<'_>> for Exponent {
+    fn from(value_information_block: &ValueInformationBlock) -> Exponent {
+        let exponent = Exponent::from(&value_information_block.value_information);
+        if value_information_block.value_information == ValueInformation::ManufacturerSpecific {
+            return exponent;
+        }
+        let correction = value_information_block
+            .value_information_extension
+            .iter()
+            .flatten()
+            .map(|vife| match vife & 0x7F {
+                0x70..=0x77 => (vife & 0b111) as isize - 6,
+                0x7D => 3,
+                _ => 0,
+            })
+            .sum::<isize>();
+        match exponent.inner {
+            _ if correction == 0 => exponent,
+            Some(inner) => Exponent::from(inner + correction),
+            None => Exponent::from(correction),
         }
     }
 }
 
+#[repr(C)]
 #[derive(Debug, Copy, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Quantity {
     Volume,
     Energy,
     ManufacturerSpecific,
+    Any,
     ErrorFlags,
     TimePoint,
     VolumeFlow,
@@ -92,23 +330,200 @@ pub enum Quantity {
     Mass,
     Temperature,
     FlowTemperature,
+    ReturnTemperature,
     TemperatureDifference,
     BinaryDigitalInput,
     RelativeHumidity,
+    OnTime,
+    OperatingTime,
     AveragingDuration,
+    ActualityDuration,
     ExternalTemperature,
     Duration,
     Power,
     Pressure,
+    HCA,
     IdentificationNumber,
+    EnhancedIdentification,
+    BusAddress,
     PlainText,
+    Reserved,
+    Credit,
+    Debit,
+    AccessNumber,
+    Medium,
+    Manufacturer,
+    ParameterSetIdentification,
+    ModelVersion,
+    HardwareVersion,
+    FirmwareVersion,
+    SoftwareVersion,
+    CustomerLocation,
+    Customer,
+    AccessCode,
+    Password,
+    ErrorMask,
+    SecurityKey,
+    BinaryDigitalOutput,
+    BaudRate,
+    ResponseDelayTime,
+    Retry,
+    RemoteControl,
+    FirstStorageNumber,
+    LastStorageNumber,
+    SizeOfStorageBlock,
+    StorageInterval,
+    OperatorSpecificData,
+    DurationSinceLastReadout,
+    DurationOfTariff,
+    PeriodOfTariff,
+    Dimensionless,
+    DataContainer,
+    PeriodOfNominalDataTransmissions,
+    Voltage,
+    Current,
+    ResetCounter,
+    CumulationCounter,
+    ControlSignal,
+    DayOfWeek,
+    WeekNumber,
+    StateOfParameterActivation,
+    SpecialSupplierInformation,
+    DurationSinceLastCumulation,
+    OperatingTimeBattery,
+    RFLevel,
+    DaylightSaving,
+    ListeningWindowManagement,
+    RemainingBatteryLifeTime,
+    NumberOfTimesMeterStopped,
+    ReactiveEnergy,
+    ApparentEnergy,
+    ReactivePower,
+    ApparentPower,
+    PhaseVoltageToVoltage,
+    PhaseVoltageToCurrent,
+    Frequency,
+    ColdWarmTemperatureLimit,
+    CumulativeCountMaxPower,
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Quantity::Volume => "Volume",
+            Quantity::Energy => "Energy",
+            Quantity::ManufacturerSpecific => "Manufacturer specific",
+            Quantity::Any => "Any",
+            Quantity::ErrorFlags => "Error flags",
+            Quantity::TimePoint => "Time point",
+            Quantity::VolumeFlow => "Volume flow",
+            Quantity::MassFlow => "Mass flow",
+            Quantity::Mass => "Mass",
+            Quantity::Temperature => "Temperature",
+            Quantity::FlowTemperature => "Flow temperature",
+            Quantity::ReturnTemperature => "Return temperature",
+            Quantity::TemperatureDifference => "Temperature difference",
+            Quantity::BinaryDigitalInput => "Digital input",
+            Quantity::RelativeHumidity => "Relative humidity",
+            Quantity::OnTime => "On time",
+            Quantity::OperatingTime => "Operating time",
+            Quantity::AveragingDuration => "Averaging duration",
+            Quantity::ActualityDuration => "Actuality duration",
+            Quantity::ExternalTemperature => "External temperature",
+            Quantity::Duration => "Duration",
+            Quantity::Power => "Power",
+            Quantity::Pressure => "Pressure",
+            Quantity::HCA => "Heat cost allocation",
+            Quantity::IdentificationNumber => "Identification number",
+            Quantity::EnhancedIdentification => "Enhanced identification",
+            Quantity::BusAddress => "Bus address",
+            Quantity::PlainText => "Plain text",
+            Quantity::Reserved => "Reserved",
+            Quantity::Credit => "Credit",
+            Quantity::Debit => "Debit",
+            Quantity::AccessNumber => "Access number",
+            Quantity::Medium => "Medium",
+            Quantity::Manufacturer => "Manufacturer",
+            Quantity::ParameterSetIdentification => "Parameter set identification",
+            Quantity::ModelVersion => "Model version",
+            Quantity::HardwareVersion => "Hardware version",
+            Quantity::FirmwareVersion => "Firmware version",
+            Quantity::SoftwareVersion => "Software version",
+            Quantity::CustomerLocation => "Customer location",
+            Quantity::Customer => "Customer",
+            Quantity::AccessCode => "Access code",
+            Quantity::Password => "Password",
+            Quantity::ErrorMask => "Error mask",
+            Quantity::SecurityKey => "Security key",
+            Quantity::BinaryDigitalOutput => "Digital output",
+            Quantity::BaudRate => "Baud rate",
+            Quantity::ResponseDelayTime => "Response delay time",
+            Quantity::Retry => "Retry",
+            Quantity::RemoteControl => "Remote control",
+            Quantity::FirstStorageNumber => "First storage number",
+            Quantity::LastStorageNumber => "Last storage number",
+            Quantity::SizeOfStorageBlock => "Size of storage block",
+            Quantity::StorageInterval => "Storage interval",
+            Quantity::OperatorSpecificData => "Operator specific data",
+            Quantity::DurationSinceLastReadout => "Duration since last readout",
+            Quantity::DurationOfTariff => "Duration of tariff",
+            Quantity::PeriodOfTariff => "Period of tariff",
+            Quantity::Dimensionless => "Dimensionless",
+            Quantity::DataContainer => "Data container",
+            Quantity::PeriodOfNominalDataTransmissions => "Period of nominal data transmissions",
+            Quantity::Voltage => "Voltage",
+            Quantity::Current => "Current",
+            Quantity::ResetCounter => "Reset counter",
+            Quantity::CumulationCounter => "Cumulation counter",
+            Quantity::ControlSignal => "Control signal",
+            Quantity::DayOfWeek => "Day of week",
+            Quantity::WeekNumber => "Week number",
+            Quantity::StateOfParameterActivation => "State of parameter activation",
+            Quantity::SpecialSupplierInformation => "Special supplier information",
+            Quantity::DurationSinceLastCumulation => "Duration since last cumulation",
+            Quantity::OperatingTimeBattery => "Operating time battery",
+            Quantity::RFLevel => "RF level",
+            Quantity::DaylightSaving => "Daylight saving",
+            Quantity::ListeningWindowManagement => "Listening window management",
+            Quantity::RemainingBatteryLifeTime => "Remaining battery life time",
+            Quantity::NumberOfTimesMeterStopped => "Number of times the meter was stopped",
+            Quantity::ReactiveEnergy => "Reactive energy",
+            Quantity::ApparentEnergy => "Apparent energy",
+            Quantity::ReactivePower => "Reactive power",
+            Quantity::ApparentPower => "Apparent power",
+            Quantity::PhaseVoltageToVoltage => "Phase voltage to voltage",
+            Quantity::PhaseVoltageToCurrent => "Phase voltage to current",
+            Quantity::Frequency => "Frequency",
+            Quantity::ColdWarmTemperatureLimit => "Cold / warm temperature limit",
+            Quantity::CumulativeCountMaxPower => "Cumulative count of maximum power",
+        };
+        f.write_str(name)
+    }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataRecordError {
     DataInformationError(data_information::DataInformationError),
+    ValueInformationError(value_information::ValueInformationError),
+}
+
+impl fmt::Display for DataRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataRecordError::DataInformationError(error) => {
+                write!(f, "Invalid data information: {}", error)
+            }
+            DataRecordError::ValueInformationError(error) => {
+                write!(f, "Invalid value information: {}", error)
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for DataRecordError {}
+
 impl From<data_information::DataInformationError> for DataRecordError {
     fn from(error: data_information::DataInformationError) -> Self {
         DataRecordError::DataInformationError(error)
@@ -116,87 +531,489 @@ impl From<data_information::DataInformationError> for DataRecordError {
 }
 
 impl From<value_information::ValueInformationError> for DataRecordError {
-    fn from(_error: value_information::ValueInformationError) -> Self {
-        DataRecordError::DataInformationError(data_information::DataInformationError::NoData)
+    fn from(error: value_information::ValueInformationError) -> Self {
+        DataRecordError::ValueInformationError(error)
     }
 }
 
-impl TryFrom<&[u8]> for DataRecord {
-    type Error = DataRecordError;
-    fn try_from(data: &[u8]) -> Result<DataRecord, DataRecordError> {
+// Integers are signed (type B) unless the VIF describes an identification,
+// a counter or a bit field (types C and D)
+fn is_unsigned(value_information: &ValueInformation) -> bool {
+    match value_information {
+        ValueInformation::Primary(x) => matches!(x & 0x7F, 0x78..=0x7A),
+        ValueInformation::Extended(x) => matches!(
+            x,
+            VIFExtension::AccessNumber
+                | VIFExtension::Medium
+                | VIFExtension::Manufacturer
+                | VIFExtension::ParameterSetIdentification
+                | VIFExtension::ModelVersion
+                | VIFExtension::HardwareVersion
+                | VIFExtension::FirmwareVersion
+                | VIFExtension::SoftwareVersion
+                | VIFExtension::ErrorFlags
+                | VIFExtension::ErrorMask
+                | VIFExtension::SecurityKey
+                | VIFExtension::DigitalOutput
+                | VIFExtension::DigitalInput
+                | VIFExtension::FirstStorage
+                | VIFExtension::LastStorage
+                | VIFExtension::SizeOfStorageBlock
+                | VIFExtension::ResetCounter
+                | VIFExtension::CumulationCounter
+                | VIFExtension::ControlSignal
+                | VIFExtension::DayOfWeek
+                | VIFExtension::WeekNumber
+                | VIFExtension::StateOfParameterActivation
+                | VIFExtension::NumberOfTimesMeterStopped
+        ),
+        _ => false,
+    }
+}
+
+// Time points (VIF 0x6C, 0x6D and their 0xFD counterparts) take their data
+// type from the length of the data field: G (16 bit), J (24 bit), F (32 bit),
+// I (48 bit) or M (variable length). Daylight saving is always type K and the
+// listening window type L. The data field of a manufacturer specific or any
+// VIF record is kept as is.
+fn is_time_point(value_information: &ValueInformation) -> bool {
+    match value_information {
+        ValueInformation::Primary(x) => matches!(x & 0x7F, 0x6C..=0x6D),
+        ValueInformation::Extended(x) => matches!(
+            x,
+            VIFExtension::StartOfTariff | VIFExtension::DateAndTimeOfBatteryChange
+        ),
+        _ => false,
+    }
+}
+
+fn extract_value<'a>(
+    value_information: &ValueInformation,
+    coding: DataFieldCoding,
+    data: &'a [u8],
+) -> Result<Value<'a>, DataInformationError> {
+    let value = if is_unsigned(value_information) {
+        coding.extract_unsigned_from_bytes(data)?
+    } else {
+        coding.extract_from_bytes(data)?
+    };
+    let bytes = &data[..value.byte_size];
+    let data = match (value_information, coding, value.data) {
+        (_, _, DataValue::NoData) => DataValue::NoData,
+        (ValueInformation::ManufacturerSpecific | ValueInformation::Any, _, _) => {
+            DataValue::Raw(bytes)
+        }
+        (vi, DataFieldCoding::Integer16Bit, _) if is_time_point(vi) => {
+            DataValue::Date(Date::from_type_g([bytes[0], bytes[1]]))
+        }
+        (vi, DataFieldCoding::Integer24Bit, _) if is_time_point(vi) => {
+            DataValue::Time(Time::from_type_j([bytes[0], bytes[1], bytes[2]]))
+        }
+        (vi, DataFieldCoding::Integer32Bit, _) if is_time_point(vi) => {
+            DataValue::DateTime(DateTime::from_type_f([
+                bytes[0], bytes[1], bytes[2], bytes[3],
+            ]))
+        }
+        (vi, DataFieldCoding::Integer48Bit, _) if is_time_point(vi) => {
+            DataValue::DateTime(DateTime::from_type_i([
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5],
+            ]))
+        }
+        (vi, DataFieldCoding::VariableLength, _)
+            if is_time_point(vi) && (0xE0..=0xEF).contains(&bytes[0]) =>
+        {
+            DateTime::from_type_m(&bytes[1..]).map_or(value.data, DataValue::DateTime)
+        }
+        (
+            ValueInformation::Extended(VIFExtension::DaylightSaving),
+            DataFieldCoding::Integer32Bit,
+            _,
+        ) => DataValue::DaylightSaving(DaylightSaving::from_type_k([
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ])),
+        (ValueInformation::Extended(VIFExtension::ListeningWindowManagement), _, data) => {
+            match data {
+                DataValue::Raw(slots) | DataValue::Ascii(AsciiText { bytes: slots }) => {
+                    DataValue::ListeningWindow(ListeningWindow::from_type_l(slots))
+                }
+                DataValue::Unsigned(_) | DataValue::Signed(_) => {
+                    DataValue::ListeningWindow(ListeningWindow::from_type_l(bytes))
+                }
+                data => data,
+            }
+        }
+        (ValueInformation::Extended(VIFExtension::Medium), _, DataValue::Unsigned(x)) => {
+            DataValue::Medium(Medium::from_byte(x as u8))
+        }
+        (ValueInformation::Extended(VIFExtension::Manufacturer), _, DataValue::Unsigned(x)) => {
+            ManufacturerCode::from_id(x as u16).map_or(value.data, DataValue::Manufacturer)
+        }
+        (_, _, data) => data,
+    };
+    Ok(Value {
+        data,
+        byte_size: value.byte_size,
+    })
+}
+
+impl<'a> DataRecord<'a> {
+    pub fn parse(data: &'a [u8], options: &ParserOptions) -> Result<Self, DataRecordError> {
         let data_information = DataInformationField::try_from(data)?;
-        let value_information = ValueInformation::try_from(&data[1..])?;
+        let value_information_block = ValueInformationBlock::parse(
+            &data[data_information.get_size()..],
+            options.plain_text_layout,
+        )?;
+        let value_information = &value_information_block.value_information;
         let value_and_data_information_size =
-            data_information.get_size() + value_information.get_size();
-        match value_information {
-            ValueInformation::PlainText(_) => {
-                let plaintext_size = data[value_and_data_information_size] as usize;
-                let total_size = value_and_data_information_size + plaintext_size + 1;
-                Ok(DataRecord {
-                    function: data_information.function_field,
-                    storage_number: data_information.storage_number,
-                    unit: Unit::try_from(&value_information)?,
-                    exponent: Exponent::from(&value_information),
-                    quantity: Quantity::from(&value_information),
-                    value: 0.0,
-                    size: total_size,
-                })
+            data_information.get_size() + value_information_block.get_size();
+        let raw_value_information =
+            &data[data_information.get_size()..value_and_data_information_size];
+        let value = extract_value(
+            value_information,
+            data_information.data_field_coding,
+            &data[value_and_data_information_size..],
+        )?;
+        Ok(DataRecord {
+            function: data_information.function_field,
+            storage_number: data_information.storage_number,
+            tariff: data_information.tariff,
+            sub_unit: data_information.sub_unit,
+            unit: Unit::try_from(value_information)?,
+            plain_text_unit: match value_information {
+                ValueInformation::PlainText(text) => Some(*text),
+                _ => None,
+            },
+            raw_data_information: &data[..data_information.get_size()],
+            raw_value_information,
+            raw_data: &data[value_and_data_information_size..][..value.byte_size],
+            exponent: Exponent::from(&value_information_block),
+            quantity: Quantity::from(value_information),
+            value: value.data,
+            size: value_and_data_information_size + value.byte_size,
+        })
+    }
+
+    /// The numeric value multiplied by 10^exponent, in the unit of the record
+    pub fn scaled_value(&self) -> Option<f64> {
+        let value = self.value.as_f64()?;
+        Some(scale(value, self.exponent.inner.unwrap_or(0)))
+    }
+
+    /// The scaled value converted to `unit`, temperature differences are
+    /// converted as differences
+    pub fn value_in(&self, unit: Unit) -> Result<f64, UnitConversionError> {
+        let value = self
+            .scaled_value()
+            .ok_or(UnitConversionError::NonNumericValue)?;
+        if self.quantity == Quantity::TemperatureDifference {
+            self.unit.convert_difference(value, unit)
+        } else {
+            self.unit.convert(value, unit)
+        }
+    }
+
+    /// The integer or BCD value with the exponent of the record, for values
+    /// which have to be exact, `None` for reals and BCD with invalid digits
+    pub fn decimal_value(&self) -> Option<Decimal> {
+        let mantissa = match self.value {
+            DataValue::Unsigned(x) => x as i128,
+            DataValue::Signed(x) => x as i128,
+            DataValue::Bcd(bcd) if bcd.invalid_digits == 0 => {
+                if bcd.negative {
+                    -(bcd.value as i128)
+                } else {
+                    bcd.value as i128
+                }
             }
-            _ => {
-                let value = data_information
-                    .data_field_coding
-                    .extract_from_bytes(&data[value_and_data_information_size..]);
-                let total_size = value_and_data_information_size + value.byte_size;
-                Ok(DataRecord {
-                    function: data_information.function_field,
-                    storage_number: data_information.storage_number,
-                    unit: Unit::try_from(&value_information)?,
-                    exponent: Exponent::from(&value_information),
-                    quantity: Quantity::from(&value_information),
-                    value: value.data,
-                    size: total_size,
-                })
+            _ => return None,
+        };
+        Some(Decimal::new(mantissa, self.exponent.inner.unwrap_or(0)))
+    }
+
+    /// The decimal value with the next larger prefix of energies and powers
+    /// as long as it is 1000 or more, e.g. 218370 Wh as 218.37 kWh
+    pub fn rescaled_decimal_value(&self) -> Option<(Decimal, Unit)> {
+        let mut decimal = self.decimal_value()?;
+        let mut unit = self.unit;
+        while let Some(larger) = larger_unit(unit).filter(|_| decimal.to_f64().abs() >= 1000.0) {
+            decimal.exponent -= 3;
+            unit = larger;
+        }
+        Some((decimal, unit))
+    }
+
+    /// The exact sum of the values of two records of the same quantity and unit
+    pub fn checked_add(&self, other: &DataRecord) -> Result<Decimal, DecimalError> {
+        let (left, right) = self.decimal_values(other)?;
+        left.checked_add(right).ok_or(DecimalError::Overflow)
+    }
+
+    /// The exact difference of the values of two records of the same quantity
+    /// and unit, e.g. the consumption between two readings
+    pub fn checked_sub(&self, other: &DataRecord) -> Result<Decimal, DecimalError> {
+        let (left, right) = self.decimal_values(other)?;
+        left.checked_sub(right).ok_or(DecimalError::Overflow)
+    }
+
+    fn decimal_values(&self, other: &DataRecord) -> Result<(Decimal, Decimal), DecimalError> {
+        if self.quantity != other.quantity
+            || self.unit != other.unit
+            || self.plain_text_unit != other.plain_text_unit
+        {
+            return Err(DecimalError::DifferentUnits);
+        }
+        match (self.decimal_value(), other.decimal_value()) {
+            (Some(left), Some(right)) => Ok((left, right)),
+            _ => Err(DecimalError::NonDecimalValue),
+        }
+    }
+
+    /// The scaled value normalised to its SI base unit
+    pub fn base_value(&self) -> Result<(f64, BaseUnit), UnitConversionError> {
+        let value = self
+            .scaled_value()
+            .ok_or(UnitConversionError::NonNumericValue)?;
+        if self.quantity == Quantity::TemperatureDifference {
+            let value = self.unit.convert_difference(value, Unit::Kelvin)?;
+            Ok((value, BaseUnit::Kelvin))
+        } else {
+            self.unit.to_base(value)
+        }
+    }
+}
+
+// The unit 1000 times as large, if there is one
+fn larger_unit(unit: Unit) -> Option<Unit> {
+    match unit {
+        Unit::WattHour => Some(Unit::KiloWattHour),
+        Unit::KiloWattHour => Some(Unit::MegaWattHour),
+        Unit::Joul => Some(Unit::KiloJoul),
+        Unit::KiloJoul => Some(Unit::MegaJoul),
+        Unit::MegaJoul => Some(Unit::GigaJoul),
+        Unit::Watt => Some(Unit::KiloWatt),
+        Unit::KiloWatt => Some(Unit::MegaWatt),
+        Unit::JoulPerHour => Some(Unit::KiloJoulHour),
+        Unit::KiloJoulHour => Some(Unit::MegaJoulHour),
+        Unit::MegaJoulHour => Some(Unit::GigaJoulHour),
+        _ => None,
+    }
+}
+
+/// One line like `Energy 218.37 kWh (storage 1, tariff 2, max)`. Integer and
+/// BCD values keep all their digits, energies and powers of 1000 and more
+/// are printed with the next larger prefix, see
+/// [`DataRecord::rescaled_decimal_value`]. Storage number, tariff and
+/// subunit are only printed if they are not 0.
+impl fmt::Display for DataRecord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.quantity)?;
+        let mut unit = Some(self.unit);
+        match (self.rescaled_decimal_value(), self.value) {
+            (Some((decimal, larger)), _) => {
+                unit = Some(larger);
+                write!(f, " {}", decimal)?;
+            }
+            (None, DataValue::Real(_)) => {
+                let mut value = self.scaled_value().unwrap_or(f64::NAN);
+                while let Some(larger) =
+                    unit.and_then(larger_unit).filter(|_| value.abs() >= 1000.0)
+                {
+                    value /= 1000.0;
+                    unit = Some(larger);
+                }
+                // as precise as the transmitted real
+                write!(f, " {}", value as f32)?;
+            }
+            (None, value) => {
+                unit = None;
+                match value {
+                    DataValue::NoData => write!(f, " no data")?,
+                    // Meters send invalid digits like FFFF while they can not measure
+                    DataValue::Bcd(_) => {
+                        f.write_str(" ")?;
+                        for byte in self.raw_data.iter().rev() {
+                            write!(f, "{:02X}", byte)?;
+                        }
+                    }
+                    DataValue::Date(date) => write!(f, " {}", date)?,
+                    DataValue::Time(time) => write!(f, " {}", time)?,
+                    DataValue::DateTime(date_time) => write!(f, " {}", date_time)?,
+                    DataValue::Manufacturer(code) => write!(f, " {}", code)?,
+                    DataValue::Medium(medium) => write!(f, " {}", medium)?,
+                    DataValue::Ascii(text) => write!(f, " {}", text)?,
+                    DataValue::Raw(bytes) => {
+                        f.write_str(" ")?;
+                        for byte in bytes {
+                            write!(f, "{:02X}", byte)?;
+                        }
+                    }
+                    value => write!(f, " {:?}", value)?,
+                }
             }
         }
+        match (self.plain_text_unit, unit) {
+            (Some(text), _) => write!(f, " {}", text)?,
+            (None, Some(unit)) if !unit.symbol().is_empty() => write!(f, " {}", unit)?,
+            _ => {}
+        }
+
+        let function = match self.function {
+            FunctionField::InstantaneousValue => None,
+            FunctionField::MaximumValue => Some("max"),
+            FunctionField::MinimumValue => Some("min"),
+            FunctionField::ValueDuringErrorState => Some("error state"),
+        };
+        let mut separator = " (";
+        for (name, number) in [
+            ("storage", self.storage_number),
+            ("tariff", self.tariff),
+            ("subunit", self.sub_unit as u64),
+        ] {
+            if number != 0 {
+                write!(f, "{}{} {}", separator, name, number)?;
+                separator = ", ";
+            }
+        }
+        if let Some(function) = function {
+            write!(f, "{}{}", separator, function)?;
+            separator = ", ";
+        }
+        if separator == ", " {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized with the value scaled to the unit, exactly as a decimal for
+/// integers and BCD, and the symbol of the unit, e.g. `"218370"` and `"Wh"`.
+/// BCD with invalid digits is serialized as its digits like `"FFFF"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for DataRecord<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut record = serializer.serialize_struct("DataRecord", 9)?;
+        record.serialize_field("function", &self.function)?;
+        record.serialize_field("storage_number", &self.storage_number)?;
+        record.serialize_field("tariff", &self.tariff)?;
+        record.serialize_field("sub_unit", &self.sub_unit)?;
+        record.serialize_field("quantity", &self.quantity)?;
+        record.serialize_field("value", &SerializedValue(self))?;
+        record.serialize_field("unit", self.unit.symbol())?;
+        record.serialize_field("plain_text_unit", &self.plain_text_unit)?;
+        record.serialize_field("size", &self.size)?;
+        record.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SerializedValue<'r, 'a>(&'r DataRecord<'a>);
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SerializedValue<'_, '_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let record = self.0;
+        match (record.decimal_value(), &record.value) {
+            (Some(decimal), _) => decimal.serialize(serializer),
+            (None, DataValue::Real(_)) => record.scaled_value().serialize(serializer),
+            (None, DataValue::Bcd(_)) => {
+                crate::serde_helpers::BcdDigits(record.raw_data).serialize(serializer)
+            }
+            (None, value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for DataRecord<'a> {
+    type Error = DataRecordError;
+    fn try_from(data: &'a [u8]) -> Result<DataRecord<'a>, DataRecordError> {
+        DataRecord::parse(data, &ParserOptions::default())
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VariableUserDataError {
     DataInformationError(DataRecordError),
+    TooManyRecords,
 }
 
+impl fmt::Display for VariableUserDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableUserDataError::DataInformationError(error) => write!(f, "{}", error),
+            VariableUserDataError::TooManyRecords => write!(f, "Too many records"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VariableUserDataError {}
+
 impl From<DataRecordError> for VariableUserDataError {
     fn from(error: DataRecordError) -> Self {
         VariableUserDataError::DataInformationError(error)
     }
 }
 
-impl TryFrom<&[u8]> for DataRecords {
+impl<'a> TryFrom<&'a [u8]> for DataRecords<'a> {
     type Error = VariableUserDataError;
-    fn try_from(data: &[u8]) -> Result<DataRecords, VariableUserDataError> {
+    fn try_from(data: &'a [u8]) -> Result<DataRecords<'a>, VariableUserDataError> {
+        DataRecords::parse(data, &ParserOptions::default())
+    }
+}
+
+impl<'a> DataRecords<'a> {
+    pub fn parse(data: &'a [u8], options: &ParserOptions) -> Result<Self, VariableUserDataError> {
+        Self::parse_records(data, options, None)
+    }
+
+    /// Parses the records and passes the manufacturer specific parts to the
+    /// first decoder of `options` which matches the header
+    pub fn parse_with_header(
+        data: &'a [u8],
+        header: &FixedDataHeader,
+        options: &ParserOptions,
+    ) -> Result<Self, VariableUserDataError> {
+        let decoder =
+            find_decoder(options.manufacturer_decoders, header).map(|decoder| (header, decoder));
+        Self::parse_records(data, options, decoder)
+    }
+
+    fn parse_records(
+        data: &'a [u8],
+        options: &ParserOptions,
+        decoder: Option<(&FixedDataHeader, &dyn ManufacturerDecoder)>,
+    ) -> Result<Self, VariableUserDataError> {
         let mut records = DataRecords::new();
         let mut offset = 0;
-        let mut _more_records_follow = false;
 
         while offset < data.len() {
             match data[offset] {
-                0x0F => {
-                    /* TODO: parse manufacturer specific */
-                    offset = data.len();
-                }
-                0x1F => {
-                    /* TODO: parse manufacturer specific */
-                    _more_records_follow = true;
+                0x0F | 0x1F => {
+                    let manufacturer_data = &data[offset + 1..];
+                    records.manufacturer_data = Some(manufacturer_data);
+                    records.more_records_follow = data[offset] == 0x1F;
+                    if let Some((header, decoder)) = decoder {
+                        decoder.decode_manufacturer_data(header, manufacturer_data, &mut records);
+                    }
                     offset = data.len();
                 }
                 0x2F => {
                     offset += 1;
                 }
                 _ => {
-                    let record = DataRecord::try_from(&data[offset..])?;
-                    let _ = records.add_record(record);
-                    offset += records.last().unwrap().size;
+                    let record = DataRecord::parse(&data[offset..], options)?;
+                    offset += record.size;
+                    records
+                        .add_record(record)
+                        .map_err(|_| VariableUserDataError::TooManyRecords)?;
+                    if let (Some((header, decoder)), Quantity::ManufacturerSpecific) =
+                        (decoder, record.quantity)
+                    {
+                        decoder.decode_record(header, &record, &mut records);
+                    }
                 }
             }
         }
@@ -205,13 +1022,787 @@ impl TryFrom<&[u8]> for DataRecords {
     }
 }
 
+#[cfg(test)]
 mod tests {
+    extern crate std;
+    use std::string::ToString;
+
+    use crate::user_data::value_information::Unit;
+    use crate::user_data::variable_user_data::{Exponent, Quantity, VariableUserDataError};
+    use crate::user_data::DataRecord;
+
+    fn display(data: &[u8]) -> std::string::String {
+        DataRecord::try_from(data).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_display() {
+        // maximum of storage 1 and tariff 2, 21837 * 10 Wh
+        assert_eq!(
+            display(&[0xD4, 0x20, 0x04, 0x4D, 0x55, 0x00, 0x00]),
+            "Energy 218.37 kWh (storage 1, tariff 2, max)"
+        );
+        assert_eq!(
+            display(&[0x0C, 0x06, 0x35, 0x00, 0x00, 0x00]),
+            "Energy 35 kWh"
+        );
+        assert_eq!(
+            display(&[0x02, 0x5A, 0xEB, 0x02]),
+            "Flow temperature 74.7 °C"
+        );
+        assert_eq!(
+            display(&[0x0C, 0x78, 0x78, 0x56, 0x34, 0x12]),
+            "Identification number 12345678"
+        );
+        assert_eq!(
+            display(&[0x0C, 0x13, 0xFF, 0xFF, 0x00, 0x00]),
+            "Volume 0000FFFF"
+        );
+        assert_eq!(
+            display(&[0x42, 0x6C, 0xBF, 0x1C]),
+            "Time point 2013-12-31 (storage 1)"
+        );
+        assert_eq!(
+            display(&[0x05, 0x3E, 0x00, 0x00, 0x80, 0x3E]),
+            "Volume flow 0.25 m³/h"
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = DataRecord::try_from([0x0C, 0x13, 0xFF].as_slice()).unwrap_err();
+        assert_eq!(
+            VariableUserDataError::from(error).to_string(),
+            "Invalid data information: Data too short"
+        );
+    }
+
+    #[test]
+    fn test_rescaled_decimal_value() {
+        let data = [0xD4, 0x20, 0x04, 0x4D, 0x55, 0x00, 0x00];
+        let record = DataRecord::try_from(data.as_slice()).unwrap();
+        let (value, unit) = record.rescaled_decimal_value().unwrap();
+        assert_eq!(
+            (value.to_string(), unit),
+            ("218.37".to_string(), Unit::KiloWattHour)
+        );
+        /* volumes have no larger unit */
+        let data = [0x0C, 0x15, 0x00, 0x00, 0x10, 0x00];
+        let record = DataRecord::try_from(data.as_slice()).unwrap();
+        let (value, unit) = record.rescaled_decimal_value().unwrap();
+        assert_eq!(
+            (value.to_string(), unit),
+            ("10000.0".to_string(), Unit::CubicMeter)
+        );
+    }
+
+    fn unit_quantity_exponent(data: &[u8]) -> (Unit, Quantity, Exponent) {
+        let record = DataRecord::try_from(data).unwrap();
+        assert_eq!(record.size, data.len());
+        (record.unit, record.quantity, record.exponent)
+    }
+
+    // Records copied from an rscada test frame name the frame and the unit
+    // libmbus printed for them in its XML. The other records are built from
+    // the primary VIF table of EN 13757-3, the comment gives the VIF range.
+    #[test]
+    fn test_primary_vif_energy() {
+        /* EFE_Engelmann-Elster-SensoStar-2: Energy (kWh) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x06, 0x00, 0x00, 0x00, 0x00]),
+            (Unit::WattHour, Quantity::Energy, Exponent::from(3))
+        );
+        /* EMU_EMU-Professional-375-M-Bus: Energy (Wh) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x84, 0x10, 0x03, 0x54, 0x05, 0x00, 0x00]),
+            (Unit::WattHour, Quantity::Energy, Exponent::from(0))
+        );
+        /* sontex_supercal_531_telegram1: Energy (MJ) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x0E, 0x00, 0x00, 0x00, 0x00]),
+            (Unit::Joul, Quantity::Energy, Exponent::from(6))
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_volume_and_mass() {
+        /* ACW_Itron-BM-plus-m: Volume (m m^3) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x13, 0x31, 0xD4, 0x00, 0x00]),
+            (Unit::CubicMeter, Quantity::Volume, Exponent::from(-3))
+        );
+        /* 0x18-0x1F mass in 10^(nnn-3) kg, so 0x1E is t */
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0x1E, 0x01, 0x00]),
+            (Unit::Kilogram, Quantity::Mass, Exponent::from(3))
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_on_and_operating_time() {
+        /* SEN_Pollustat: On time (seconds) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x20, 0x92, 0x22, 0xF1, 0x00]),
+            (Unit::Seconds, Quantity::OnTime, Exponent::from(0))
+        );
+        /* SEN_Pollustat: Operating time (seconds) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x24, 0xA4, 0x1A, 0xE7, 0x00]),
+            (Unit::Seconds, Quantity::OperatingTime, Exponent::from(0))
+        );
+        /* Elster-F2: Operating time (hours) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x26, 0xB1, 0xA1, 0x00, 0x00]),
+            (Unit::Hours, Quantity::OperatingTime, Exponent::from(0))
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_power() {
+        /* EDC: Power (W) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x85, 0x00, 0x2B, 0x00, 0x00, 0x00, 0x00]),
+            (Unit::Watt, Quantity::Power, Exponent::from(0))
+        );
+        /* 0x30-0x37 power in 10^nnn J/h, so 0x33 is kJ/h */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x33, 0x01]),
+            (Unit::JoulPerHour, Quantity::Power, Exponent::from(3))
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_volume_flow() {
+        /* abb_f95: Volume flow (1e-4  m^3/h) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x3B, 0x3A, 0xDD, 0xB4, 0xEB]),
+            (
+                Unit::CubicMeterPerHour,
+                Quantity::VolumeFlow,
+                Exponent::from(-4)
+            )
+        );
+        /* 0x40-0x47 volume flow in 10^(nnn-7) m^3/min and 0x48-0x4F in
+        10^(nnn-9) m^3/s, so 0x44 is l/min and 0x4B ml/s */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x44, 0x01]),
+            (
+                Unit::CubicMeterPerMinute,
+                Quantity::VolumeFlow,
+                Exponent::from(-3)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x4B, 0x01]),
+            (
+                Unit::CubicMeterPerSecond,
+                Quantity::VolumeFlow,
+                Exponent::from(-6)
+            )
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_mass_flow() {
+        /* 0x50-0x57 mass flow in 10^(nnn-3) kg/h, so 0x53 is kg/h */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x53, 0x01]),
+            (Unit::KilogramPerHour, Quantity::MassFlow, Exponent::from(0))
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_temperatures() {
+        /* ELS_Elster-F96-Plus: Flow temperature (1e-1 deg C) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x0A, 0x5A, 0x27, 0x02]),
+            (Unit::Celsius, Quantity::FlowTemperature, Exponent::from(-1))
+        );
+        /* ELS_Elster-F96-Plus: Return temperature (1e-1 deg C) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x0A, 0x5E, 0x26, 0x02]),
+            (
+                Unit::Celsius,
+                Quantity::ReturnTemperature,
+                Exponent::from(-1)
+            )
+        );
+        /* ELS_Elster-F96-Plus: Temperature Difference (1e-1  deg C) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x0A, 0x62, 0x01, 0x00]),
+            (
+                Unit::Kelvin,
+                Quantity::TemperatureDifference,
+                Exponent::from(-1)
+            )
+        );
+        /* tecson: External temperature ( deg C) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x67, 0x09]),
+            (
+                Unit::Celsius,
+                Quantity::ExternalTemperature,
+                Exponent::from(0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_pressure() {
+        /* 0x68-0x6B pressure in 10^(nn-3) bar, so 0x69 is 10 mbar */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x69, 0x64]),
+            (Unit::Bar, Quantity::Pressure, Exponent::from(-2))
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_time_point() {
+        /* ACW_Itron-BM-plus-m: Time Point (date) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x42, 0x6C, 0x00, 0x00]),
+            (
+                Unit::DayMonthYear,
+                Quantity::TimePoint,
+                Exponent { inner: None }
+            )
+        );
+        /* ACW_Itron-BM-plus-m: Time Point (time & date) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0x6D, 0x0B, 0x0B, 0xCD, 0x13]),
+            (
+                Unit::TimePoint,
+                Quantity::TimePoint,
+                Exponent { inner: None }
+            )
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_hca() {
+        /* Elster-F2: Units for H.C.A. */
+        assert_eq!(
+            unit_quantity_exponent(&[0x84, 0x40, 0x6E, 0x00, 0x00, 0x00, 0x00]),
+            (Unit::HCA, Quantity::HCA, Exponent { inner: None })
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_averaging_and_actuality_duration() {
+        /* landis+gyr_ultraheat_t230: Averaging Duration (seconds) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x09, 0x70, 0x08]),
+            (
+                Unit::Seconds,
+                Quantity::AveragingDuration,
+                Exponent::from(0)
+            )
+        );
+        /* landis+gyr_ultraheat_t230: Averaging Duration (minutes) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x89, 0x10, 0x71, 0x07]),
+            (
+                Unit::Minutes,
+                Quantity::AveragingDuration,
+                Exponent::from(0)
+            )
+        );
+        /* landis+gyr_ultraheat_t230: Actuality Duration (seconds) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x09, 0x74, 0x04]),
+            (
+                Unit::Seconds,
+                Quantity::ActualityDuration,
+                Exponent::from(0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_identification() {
+        /* ACW_Itron-BM-plus-m: Fabrication number */
+        assert_eq!(
+            unit_quantity_exponent(&[0x0C, 0x78, 0x78, 0x03, 0x49, 0x11]),
+            (
+                Unit::FabricationNumber,
+                Quantity::IdentificationNumber,
+                Exponent { inner: None }
+            )
+        );
+        /* minol_minocal_wr3: Unknown (VIF=0x79) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x8C, 0x40, 0x79, 0x00, 0x00, 0x00, 0x00]),
+            (
+                Unit::WithoutUnits,
+                Quantity::EnhancedIdentification,
+                Exponent { inner: None }
+            )
+        );
+        /* 0x7A bus address */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x7A, 0x05]),
+            (
+                Unit::WithoutUnits,
+                Quantity::BusAddress,
+                Exponent { inner: None }
+            )
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_reserved() {
+        /* sen_pollutherm: Unknown (VIF=0x7B) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x0C, 0x7B, 0x02, 0x03, 0x00, 0x00]),
+            (Unit::Reserved, Quantity::Reserved, Exponent { inner: None })
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0x6F, 0x00]),
+            (Unit::Reserved, Quantity::Reserved, Exponent { inner: None })
+        );
+    }
+
+    #[test]
+    fn test_primary_vif_mappings_are_total() {
+        use crate::user_data::value_information::ValueInformation;
+        for vif in 0x00..=0xFF {
+            let value_information = ValueInformation::Primary(vif);
+            assert!(Unit::try_from(&value_information).is_ok());
+            let _ = Quantity::from(&value_information);
+            let _ = Exponent::from(&value_information);
+        }
+    }
+
+    #[test]
+    fn test_extended_vif_electrical_units() {
+        /* eastron_sdm630: 1e-2  V */
+        assert_eq!(
+            unit_quantity_exponent(&[0x0B, 0xFD, 0x47, 0x56, 0x34, 0x12]),
+            (Unit::Volt, Quantity::Voltage, Exponent::from(-2))
+        );
+        /* EMU_EMU-Professional-375-M-Bus: m A */
+        assert_eq!(
+            unit_quantity_exponent(&[0x03, 0xFD, 0x59, 0xBE, 0xFF, 0xFF]),
+            (Unit::Ampere, Quantity::Current, Exponent::from(-3))
+        );
+        /* nzr_dhz_5_63: 1e-1  A */
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x5B, 0x00, 0x00]),
+            (Unit::Ampere, Quantity::Current, Exponent::from(-1))
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_currency() {
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x01, 0x10, 0x27]),
+            (Unit::LocalCurrency, Quantity::Credit, Exponent::from(-2))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x07, 0x10, 0x27]),
+            (Unit::LocalCurrency, Quantity::Debit, Exponent::from(0))
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_metadata_records() {
+        use crate::user_data::data_information::{AsciiText, Bcd, DataValue};
+        use crate::user_data::{ManufacturerCode, Medium};
+
+        /* minol_minocal_wr3: Medium (as in fixed header), water */
+        let record = DataRecord::try_from([0x81, 0x40, 0xFD, 0x09, 0x07].as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::Medium);
+        assert_eq!(record.value, DataValue::Medium(Medium::Water));
+
+        let record = DataRecord::try_from([0x02, 0xFD, 0x0A, 0xE6, 0x1E].as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::Manufacturer);
+        assert_eq!(
+            record.value,
+            DataValue::Manufacturer(ManufacturerCode {
+                code: ['G', 'W', 'F']
+            })
+        );
+
+        /* ACW_Itron-BM-plus-m: Firmware version */
+        let record = DataRecord::try_from([0x09, 0xFD, 0x0E, 0x02].as_slice()).unwrap();
+        assert_eq!(
+            (record.unit, record.quantity, record.exponent),
+            (
+                Unit::WithoutUnits,
+                Quantity::FirmwareVersion,
+                Exponent { inner: None }
+            )
+        );
+        assert_eq!(
+            record.value,
+            DataValue::Bcd(Bcd {
+                value: 2,
+                negative: false,
+                invalid_digits: 0
+            })
+        );
+
+        /* siemens_rvd235: Parameter set identification */
+        let data = [0x0D, 0xFD, 0x0B, 0x06, 0x35, 0x33, 0x32, 0x44, 0x56, 0x52];
+        let record = DataRecord::try_from(data.as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::ParameterSetIdentification);
+        assert_eq!(
+            record.value,
+            DataValue::Ascii(AsciiText { bytes: &data[4..] })
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_durations() {
+        /* storage interval in minutes, 0xFD 0x24-0x27 */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFD, 0x25, 0x0F]),
+            (Unit::Minutes, Quantity::StorageInterval, Exponent::from(0))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFD, 0x29, 0x01]),
+            (Unit::Years, Quantity::StorageInterval, Exponent::from(0))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x2E, 0x10, 0x00]),
+            (
+                Unit::Hours,
+                Quantity::DurationSinceLastReadout,
+                Exponent::from(0)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x6D, 0x10, 0x00]),
+            (
+                Unit::Days,
+                Quantity::OperatingTimeBattery,
+                Exponent::from(0)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x3C, 0x08, 0x00]),
+            (
+                Unit::Seconds,
+                Quantity::PeriodOfNominalDataTransmissions,
+                Exponent::from(0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_oms_records() {
+        use crate::user_data::data_information::DataValue;
+        use crate::user_data::date_time::{Date, DaylightSaving, ListeningWindow};
+
+        let record = DataRecord::try_from([0x01, 0xFD, 0x71, 0xC4].as_slice()).unwrap();
+        assert_eq!(
+            (record.unit, record.quantity, record.value),
+            (
+                Unit::DecibelMilliwatt,
+                Quantity::RFLevel,
+                DataValue::Signed(-60)
+            )
+        );
+
+        let data = [0x04, 0xFD, 0x72, 0x1F, 0x43, 0x1F, 0x4A];
+        let record = DataRecord::try_from(data.as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::DaylightSaving);
+        assert_eq!(
+            record.value,
+            DataValue::DaylightSaving(DaylightSaving::from_type_k([0x1F, 0x43, 0x1F, 0x4A]))
+        );
+
+        let data = [0x0D, 0xFD, 0x73, 0x02, 0xFF, 0x00];
+        let record = DataRecord::try_from(data.as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::ListeningWindowManagement);
+        assert_eq!(
+            record.value,
+            DataValue::ListeningWindow(ListeningWindow::from_type_l(&data[4..]))
+        );
+
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFD, 0x74, 0x6D, 0x01]),
+            (
+                Unit::Days,
+                Quantity::RemainingBatteryLifeTime,
+                Exponent::from(0)
+            )
+        );
+
+        let record = DataRecord::try_from([0x01, 0xFD, 0x75, 0x03].as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::NumberOfTimesMeterStopped);
+        assert_eq!(record.value, DataValue::Unsigned(3));
+
+        /* date of the last battery change, type G */
+        let record = DataRecord::try_from([0x02, 0xFD, 0x70, 0xDF, 0x1C].as_slice()).unwrap();
+        assert_eq!(record.quantity, Quantity::TimePoint);
+        assert_eq!(
+            record.value,
+            DataValue::Date(Date {
+                year: 2014,
+                month: 12,
+                day: 31
+            })
+        );
+
+        /* siemens_rvd235: Reserved VIF extension */
+        assert_eq!(
+            unit_quantity_exponent(&[0x81, 0x30, 0xFD, 0x7C, 0x01]),
+            (
+                Unit::WithoutUnits,
+                Quantity::Reserved,
+                Exponent { inner: None }
+            )
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_fb_energy_volume_and_mass() {
+        /* engelmann_sensostar2c: Energy (0.1 MWh) */
+        assert_eq!(
+            unit_quantity_exponent(&[0x04, 0xFB, 0x00, 0x08, 0x00, 0x00, 0x00]),
+            (Unit::MegaWattHour, Quantity::Energy, Exponent::from(-1))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x09, 0x01]),
+            (Unit::GigaJoul, Quantity::Energy, Exponent::from(0))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x0D, 0x01]),
+            (Unit::MegaCalorie, Quantity::Energy, Exponent::from(0))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x11, 0x01]),
+            (Unit::CubicMeter, Quantity::Volume, Exponent::from(3))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x18, 0x01]),
+            (Unit::Tonne, Quantity::Mass, Exponent::from(2))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x1A, 0x01]),
+            (
+                Unit::Percent,
+                Quantity::RelativeHumidity,
+                Exponent::from(-1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_fb_electrical_units() {
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x02, 0x01]),
+            (
+                Unit::KiloVoltAmpereReactiveHour,
+                Quantity::ReactiveEnergy,
+                Exponent::from(0)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x05, 0x01]),
+            (
+                Unit::KiloVoltAmpereHour,
+                Quantity::ApparentEnergy,
+                Exponent::from(1)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x15, 0x01]),
+            (
+                Unit::KiloVoltAmpereReactive,
+                Quantity::ReactivePower,
+                Exponent::from(-2)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x29, 0x01]),
+            (Unit::MegaWatt, Quantity::Power, Exponent::from(0))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x2A, 0x01]),
+            (
+                Unit::Degree,
+                Quantity::PhaseVoltageToVoltage,
+                Exponent::from(-1)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x2D, 0x01]),
+            (Unit::Hertz, Quantity::Frequency, Exponent::from(-2))
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x36, 0x01]),
+            (
+                Unit::KiloVoltAmpere,
+                Quantity::ApparentPower,
+                Exponent::from(-1)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x7B, 0x01]),
+            (
+                Unit::Watt,
+                Quantity::CumulativeCountMaxPower,
+                Exponent::from(0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_fb_imperial_units() {
+        let expected = [
+            (0x20, Unit::CubicFeet, Quantity::Volume, 0),
+            (0x21, Unit::CubicFeet, Quantity::Volume, -1),
+            (0x22, Unit::AmericanGallon, Quantity::Volume, -1),
+            (0x23, Unit::AmericanGallon, Quantity::Volume, 0),
+            (
+                0x24,
+                Unit::AmericanGallonPerMinute,
+                Quantity::VolumeFlow,
+                -3,
+            ),
+            (0x25, Unit::AmericanGallonPerMinute, Quantity::VolumeFlow, 0),
+            (0x26, Unit::AmericanGallonPerHour, Quantity::VolumeFlow, 0),
+        ];
+        for (code, unit, quantity, exponent) in expected {
+            assert_eq!(
+                unit_quantity_exponent(&[0x01, 0xFB, code, 0x01]),
+                (unit, quantity, Exponent::from(exponent))
+            );
+        }
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x27, 0x01]).1,
+            Quantity::Reserved
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_fb_temperatures() {
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFB, 0x5A, 0x9A, 0x02]),
+            (
+                Unit::Fahrenheit,
+                Quantity::FlowTemperature,
+                Exponent::from(-1)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x02, 0xFB, 0x5D, 0x9A, 0x02]),
+            (
+                Unit::Fahrenheit,
+                Quantity::ReturnTemperature,
+                Exponent::from(-2)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x63, 0x01]),
+            (
+                Unit::Fahrenheit,
+                Quantity::TemperatureDifference,
+                Exponent::from(0)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x67, 0x01]),
+            (
+                Unit::Fahrenheit,
+                Quantity::ExternalTemperature,
+                Exponent::from(0)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x71, 0x01]),
+            (
+                Unit::Fahrenheit,
+                Quantity::ColdWarmTemperatureLimit,
+                Exponent::from(-2)
+            )
+        );
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFB, 0x75, 0x01]),
+            (
+                Unit::Celsius,
+                Quantity::ColdWarmTemperatureLimit,
+                Exponent::from(-2)
+            )
+        );
+    }
+
+    #[test]
+    fn test_manufacturer_specific_and_any_vif_records() {
+        use crate::user_data::data_information::DataValue;
+        use crate::user_data::DataRecords;
+        /* a manufacturer specific VIF with VIFEs from EMU_EMU-Professional-375-M-Bus,
+        followed by one without VIFE, an any VIF and a volume */
+        let data = [
+            0x01, 0xFF, 0xE1, 0xFF, 0x01, 0x0D, 0x02, 0x7F, 0x34, 0x12, 0x01, 0x7E, 0x05, 0x03,
+            0x13, 0x15, 0x31, 0x00,
+        ];
+
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        assert_eq!(result.len(), 4);
+
+        let record = result.get(0).unwrap();
+        assert_eq!(record.raw_value_information, &data[1..5]);
+        assert_eq!(record.value, DataValue::Raw(&data[5..6]));
+        assert_eq!(
+            (record.unit, record.quantity, record.exponent),
+            (
+                Unit::WithoutUnits,
+                Quantity::ManufacturerSpecific,
+                Exponent { inner: None }
+            )
+        );
+
+        let record = result.get(1).unwrap();
+        assert_eq!(record.raw_value_information, &data[7..8]);
+        assert_eq!(record.value, DataValue::Raw(&data[8..10]));
+        assert_eq!(record.quantity, Quantity::ManufacturerSpecific);
+
+        let record = result.get(2).unwrap();
+        assert_eq!(record.value, DataValue::Raw(&data[12..13]));
+        assert_eq!(record.quantity, Quantity::Any);
+
+        let record = result.get(3).unwrap();
+        assert_eq!(record.value, DataValue::Signed(12565));
+        assert_eq!(record.quantity, Quantity::Volume);
+    }
+
+    #[test]
+    fn test_manufacturer_specific_vife_is_not_a_correction() {
+        /* 0x73 would be a multiplicative correction after a standard VIF */
+        assert_eq!(
+            unit_quantity_exponent(&[0x01, 0xFF, 0x73, 0x01]),
+            (
+                Unit::WithoutUnits,
+                Quantity::ManufacturerSpecific,
+                Exponent { inner: None }
+            )
+        );
+    }
+
+    #[test]
+    fn test_extended_vif_mappings_are_total() {
+        use crate::user_data::value_information::ValueInformation;
+        for vif in [0xFB, 0xFD] {
+            for code in 0x00..=0xFF {
+                let data = [vif, code];
+                let value_information = ValueInformation::try_from(data.as_slice()).unwrap();
+                assert!(Unit::try_from(&value_information).is_ok());
+                let _ = Quantity::from(&value_information);
+                let _ = Exponent::from(&value_information);
+            }
+        }
+    }
 
     #[test]
     fn test_parse_variable_data() {
         use crate::user_data::variable_user_data::Exponent;
         use crate::user_data::{
-            data_information::FunctionField, value_information::Unit, variable_user_data::Quantity,
+            data_information::{DataValue, FunctionField},
+            value_information::Unit,
+            variable_user_data::Quantity,
             DataRecord, DataRecords,
         };
         /* Data block 1: unit 0, storage No 0, no tariff, instantaneous volume, 12565 l (24 bit integer) */
@@ -224,10 +1815,16 @@ mod tests {
             Some(&DataRecord {
                 function: FunctionField::InstantaneousValue,
                 storage_number: 0,
+                tariff: 0,
+                sub_unit: 0,
                 unit: Unit::CubicMeter,
+                plain_text_unit: None,
+                raw_data_information: &data[..1],
+                raw_value_information: &data[1..2],
+                raw_data: &data[2..],
                 exponent: Exponent::from(-3),
                 quantity: Quantity::Volume,
-                value: 12565.0,
+                value: DataValue::Signed(12565),
                 size: 5,
             })
         );
@@ -237,7 +1834,9 @@ mod tests {
     fn test_parse_variable_data2() {
         use crate::user_data::variable_user_data::Exponent;
         use crate::user_data::{
-            data_information::FunctionField, value_information::Unit, variable_user_data::Quantity,
+            data_information::{DataValue, FunctionField},
+            value_information::Unit,
+            variable_user_data::Quantity,
             DataRecord, DataRecords,
         };
         /* Data block 2: unit 0, storage No 5, no tariff, maximum volume flow, 113 l/h (4 digit BCD) */
@@ -249,10 +1848,16 @@ mod tests {
             Some(&DataRecord {
                 function: FunctionField::InstantaneousValue,
                 storage_number: 0,
+                tariff: 0,
+                sub_unit: 0,
                 unit: Unit::WithoutUnits,
+                plain_text_unit: None,
+                raw_data_information: &data[..1],
+                raw_value_information: &data[1..3],
+                raw_data: &data[3..],
                 exponent: Exponent { inner: None },
                 quantity: Quantity::BinaryDigitalInput,
-                value: 0.0,
+                value: DataValue::Unsigned(0),
                 size: 4,
             })
         );
@@ -263,7 +1868,9 @@ mod tests {
     fn test_parse_variable_data3() {
         use crate::user_data::variable_user_data::Exponent;
         use crate::user_data::{
-            data_information::FunctionField, value_information::Unit, variable_user_data::Quantity,
+            data_information::{AsciiText, DataValue, FunctionField},
+            value_information::Unit,
+            variable_user_data::Quantity,
             DataRecord, DataRecords,
         };
         /* Data block 3: unit 1, storage No 0, tariff 2, instantaneous energy, 218,37 kWh (6 digit BCD) */
@@ -275,15 +1882,182 @@ mod tests {
             Some(&DataRecord {
                 function: FunctionField::InstantaneousValue,
                 storage_number: 0,
+                tariff: 0,
+                sub_unit: 0,
                 unit: Unit::PlainText,
+                plain_text_unit: Some(AsciiText { bytes: &data[3..6] }),
+                raw_data_information: &data[..1],
+                raw_value_information: &data[1..7],
+                raw_data: &data[7..],
                 exponent: Exponent::from(-2),
                 quantity: Quantity::PlainText,
-                value: 33.96,
+                value: DataValue::Signed(3396),
                 size: 9,
             })
         );
     }
 
+    #[test]
+    fn test_parse_date_records() {
+        use crate::user_data::data_information::DataValue;
+        use crate::user_data::date_time::{Date, DateTime};
+        use crate::user_data::DataRecords;
+        /* EFE_Engelmann-Elster-SensoStar-2: time point (date & time) and time point (date) */
+        let data = &[0x04, 0x6D, 0x17, 0x2E, 0xCC, 0x13, 0x02, 0x6C, 0xDF, 0x1C];
+
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        assert_eq!(
+            result.get(0).unwrap().value,
+            DataValue::DateTime(DateTime::from_type_f([0x17, 0x2E, 0xCC, 0x13]))
+        );
+        assert_eq!(
+            result.get(1).unwrap().value,
+            DataValue::Date(Date {
+                year: 2014,
+                month: 12,
+                day: 31
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_time_point_data_types() {
+        use crate::user_data::data_information::DataValue;
+        use crate::user_data::date_time::{DateTime, Time};
+        use crate::user_data::DataRecords;
+        /* VIF 0x6D with a 24 bit (type J) and a 48 bit (type I) data field */
+        let data = &[
+            0x03, 0x6D, 0x2D, 0x1E, 0x08, 0x06, 0x6D, 0x2D, 0x5E, 0x08, 0xAF, 0x26, 0x18,
+        ];
+
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        assert_eq!(
+            result.get(0).unwrap().value,
+            DataValue::Time(Time {
+                hour: 8,
+                minute: 30,
+                second: 45
+            })
+        );
+        assert_eq!(
+            result.get(1).unwrap().value,
+            DataValue::DateTime(DateTime::from_type_i([0x2D, 0x5E, 0x08, 0xAF, 0x26, 0x18]))
+        );
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_signed_and_unsigned_records() {
+        use crate::user_data::data_information::DataValue;
+        use crate::user_data::DataRecords;
+        /* negative power of -200 W followed by digital input 0xFF38 */
+        let data = &[0x02, 0x2B, 0x38, 0xFF, 0x02, 0xFD, 0x1B, 0x38, 0xFF];
+
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        assert_eq!(result.get(0).unwrap().value, DataValue::Signed(-200));
+        assert_eq!(result.get(1).unwrap().value, DataValue::Unsigned(0xFF38));
+    }
+
+    #[test]
+    fn test_parse_variable_length_records() {
+        use crate::user_data::data_information::DataValue;
+        use crate::user_data::DataRecords;
+        /* fabrication number "12345678" as LVAR ASCII followed by a 24 bit volume */
+        let data = &[
+            0x0D, 0x78, 0x08, b'8', b'7', b'6', b'5', b'4', b'3', b'2', b'1', 0x03, 0x13, 0x15,
+            0x31, 0x00,
+        ];
+
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        assert_eq!(result.len(), 2);
+        let record = result.get(0).unwrap();
+        assert_eq!(record.size, 11);
+        match record.value {
+            DataValue::Ascii(text) => assert!(text.chars().eq("12345678".chars())),
+            _ => panic!("expected ascii text, got {:?}", record.value),
+        }
+        assert_eq!(result.get(1).unwrap().value, DataValue::Signed(12565));
+    }
+
+    #[test]
+    fn test_scaled_and_converted_values() {
+        use crate::user_data::unit_conversion::{BaseUnit, UnitConversionError};
+        use crate::user_data::DataRecords;
+        /* 21837 * 10^2 Wh (8 digit BCD), flow temperature 2000 * 10^-2 °C,
+        temperature difference 1000 * 10^-2 K and a date */
+        let data = &[
+            0x0C, 0x05, 0x37, 0x18, 0x02, 0x00, 0x02, 0x59, 0xD0, 0x07, 0x02, 0x61, 0xE8, 0x03,
+            0x02, 0x6C, 0x61, 0x25,
+        ];
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+
+        let energy = result.get(0).unwrap();
+        assert_eq!(energy.scaled_value(), Some(2183700.0));
+        assert_eq!(energy.value_in(Unit::KiloWattHour), Ok(2183.7));
+        assert_eq!(energy.base_value(), Ok((7861320000.0, BaseUnit::Joule)));
+        assert_eq!(
+            energy.value_in(Unit::CubicMeter),
+            Err(UnitConversionError::IncompatibleUnits {
+                from: Unit::WattHour,
+                to: Unit::CubicMeter
+            })
+        );
+
+        let flow_temperature = result.get(1).unwrap();
+        assert_eq!(flow_temperature.scaled_value(), Some(20.0));
+        let fahrenheit = flow_temperature.value_in(Unit::Fahrenheit).unwrap();
+        assert!((fahrenheit - 68.0).abs() < 1e-9);
+
+        let temperature_difference = result.get(2).unwrap();
+        assert_eq!(temperature_difference.value_in(Unit::Fahrenheit), Ok(18.0));
+        assert_eq!(
+            temperature_difference.base_value(),
+            Ok((10.0, BaseUnit::Kelvin))
+        );
+
+        let date = result.get(3).unwrap();
+        assert_eq!(date.scaled_value(), None);
+        assert_eq!(
+            date.value_in(Unit::Seconds),
+            Err(UnitConversionError::NonNumericValue)
+        );
+    }
+
+    #[test]
+    fn test_decimal_values() {
+        use crate::user_data::decimal::{Decimal, DecimalError};
+        use crate::user_data::DataRecords;
+        /* 21837 * 10^1 Wh (8 digit BCD), 21838 * 10^1 Wh (32 bit integer),
+        2 * 10^3 Wh (8 bit integer), volume 1 * 10^-3 m³ and a real energy */
+        let data = &[
+            0x0C, 0x04, 0x37, 0x18, 0x02, 0x00, 0x04, 0x04, 0x4E, 0x55, 0x00, 0x00, 0x01, 0x06,
+            0x02, 0x01, 0x13, 0x01, 0x05, 0x04, 0x00, 0x00, 0x80, 0x3F,
+        ];
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        let (bcd, integer, kilo, volume, real) = (
+            result.get(0).unwrap(),
+            result.get(1).unwrap(),
+            result.get(2).unwrap(),
+            result.get(3).unwrap(),
+            result.get(4).unwrap(),
+        );
+
+        assert_eq!(bcd.decimal_value(), Some(Decimal::new(21837, 1)));
+        assert_eq!(integer.checked_sub(bcd), Ok(Decimal::new(10, 0)));
+        let sum = bcd.checked_add(kilo).unwrap();
+        assert_eq!((sum.mantissa, sum.exponent), (22037, 1));
+        assert_eq!(real.decimal_value(), None);
+        assert_eq!(bcd.checked_add(volume), Err(DecimalError::DifferentUnits));
+        assert_eq!(bcd.checked_add(real), Err(DecimalError::NonDecimalValue));
+
+        /* flow temperature 74.7 °C and return temperature 52.8 °C share the unit */
+        let data = &[0x02, 0x5A, 0xEB, 0x02, 0x02, 0x5E, 0x10, 0x02];
+        let result = DataRecords::try_from(data.as_slice()).unwrap();
+        let (flow, return_) = (result.get(0).unwrap(), result.get(1).unwrap());
+        assert_eq!(flow.unit, return_.unit);
+        assert_eq!(flow.checked_sub(return_), Err(DecimalError::DifferentUnits));
+    }
+
     fn _test_parse_variable_data2() {
         /* Data block 2: unit 0, storage No 5, no tariff, maximum volume flow, 113 l/h (4 digit BCD) */
         let _data = &[0xDA, 0x02, 0x3B, 0x13, 0x01];
@@ -0,0 +1,180 @@
+//! WebAssembly bindings for decoding frames in the browser, built with
+//! `wasm-pack build --target web --features wasm`.
+//!
+//! `decode` takes a frame as hex, with or without whitespace, and returns an
+//! object with the link layer, the fixed data header and the data records of
+//! the frame, and its annotated byte spans as in [`crate::dissection`].
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::dissection::{Dissection, Span};
+use crate::frames::{Frame, FrameError};
+use crate::user_data::{DataRecords, FixedDataHeader, UserDataBlock};
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    InvalidHex,
+    Frame(FrameError),
+}
+
+/// A span of the dissection with its layer and meaning as text
+#[derive(Debug, Serialize)]
+pub struct DecodedSpan<'a> {
+    pub offset: usize,
+    pub length: usize,
+    pub layer: String,
+    pub field: &'static str,
+    #[serde(serialize_with = "crate::serde_helpers::hex")]
+    pub bytes: &'a [u8],
+    pub meaning: String,
+}
+
+impl<'a> From<&Span<'a>> for DecodedSpan<'a> {
+    fn from(span: &Span<'a>) -> Self {
+        DecodedSpan {
+            offset: span.offset,
+            length: span.length,
+            layer: span.layer.to_string(),
+            field: span.field,
+            bytes: span.bytes,
+            meaning: span.meaning.to_string(),
+        }
+    }
+}
+
+/// The object returned by `decode`. `header` and `records` are only set for
+/// a variable data structure, `records` only if all of them could be decoded;
+/// the spans show where decoding stopped otherwise.
+#[derive(Debug, Serialize)]
+pub struct Decoded<'a> {
+    pub frame: Frame<'a>,
+    pub header: Option<FixedDataHeader>,
+    pub records: Option<DataRecords<'a>>,
+    pub spans: Vec<DecodedSpan<'a>>,
+}
+
+impl<'a> Decoded<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, DecodeError> {
+        let frame = Frame::try_from(data).map_err(DecodeError::Frame)?;
+        let dissection = Dissection::try_from(data).map_err(DecodeError::Frame)?;
+        let (header, records) = match &frame {
+            Frame::LongFrame { data, .. } | Frame::ControlFrame { data, .. } => {
+                match UserDataBlock::try_from(*data) {
+                    Ok(UserDataBlock::VariableDataStructure {
+                        fixed_data_header,
+                        variable_data_block,
+                    }) => (
+                        Some(fixed_data_header),
+                        DataRecords::try_from(variable_data_block).ok(),
+                    ),
+                    _ => (None, None),
+                }
+            }
+            _ => (None, None),
+        };
+        Ok(Decoded {
+            frame,
+            header,
+            records,
+            spans: dissection.iter().map(DecodedSpan::from).collect(),
+        })
+    }
+}
+
+/// Decodes a frame given as hex, whitespace between the digits is ignored
+#[wasm_bindgen]
+pub fn decode(hex: &str) -> Result<JsValue, JsError> {
+    let data = parse_hex(hex).map_err(|error| JsError::new(&message(&error)))?;
+    let decoded = Decoded::parse(&data).map_err(|error| JsError::new(&message(&error)))?;
+    // plain objects and null instead of Maps and undefined, as with JSON.parse
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    decoded
+        .serialize(&serializer)
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+fn message(error: &DecodeError) -> String {
+    match error {
+        DecodeError::InvalidHex => "invalid hex input".to_string(),
+        DecodeError::Frame(error) => format!("invalid frame: {}", error),
+    }
+}
+
+pub fn parse_hex(hex: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits: Vec<u8> = hex
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()
+        .ok_or(DecodeError::InvalidHex)?;
+    if digits.len() % 2 != 0 {
+        return Err(DecodeError::InvalidHex);
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(
+            parse_hex("68 03\n03 68"),
+            Ok(alloc::vec![0x68, 0x03, 0x03, 0x68])
+        );
+        assert_eq!(parse_hex("e5"), Ok(alloc::vec![0xE5]));
+        assert_eq!(parse_hex("E"), Err(DecodeError::InvalidHex));
+        assert_eq!(parse_hex("0x10"), Err(DecodeError::InvalidHex));
+    }
+
+    #[test]
+    fn test_decode_frame() {
+        let data = parse_hex("10 40 01 41 16").unwrap();
+        let decoded = Decoded::parse(&data).unwrap();
+        assert!(decoded.header.is_none());
+        assert!(decoded.records.is_none());
+        let fields: Vec<&str> = decoded.spans.iter().map(|span| span.field).collect();
+        assert_eq!(fields, ["start", "C field", "A field", "checksum", "stop"]);
+        assert_eq!(decoded.spans[3].bytes, [0x41]);
+
+        let data = parse_hex("10 40 01 42 16").unwrap();
+        let error = Decoded::parse(&data).unwrap_err();
+        assert!(matches!(
+            error,
+            DecodeError::Frame(FrameError::WrongChecksum { .. })
+        ));
+        assert!(message(&error).starts_with("invalid frame: Wrong checksum"));
+    }
+
+    #[test]
+    fn test_decode_variable_data_structure() {
+        let data = parse_hex(
+            "68 1F 1F 68 08 02 72 78 56 34 12 24 40 01 07 55 00 00 00 03 13 15 31 00 \
+             DA 02 3B 13 01 8B 60 04 37 18 02 18 16",
+        )
+        .unwrap();
+        let decoded = Decoded::parse(&data).unwrap();
+        let json = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(json["header"]["identification_number"], "12345678");
+        assert_eq!(json["records"]["records"].as_array().unwrap().len(), 3);
+        let span = &json["spans"][6];
+        assert_eq!(span["offset"], 6);
+        assert_eq!(span["bytes"], "72");
+        assert_eq!(span["layer"], "application");
+        assert_eq!(
+            decoded.spans.iter().map(|span| span.length).sum::<usize>(),
+            data.len()
+        );
+    }
+}
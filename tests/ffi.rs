@@ -0,0 +1,72 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const HEADER: &str = "include/m_bus_parser.h";
+
+/// Regenerates the C header and fails if the checked in one was outdated
+#[test]
+fn test_header() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let config = cbindgen::Config::from_file(Path::new(root).join("cbindgen.toml")).unwrap();
+    let mut header = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(root)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut header);
+
+    let path = Path::new(root).join(HEADER);
+    if fs::read(&path).ok().as_ref() != Some(&header) {
+        fs::write(&path, &header).unwrap();
+        panic!("{} was outdated and has been regenerated", HEADER);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_c_example() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // cargo test only builds the rlib, so the static library is built into a
+    // target directory of its own which does not block the running cargo
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    let build = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--offline", "--manifest-path"])
+        .arg(root.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "{}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+    let executable = Path::new(env!("CARGO_TARGET_TMPDIR")).join("parse");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let status = Command::new(compiler)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&executable)
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(root.join("examples/c/parse.c"))
+        .arg(target.join("debug/libm_bus_parser.a"))
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(&executable).output().unwrap();
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+    let mut lines = output.lines();
+    assert_eq!(
+        lines.next(),
+        Some("address 1, identification number 00000001, manufacturer ELV")
+    );
+    assert_eq!(lines.next_back(), Some("more records follow: yes"));
+    assert!(output.contains("unit %RH, value 33.96"));
+}
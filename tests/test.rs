@@ -1,4 +1,3 @@
-use hex;
 use m_bus_parser::user_data::Medium;
 use serde::Deserialize;
 use serde_xml_rs::from_str;
@@ -102,7 +101,7 @@ mod tests {
         for entry in WalkDir::new("./tests/rscada/test-frames")
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "hex"))
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "hex"))
         {
             let contents =
                 fs::read_to_string(entry.path()).expect("Something went wrong reading the file");
@@ -181,4 +180,191 @@ mod tests {
             }
         }
     }
+
+    // Every frame of the corpus is written as both files libmbus produced
+    // for it
+    #[test]
+    fn test_libmbus_xml() {
+        use m_bus_parser::user_data::libmbus_xml::{write_user_data_xml, XmlFormat};
+
+        let mut mismatches = Vec::new();
+        for entry in WalkDir::new("./tests/rscada/test-frames")
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "hex"))
+        {
+            let contents = fs::read_to_string(entry.path()).unwrap();
+            let bytes = hex::decode(contents.trim().replace(' ', "")).unwrap();
+            let Frame::LongFrame { data, .. } = Frame::try_from(bytes.as_slice()).unwrap() else {
+                panic!("Frame is not a long frame");
+            };
+            let user_data = UserDataBlock::try_from(data).unwrap();
+
+            for (format, extension) in [
+                (XmlFormat::Plain, "xml"),
+                (XmlFormat::Normalized, "norm.xml"),
+            ] {
+                let path = entry.path().with_extension(extension);
+                let expected = fs::read_to_string(&path).unwrap();
+                let mut xml = String::new();
+                write_user_data_xml(&mut xml, &user_data, format).unwrap();
+                if let Some((line, (expected, actual))) = expected
+                    .lines()
+                    .zip(xml.lines())
+                    .enumerate()
+                    .find(|(_, (expected, actual))| expected != actual)
+                {
+                    mismatches.push(format!(
+                        "{}:{}\n  expected: {}\n  actual:   {}",
+                        path.display(),
+                        line + 1,
+                        expected,
+                        actual
+                    ));
+                } else if expected != xml {
+                    mismatches.push(format!("{}: length differs", path.display()));
+                }
+            }
+        }
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    }
+
+    // The spans of every frame of the corpus cover all its bytes in order,
+    // with one layer per data record
+    #[test]
+    fn test_dissection() {
+        use m_bus_parser::dissection::{Dissection, Layer, Meaning};
+
+        for entry in WalkDir::new("./tests/rscada/test-frames")
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "hex"))
+        {
+            let contents = fs::read_to_string(entry.path()).unwrap();
+            let bytes = hex::decode(contents.trim().replace(' ', "")).unwrap();
+            let dissection = Dissection::try_from(bytes.as_slice()).unwrap();
+
+            let mut offset = 0;
+            for span in dissection.iter() {
+                assert_eq!(span.offset, offset, "{}", entry.path().display());
+                assert_eq!(span.bytes, &bytes[offset..offset + span.length]);
+                assert!(!matches!(span.meaning, Meaning::Undecoded(_)));
+                offset += span.length;
+            }
+            assert_eq!(offset, bytes.len());
+
+            let Frame::LongFrame { data, .. } = Frame::try_from(bytes.as_slice()).unwrap() else {
+                panic!("Frame is not a long frame");
+            };
+            let Ok(UserDataBlock::VariableDataStructure {
+                variable_data_block,
+                ..
+            }) = UserDataBlock::try_from(data)
+            else {
+                continue;
+            };
+            let records =
+                m_bus_parser::user_data::DataRecords::try_from(variable_data_block).unwrap();
+            let last_record = dissection.iter().rev().find_map(|span| match span.layer {
+                Layer::Record(index) => Some(index),
+                _ => None,
+            });
+            assert_eq!(
+                last_record.map_or(0, |index| index + 1),
+                records.len(),
+                "{}",
+                entry.path().display()
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use m_bus_parser::frames::Frame;
+        use m_bus_parser::user_data::{FixedDataHeader, UserDataBlock};
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn test_serialize_user_data() {
+            let contents =
+                fs::read_to_string("./tests/rscada/test-frames/ELS_Elster-F96-Plus.hex").unwrap();
+            let bytes = hex::decode(contents.trim().replace(' ', "")).unwrap();
+            let frame = Frame::try_from(bytes.as_slice()).unwrap();
+            let Frame::LongFrame { data, .. } = frame else {
+                panic!("Frame is not a long frame");
+            };
+            let user_data = UserDataBlock::try_from(data).unwrap();
+
+            let frame = serde_json::to_value(&frame).unwrap();
+            assert_eq!(frame["LongFrame"]["address"], "Uninitalized");
+            assert!(frame["LongFrame"]["data"]
+                .as_str()
+                .unwrap()
+                .starts_with("725139494493152F04A170"));
+
+            let user_data = serde_json::to_value(&user_data).unwrap();
+            let header = &user_data["VariableDataStructure"]["fixed_data_header"];
+            assert_eq!(
+                header,
+                &json!({
+                    "identification_number": "44493951",
+                    "manufacturer": "ELS",
+                    "version": 47,
+                    "medium": "Heat",
+                    "access_number": 161,
+                    "status": "TEMPORARY_ERROR | MANUFACTURER_SPECIFIC_1 | MANUFACTURER_SPECIFIC_2",
+                    "signature": 0,
+                })
+            );
+            let header: FixedDataHeader = serde_json::from_value(header.clone()).unwrap();
+            assert_eq!(header.manufacturer.code, ['E', 'L', 'S']);
+            assert_eq!(u32::from(header.identification_number), 44493951);
+
+            let records = &user_data["VariableDataStructure"]["data_records"]["records"];
+            assert_eq!(
+                records[0],
+                json!({
+                    "function": "InstantaneousValue",
+                    "storage_number": 0,
+                    "tariff": 0,
+                    "sub_unit": 0,
+                    "quantity": "Energy",
+                    "value": "0",
+                    "unit": "Wh",
+                    "plain_text_unit": null,
+                    "size": 6,
+                })
+            );
+            /* meters send invalid BCD digits while they can not measure */
+            assert_eq!(records[4]["value"], "DDDDEBBD");
+            assert_eq!(records[6]["value"], "22.7");
+            assert_eq!(records[6]["unit"], "°C");
+            assert_eq!(
+                records.as_array().unwrap().last().unwrap()["value"],
+                json!({ "Date": { "year": 2013, "month": 5, "day": 31 } })
+            );
+        }
+
+        #[test]
+        fn test_reject_invalid_strings() {
+            let header = |identification_number: &str, manufacturer: &str| {
+                serde_json::from_value::<FixedDataHeader>(json!({
+                    "identification_number": identification_number,
+                    "manufacturer": manufacturer,
+                    "version": 1,
+                    "medium": "Water",
+                    "access_number": 0,
+                    "status": "",
+                    "signature": 0,
+                }))
+            };
+            assert!(header("00001234", "ABC").is_ok());
+            assert!(header("1234", "ABC").is_err());
+            assert!(header("0000123A", "ABC").is_err());
+            assert!(header("00001234", "AB").is_err());
+            assert!(header("00001234", "abc").is_err());
+        }
+    }
 }